@@ -0,0 +1,20 @@
+#!/bin/sh
+# Downloads frozen copies of well-known JSON-LD contexts into this directory.
+#
+# Review the diff of the downloaded files before committing them: documents signed with
+# these contexts depend on their exact content.
+set -eu
+
+cd "$(dirname "$0")"
+
+fetch() {
+    curl -fsSL -H 'Accept: application/ld+json' -o "$1" "$2"
+}
+
+fetch activitystreams.jsonld 'https://www.w3.org/ns/activitystreams'
+fetch schema-org.jsonld 'https://schema.org/docs/jsonldcontext.jsonld'
+fetch credentials-v1.jsonld 'https://www.w3.org/2018/credentials/v1'
+fetch credentials-v2.jsonld 'https://www.w3.org/ns/credentials/v2'
+fetch security-v1.jsonld 'https://w3id.org/security/v1'
+fetch security-v2.jsonld 'https://w3id.org/security/v2'
+fetch did-v1.jsonld 'https://www.w3.org/ns/did/v1'
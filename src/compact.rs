@@ -0,0 +1,279 @@
+//! Compaction algorithms.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#compaction-algorithms>.
+
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    context::Context, error::Result, expand, json::single_entry_map, processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+pub(crate) use self::{inverse::InverseContext, iri::CompactIriOptions};
+
+use self::element::compact_element;
+
+mod element;
+pub(crate) mod inverse;
+mod iri;
+mod value;
+
+/// Compacts the given document.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-compact>.
+pub(crate) async fn compact<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+    context: &Value,
+) -> Result<Value> {
+    // Step 2-6
+    let expanded_input = expand::expand(processor, input).await?;
+    // Step 7-13
+    compact_expanded(processor, &expanded_input, context, false).await
+}
+
+/// Compacts the given expanded document.
+///
+/// If `force_graph` is true, the result always has the `@graph` entry (or its alias), even if
+/// the document is empty or has only one node.
+/// This is used to make the structure of the result of flattening deterministic.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-compact>.
+pub(crate) async fn compact_expanded<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    expanded_input: &Value,
+    context: &Value,
+    force_graph: bool,
+) -> Result<Value> {
+    // Step 7
+    let context = match context {
+        Value::Object(map) if map.contains_key("@context") => &map["@context"],
+        v => v,
+    };
+    // Step 8-10
+    let base_url = processor.options().document_iri();
+    let active_context = Context::with_base(base_url.to_owned())
+        .join_context_value(processor, context, base_url, false)
+        .await?;
+    let inverse_context = InverseContext::new(&active_context);
+    // Step 11
+    let compacted_output = compact_element(
+        processor,
+        &active_context,
+        &inverse_context,
+        None,
+        expanded_input,
+    )
+    .await?;
+    let compacted_output = match compacted_output {
+        Value::Object(map) if force_graph => Value::Array(vec![Value::Object(map)]),
+        v => v,
+    };
+    let mut compacted_output = match compacted_output {
+        // Step 12.1
+        Value::Array(arr) if arr.is_empty() && !force_graph => JsonMap::new(),
+        // Step 12.2
+        Value::Array(arr) => {
+            let alias = CompactIriOptions::new(&active_context, &inverse_context)
+                .vocab(true)
+                .compact(processor, "@graph")?;
+            single_entry_map(alias, arr)
+        }
+        Value::Object(map) => map,
+        v => unreachable!(
+            "Should never fail: compaction of expanded document results in an array or a map, \
+             but got {:?}",
+            v
+        ),
+    };
+    // Step 12.3
+    if let Some(context) = non_empty_context(context) {
+        compacted_output.insert("@context".into(), context);
+    }
+
+    // Step 13
+    Ok(Value::Object(compacted_output))
+}
+
+/// Returns the context without empty maps, or `None` if nothing remains.
+fn non_empty_context(context: &Value) -> Option<Value> {
+    let is_empty_map = |v: &Value| v.as_object().is_some_and(JsonMap::is_empty);
+    match context {
+        Value::Null => None,
+        Value::Array(arr) => {
+            let mut arr = arr
+                .iter()
+                .filter(|v| !is_empty_map(v))
+                .cloned()
+                .collect::<Vec<_>>();
+            match arr.len() {
+                0 => None,
+                1 => arr.pop(),
+                _ => Some(Value::Array(arr)),
+            }
+        }
+        v if is_empty_map(v) => None,
+        v => Some(v.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iri_string::types::IriStr;
+    use serde_json::{json, Value};
+
+    use crate::{
+        test_utils::{block_on, processor, NoLoader, BASE},
+        ProcessorOptions,
+    };
+
+    /// Compacts the input with the context.
+    fn compact(input: Value, context: Value) -> Value {
+        block_on(processor().compact(&input, &context)).expect("Should never fail: valid input")
+    }
+
+    #[test]
+    fn compact_node_object() {
+        let context = json!({
+            "@vocab": "http://example.org/",
+            "knows": { "@type": "@id" },
+            "date": { "@type": "http://www.w3.org/2001/XMLSchema#date" }
+        });
+        let input = json!({
+            "@id": "http://example.com/alice",
+            "@type": "http://example.org/Person",
+            "http://example.org/name": "Alice",
+            "http://example.org/knows": { "@id": "http://example.com/bob" },
+            "http://example.org/date": {
+                "@value": "2019-11-12",
+                "@type": "http://www.w3.org/2001/XMLSchema#date"
+            },
+            "http://example.org/empty": []
+        });
+        assert_eq!(
+            compact(input, json!({ "@context": context.clone() })),
+            json!({
+                "@context": context,
+                "@id": "alice",
+                "@type": "Person",
+                "name": "Alice",
+                "knows": "bob",
+                "date": "2019-11-12",
+                "empty": []
+            })
+        );
+    }
+
+    #[test]
+    fn compact_containers() {
+        let context = json!({
+            "@vocab": "http://example.org/",
+            "label": { "@container": "@language" },
+            "list": { "@container": "@list" },
+            "byIndex": { "@id": "http://example.org/indexed", "@container": "@index" },
+            "byId": { "@id": "http://example.org/identified", "@container": "@id" },
+            "byType": { "@id": "http://example.org/typed", "@container": "@type" },
+            "graph": { "@container": "@graph" },
+            "meta": "@nest",
+            "nested": { "@nest": "meta" }
+        });
+        let input = json!({
+            "http://example.org/label": [
+                { "@value": "Hello", "@language": "en" },
+                { "@value": "Bonjour", "@language": "fr" }
+            ],
+            "http://example.org/list": { "@list": [1, { "@list": [2] }] },
+            "http://example.org/indexed": { "@id": "http://example.org/a", "@index": "first" },
+            "http://example.org/identified": {
+                "@id": "http://example.org/b",
+                "http://example.org/n": 1
+            },
+            "http://example.org/typed": {
+                "@id": "http://example.org/c",
+                "@type": "http://example.org/T"
+            },
+            "http://example.org/graph": { "@graph": { "http://example.org/n": 2 } },
+            "http://example.org/nested": "x"
+        });
+        assert_eq!(
+            compact(input, context.clone()),
+            json!({
+                "@context": context,
+                "label": { "en": "Hello", "fr": "Bonjour" },
+                "list": [1, [2]],
+                "byIndex": { "first": { "@id": "http://example.org/a" } },
+                "byId": { "http://example.org/b": { "n": 1 } },
+                "byType": { "T": "http://example.org/c" },
+                "graph": { "n": 2 },
+                "meta": { "nested": "x" }
+            })
+        );
+    }
+
+    #[test]
+    fn compact_reverse_and_graph() {
+        let context = json!({
+            "@vocab": "http://example.org/",
+            "children": { "@reverse": "http://example.org/parent", "@type": "@id" }
+        });
+        let input = json!([
+            {
+                "@id": "http://example.org/p",
+                "@reverse": { "http://example.org/parent": { "@id": "http://example.org/c" } }
+            },
+            { "@id": "http://example.org/q", "http://example.org/n": 1 }
+        ]);
+        assert_eq!(
+            compact(input, context.clone()),
+            json!({
+                "@context": context,
+                "@graph": [
+                    { "@id": "http://example.org/p", "children": "http://example.org/c" },
+                    { "@id": "http://example.org/q", "n": 1 }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn compact_empty() {
+        assert_eq!(compact(json!([]), json!({})), json!({}));
+        assert_eq!(
+            compact(json!({ "http://example.org/p": 1 }), Value::Null),
+            json!({ "http://example.org/p": 1 })
+        );
+    }
+
+    #[test]
+    fn compact_with_options() {
+        let input = json!({
+            "@id": "http://example.com/alice",
+            "http://example.org/knows": { "@id": "http://example.com/bob" }
+        });
+        let context = json!({ "@vocab": "http://example.org/" });
+        let base = IriStr::new(BASE)
+            .expect("Should never fail: valid IRI")
+            .to_owned();
+
+        let processor = ProcessorOptions::builder(base.clone()).processor(NoLoader);
+        assert_eq!(
+            block_on(processor.compact(&input, &context)).unwrap(),
+            json!({ "@context": context, "@id": "alice", "knows": { "@id": "bob" } })
+        );
+
+        let processor = ProcessorOptions::builder(base)
+            .compact_arrays(false)
+            .compact_to_relative(false)
+            .processor(NoLoader);
+        assert_eq!(
+            block_on(processor.compact(&input, &context)).unwrap(),
+            json!({
+                "@context": context,
+                "@graph": [{
+                    "@id": "http://example.com/alice",
+                    "knows": [{ "@id": "http://example.com/bob" }]
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Compaction algorithm.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#compaction-algorithm>.
+
+use std::{future::Future, pin::Pin};
+
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    compact::{inverse::InverseContext, iri::CompactIriOptions, value::compact_value},
+    context::{ContainerItem, Context, Definition},
+    error::Result,
+    json::to_ref_array,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+use self::entries::{compact_entries, EntriesEnv};
+
+mod entries;
+
+/// Active context which may be modified during compaction of a map.
+struct ActiveContext<'a> {
+    /// Original active context and its inverse context.
+    original: (&'a Context, &'a InverseContext),
+    /// Modified active context and its inverse context.
+    modified: Option<(Context, InverseContext)>,
+}
+
+impl<'a> ActiveContext<'a> {
+    /// Creates a new `ActiveContext`.
+    fn new(active_context: &'a Context, inverse_context: &'a InverseContext) -> Self {
+        Self {
+            original: (active_context, inverse_context),
+            modified: None,
+        }
+    }
+
+    /// Returns the active context.
+    fn context(&self) -> &Context {
+        match &self.modified {
+            Some((context, _)) => context,
+            None => self.original.0,
+        }
+    }
+
+    /// Returns the inverse context of the active context.
+    fn inverse(&self) -> &InverseContext {
+        match &self.modified {
+            Some((_, inverse)) => inverse,
+            None => self.original.1,
+        }
+    }
+
+    /// Replaces the active context.
+    fn set(&mut self, context: Context) {
+        let inverse = InverseContext::new(&context);
+        self.modified = Some((context, inverse));
+    }
+}
+
+/// Runs compaction algorithm.
+///
+/// `inverse_context` should be created from `active_context`.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#compaction-algorithm>.
+pub(crate) fn compact_element<'a, L: LoadRemoteDocument>(
+    processor: &'a Processor<L>,
+    active_context: &'a Context,
+    inverse_context: &'a InverseContext,
+    active_property: Option<&'a str>,
+    element: &'a Value,
+) -> Pin<Box<dyn Future<Output = Result<Value>> + 'a + Send>> {
+    Box::pin(async move {
+        compact_element_impl(
+            processor,
+            active_context,
+            inverse_context,
+            active_property,
+            element,
+        )
+        .await
+    })
+}
+
+/// Runs compaction algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#compaction-algorithm>.
+async fn compact_element_impl<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    inverse_context: &InverseContext,
+    active_property: Option<&str>,
+    element: &Value,
+) -> Result<Value> {
+    match element {
+        // Step 3
+        Value::Array(element) => {
+            compact_array(
+                processor,
+                active_context,
+                inverse_context,
+                active_property,
+                element,
+            )
+            .await
+        }
+        // Step 4
+        Value::Object(element) => {
+            compact_map(
+                processor,
+                active_context,
+                inverse_context,
+                active_property,
+                element,
+            )
+            .await
+        }
+        // Step 2
+        v => Ok(v.clone()),
+    }
+}
+
+/// Compacts an array.
+// Step 3
+async fn compact_array<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    inverse_context: &InverseContext,
+    active_property: Option<&str>,
+    element: &[Value],
+) -> Result<Value> {
+    // Step 3.1
+    let mut result = Vec::new();
+    // Step 3.2
+    for item in element {
+        // Step 3.2.1
+        let compacted_item = compact_element(
+            processor,
+            active_context,
+            inverse_context,
+            active_property,
+            item,
+        )
+        .await?;
+        // Step 3.2.2
+        if !compacted_item.is_null() {
+            result.push(compacted_item);
+        }
+    }
+    // Step 3.3
+    let is_list_or_set_container = active_property
+        .and_then(|prop| active_context.term_definition(prop))
+        .is_some_and(|def| {
+            def.container_contains(ContainerItem::List)
+                || def.container_contains(ContainerItem::Set)
+        });
+    if result.len() != 1
+        || !processor.options().compact_arrays()
+        || active_property == Some("@graph")
+        || active_property == Some("@set")
+        || is_list_or_set_container
+    {
+        return Ok(Value::Array(result));
+    }
+
+    // Step 3.4
+    Ok(result
+        .pop()
+        .expect("Should never fail: `result` has an element"))
+}
+
+/// Compacts a map.
+// Step 1, 5-13
+async fn compact_map<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    inverse_context: &InverseContext,
+    active_property: Option<&str>,
+    element: &JsonMap<String, Value>,
+) -> Result<Value> {
+    // Step 1
+    let type_scoped_context = active_context;
+    let type_scoped_inverse_context = inverse_context;
+    let mut active_context = ActiveContext::new(active_context, inverse_context);
+    // Step 5
+    if let Some(previous_context) = type_scoped_context.previous_context() {
+        let is_subject_reference = element.len() == 1 && element.contains_key("@id");
+        if !element.contains_key("@value") && !is_subject_reference {
+            active_context.set(previous_context.clone());
+        }
+    }
+    // Step 6
+    // NOTE: The property-scoped context is taken from the original active context, because
+    // the previous context may not have the term definition.
+    if let Some(property_scoped_context) = active_property
+        .and_then(|prop| type_scoped_context.term_definition(prop))
+        .and_then(Definition::local_context)
+    {
+        let context = active_context
+            .context()
+            .join_scoped_context(processor, property_scoped_context, true, true)
+            .await?;
+        active_context.set(context);
+    }
+    // Step 7
+    if element.contains_key("@value") || element.contains_key("@id") {
+        let compacted = compact_value(
+            processor,
+            active_context.context(),
+            active_context.inverse(),
+            active_property,
+            element,
+        )?;
+        let is_json = active_property
+            .and_then(|prop| active_context.context().term_definition(prop))
+            .is_some_and(|def| def.ty() == Some("@json"));
+        if !compacted.is_object() || is_json {
+            return Ok(compacted);
+        }
+    }
+    // Step 8
+    if let Some(list) = element.get("@list") {
+        let is_list_container = active_property
+            .and_then(|prop| active_context.context().term_definition(prop))
+            .is_some_and(|def| def.container_contains(ContainerItem::List));
+        if is_list_container {
+            return compact_element(
+                processor,
+                active_context.context(),
+                active_context.inverse(),
+                active_property,
+                list,
+            )
+            .await;
+        }
+    }
+    // Step 9, 10: See `compact_entries`.
+    // Step 11
+    if let Some(types) = element.get("@type") {
+        let mut compacted_types = to_ref_array(types)
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|ty| {
+                CompactIriOptions::new(active_context.context(), active_context.inverse())
+                    .vocab(true)
+                    .compact(processor, ty)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        compacted_types.sort_unstable();
+        // Step 11.1
+        for term in compacted_types {
+            if let Some(local_context) = type_scoped_context
+                .term_definition(&term)
+                .and_then(Definition::local_context)
+            {
+                let context = active_context
+                    .context()
+                    .join_scoped_context(processor, local_context, false, false)
+                    .await?;
+                active_context.set(context);
+            }
+        }
+    }
+    // Step 12
+    let env = EntriesEnv {
+        processor,
+        active_context: active_context.context(),
+        inverse_context: active_context.inverse(),
+        type_scoped_context,
+        type_scoped_inverse_context,
+        active_property,
+    };
+    let result = compact_entries(&env, element).await?;
+
+    // Step 13
+    Ok(Value::Object(result))
+}
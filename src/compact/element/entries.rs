@@ -0,0 +1,554 @@
+//! Compaction of map entries.
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    compact::{element::compact_element, inverse::InverseContext, iri::CompactIriOptions},
+    context::{ContainerItem, Context, Definition},
+    error::{ErrorCode, Result},
+    expand::iri::ExpandIriOptions,
+    json::{add_value, single_entry_map, to_ref_array},
+    processor::Processor,
+    remote::LoadRemoteDocument,
+    syntax::{is_graph_object, is_list_object},
+};
+
+/// Environment to compact entries of a map.
+pub(super) struct EntriesEnv<'a, L> {
+    /// Processor.
+    pub(super) processor: &'a Processor<L>,
+    /// Active context.
+    pub(super) active_context: &'a Context,
+    /// Inverse context of the active context.
+    pub(super) inverse_context: &'a InverseContext,
+    /// Type-scoped context.
+    pub(super) type_scoped_context: &'a Context,
+    /// Inverse context of the type-scoped context.
+    pub(super) type_scoped_inverse_context: &'a InverseContext,
+    /// Active property.
+    pub(super) active_property: Option<&'a str>,
+}
+
+impl<L: LoadRemoteDocument> EntriesEnv<'_, L> {
+    /// Returns the term definition in the active context.
+    fn definition(&self, term: &str) -> Option<&Definition> {
+        self.active_context.term_definition(term)
+    }
+
+    /// Returns whether the container mapping of the term contains the given item.
+    fn container_contains(&self, term: &str, item: ContainerItem) -> bool {
+        self.definition(term)
+            .is_some_and(|def| def.container_contains(item))
+    }
+
+    /// Returns `CompactIriOptions` with the active context.
+    fn compact_iri_options(&self) -> CompactIriOptions<'_> {
+        CompactIriOptions::new(self.active_context, self.inverse_context)
+    }
+
+    /// Runs IRI compaction algorithm with the active context.
+    fn compact_iri(&self, iri: &str, vocab: bool) -> Result<String> {
+        self.compact_iri_options()
+            .vocab(vocab)
+            .compact(self.processor, iri)
+    }
+}
+
+/// Compacts entries of the given map.
+// Step 9, 10, 12
+pub(super) async fn compact_entries<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    element: &JsonMap<String, Value>,
+) -> Result<JsonMap<String, Value>> {
+    let processor = env.processor;
+    let compact_arrays = processor.options().compact_arrays();
+    // Step 9
+    let inside_reverse = env.active_property == Some("@reverse");
+    // Step 10
+    let mut result = JsonMap::new();
+    // Step 12
+    for (expanded_property, expanded_value) in element {
+        match expanded_property.as_str() {
+            // Step 12.1
+            "@id" => {
+                // Step 12.1.1
+                let compacted_value = match expanded_value {
+                    Value::String(s) => env.compact_iri(s, false)?.into(),
+                    v => v.clone(),
+                };
+                // Step 12.1.2
+                let alias = env.compact_iri("@id", true)?;
+                // Step 12.1.3
+                result.insert(alias, compacted_value);
+            }
+            // Step 12.2
+            "@type" => {
+                let compact_type = |v: &Value| match v {
+                    Value::String(s) => CompactIriOptions::new(
+                        env.type_scoped_context,
+                        env.type_scoped_inverse_context,
+                    )
+                    .vocab(true)
+                    .compact(processor, s)
+                    .map(Value::String),
+                    v => Ok(v.clone()),
+                };
+                let compacted_value = match expanded_value {
+                    // Step 12.2.2
+                    Value::Array(arr) => Value::Array(
+                        arr.iter()
+                            // Step 12.2.2.2
+                            .map(compact_type)
+                            .collect::<Result<_>>()?,
+                    ),
+                    // Step 12.2.1
+                    v => compact_type(v)?,
+                };
+                // Step 12.2.3
+                let alias = env.compact_iri("@type", true)?;
+                // Step 12.2.4
+                let as_array = if processor.is_processing_mode_1_0() {
+                    !compact_arrays
+                } else {
+                    env.container_contains(&alias, ContainerItem::Set) || !compact_arrays
+                };
+                // Step 12.2.5
+                add_value(&mut result, &alias, compacted_value, as_array);
+            }
+            // Step 12.3
+            "@reverse" => compact_reverse(env, expanded_value, &mut result).await?,
+            // Step 12.4
+            "@preserve" => {
+                // Step 12.4.1
+                let compacted_value = compact_element(
+                    processor,
+                    env.active_context,
+                    env.inverse_context,
+                    env.active_property,
+                    expanded_value,
+                )
+                .await?;
+                // Step 12.4.2
+                if expanded_value
+                    .as_array()
+                    .map_or(true, |arr| !arr.is_empty())
+                {
+                    result.insert("@preserve".into(), compacted_value);
+                }
+            }
+            // Step 12.5
+            "@index"
+                if env
+                    .active_property
+                    .is_some_and(|prop| env.container_contains(prop, ContainerItem::Index)) => {}
+            // Step 12.6
+            "@direction" | "@index" | "@language" | "@value" => {
+                // Step 12.6.1
+                let alias = env.compact_iri(expanded_property, true)?;
+                // Step 12.6.2
+                result.insert(alias, expanded_value.clone());
+            }
+            _ => {
+                let expanded_items = to_ref_array(expanded_value);
+                // Step 12.7
+                if expanded_items.is_empty() {
+                    // Step 12.7.1
+                    let item_active_property = env
+                        .compact_iri_options()
+                        .value(Some(expanded_value))
+                        .vocab(true)
+                        .reverse(inside_reverse)
+                        .compact(processor, expanded_property)?;
+                    // Step 12.7.2, 12.7.3
+                    let nest_result =
+                        get_nest_result(env, &mut result, &item_active_property).await?;
+                    // Step 12.7.4
+                    add_value(
+                        nest_result,
+                        &item_active_property,
+                        Value::Array(Vec::new()),
+                        true,
+                    );
+                }
+                // Step 12.8
+                for expanded_item in expanded_items {
+                    compact_item(
+                        env,
+                        &mut result,
+                        expanded_property,
+                        expanded_item,
+                        inside_reverse,
+                    )
+                    .await?;
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Compacts `@reverse` entry.
+// Step 12.3
+async fn compact_reverse<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    expanded_value: &Value,
+    result: &mut JsonMap<String, Value>,
+) -> Result<()> {
+    let compact_arrays = env.processor.options().compact_arrays();
+    // Step 12.3.1
+    let compacted_value = compact_element(
+        env.processor,
+        env.active_context,
+        env.inverse_context,
+        Some("@reverse"),
+        expanded_value,
+    )
+    .await?;
+    let mut compacted_value = match compacted_value {
+        Value::Object(map) => map,
+        v => {
+            return Err(ErrorCode::InvalidReverseValue
+                .and_source(anyhow!("Expected a map as `@reverse`, but got {:?}", v)))
+        }
+    };
+    // Step 12.3.2
+    let reverse_properties = compacted_value
+        .keys()
+        .filter(|property| env.definition(property).is_some_and(Definition::is_reverse))
+        .cloned()
+        .collect::<Vec<_>>();
+    for property in reverse_properties {
+        // Step 12.3.2.1
+        let value = compacted_value
+            .remove(&property)
+            .expect("Should never fail: the key is taken from the map");
+        // Step 12.3.2.1.1
+        let as_array = env.container_contains(&property, ContainerItem::Set) || !compact_arrays;
+        // Step 12.3.2.1.2, 12.3.2.1.3
+        add_value(result, &property, value, as_array);
+    }
+    // Step 12.3.3
+    if !compacted_value.is_empty() {
+        // Step 12.3.3.1
+        let alias = env.compact_iri("@reverse", true)?;
+        // Step 12.3.3.2
+        result.insert(alias, Value::Object(compacted_value));
+    }
+
+    // Step 12.3.4
+    Ok(())
+}
+
+/// Compacts an item of the entry value and adds it to the result.
+// Step 12.8
+async fn compact_item<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    result: &mut JsonMap<String, Value>,
+    expanded_property: &str,
+    expanded_item: &Value,
+    inside_reverse: bool,
+) -> Result<()> {
+    let processor = env.processor;
+    // Step 12.8.1
+    let item_active_property = env
+        .compact_iri_options()
+        .value(Some(expanded_item))
+        .vocab(true)
+        .reverse(inside_reverse)
+        .compact(processor, expanded_property)?;
+    // Step 12.8.2, 12.8.3
+    let nest_result = get_nest_result(env, result, &item_active_property).await?;
+    // Step 12.8.4
+    let definition = env.definition(&item_active_property);
+    let container = definition.and_then(Definition::container);
+    let contains = |item| container.is_some_and(|container| container.contains(item));
+    // Step 12.8.5
+    let as_array = contains(ContainerItem::Set)
+        || item_active_property == "@graph"
+        || item_active_property == "@list"
+        || !processor.options().compact_arrays();
+    // Step 12.8.6
+    let is_list = is_list_object(expanded_item);
+    let is_graph = is_graph_object(expanded_item);
+    let element = if is_list {
+        &expanded_item["@list"]
+    } else if is_graph {
+        &expanded_item["@graph"]
+    } else {
+        expanded_item
+    };
+    let compacted_item = compact_element(
+        processor,
+        env.active_context,
+        env.inverse_context,
+        Some(&item_active_property),
+        element,
+    )
+    .await?;
+    let expanded_id = expanded_item.get("@id").and_then(Value::as_str);
+    let expanded_index = expanded_item.get("@index");
+
+    if is_list {
+        // Step 12.8.7
+        // Step 12.8.7.1
+        let compacted_item = match compacted_item {
+            v @ Value::Array(_) => v,
+            v => Value::Array(vec![v]),
+        };
+        if !contains(ContainerItem::List) {
+            // Step 12.8.7.2
+            // Step 12.8.7.2.1
+            let mut compacted_item =
+                single_entry_map(env.compact_iri("@list", true)?, compacted_item);
+            // Step 12.8.7.2.2
+            if let Some(index) = expanded_index {
+                compacted_item.insert(env.compact_iri("@index", true)?, index.clone());
+            }
+            // Step 12.8.7.2.3
+            add_value(
+                nest_result,
+                &item_active_property,
+                Value::Object(compacted_item),
+                as_array,
+            );
+        } else {
+            // Step 12.8.7.3
+            nest_result.insert(item_active_property, compacted_item);
+        }
+    } else if is_graph {
+        // Step 12.8.8
+        let is_simple_graph = expanded_id.is_none();
+        if contains(ContainerItem::Graph) && contains(ContainerItem::Id) {
+            // Step 12.8.8.1
+            // Step 12.8.8.1.2
+            let map_key = match expanded_id {
+                Some(id) => env.compact_iri(id, false)?,
+                None => env.compact_iri("@none", true)?,
+            };
+            // Step 12.8.8.1.1, 12.8.8.1.3
+            let map_object = get_map_object(nest_result, &item_active_property)?;
+            add_value(map_object, &map_key, compacted_item, as_array);
+        } else if contains(ContainerItem::Graph)
+            && contains(ContainerItem::Index)
+            && is_simple_graph
+        {
+            // Step 12.8.8.2
+            // Step 12.8.8.2.2
+            let map_key = match expanded_index.and_then(Value::as_str) {
+                Some(index) => index.to_owned(),
+                None => env.compact_iri("@none", true)?,
+            };
+            // Step 12.8.8.2.1, 12.8.8.2.3
+            let map_object = get_map_object(nest_result, &item_active_property)?;
+            add_value(map_object, &map_key, compacted_item, as_array);
+        } else if contains(ContainerItem::Graph) && is_simple_graph {
+            // Step 12.8.8.3
+            // Step 12.8.8.3.1
+            let compacted_item = match compacted_item {
+                Value::Array(arr) if arr.len() > 1 => {
+                    Value::Object(single_entry_map(env.compact_iri("@included", true)?, arr))
+                }
+                v => v,
+            };
+            // Step 12.8.8.3.2
+            add_value(nest_result, &item_active_property, compacted_item, as_array);
+        } else {
+            // Step 12.8.8.4
+            // Step 12.8.8.4.1
+            let mut compacted_item =
+                single_entry_map(env.compact_iri("@graph", true)?, compacted_item);
+            // Step 12.8.8.4.2
+            if let Some(id) = expanded_id {
+                compacted_item.insert(
+                    env.compact_iri("@id", true)?,
+                    env.compact_iri(id, false)?.into(),
+                );
+            }
+            // Step 12.8.8.4.3
+            if let Some(index) = expanded_index {
+                compacted_item.insert(env.compact_iri("@index", true)?, index.clone());
+            }
+            // Step 12.8.8.4.4
+            add_value(
+                nest_result,
+                &item_active_property,
+                Value::Object(compacted_item),
+                as_array,
+            );
+        }
+    } else if !contains(ContainerItem::Graph)
+        && (contains(ContainerItem::Language)
+            || contains(ContainerItem::Index)
+            || contains(ContainerItem::Id)
+            || contains(ContainerItem::Type))
+    {
+        // Step 12.8.9
+        let mut compacted_item = compacted_item;
+        // Step 12.8.9.2
+        let container_keyword = if contains(ContainerItem::Language) {
+            "@language"
+        } else if contains(ContainerItem::Index) {
+            "@index"
+        } else if contains(ContainerItem::Id) {
+            "@id"
+        } else {
+            "@type"
+        };
+        let mut container_key = env.compact_iri(container_keyword, true)?;
+        // Step 12.8.9.3
+        let index_key = definition.and_then(Definition::index).unwrap_or("@index");
+        let map_key = if contains(ContainerItem::Language) && expanded_item.get("@value").is_some()
+        {
+            // Step 12.8.9.4
+            compacted_item = expanded_item["@value"].clone();
+            expanded_item
+                .get("@language")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        } else if contains(ContainerItem::Index) && index_key == "@index" {
+            // Step 12.8.9.5
+            expanded_index
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        } else if contains(ContainerItem::Index) {
+            // Step 12.8.9.6
+            // Step 12.8.9.6.1
+            let expanded_index_key = ExpandIriOptions::constant(env.active_context)
+                .vocab(true)
+                .expand_str(processor, index_key)
+                .await?;
+            if let Some(expanded_index_key) = expanded_index_key {
+                container_key = env.compact_iri(&expanded_index_key, true)?;
+            }
+            // Step 12.8.9.6.2-12.8.9.6.4
+            take_first_string(&mut compacted_item, &container_key)
+        } else if contains(ContainerItem::Id) {
+            // Step 12.8.9.7
+            compacted_item
+                .as_object_mut()
+                .and_then(|map| map.remove(&container_key))
+                .and_then(|v| v.as_str().map(ToOwned::to_owned))
+        } else {
+            // Step 12.8.9.8
+            // Step 12.8.9.8.1-12.8.9.8.3
+            let map_key = take_first_string(&mut compacted_item, &container_key);
+            // Step 12.8.9.8.4
+            if let Some(map) = compacted_item.as_object().filter(|map| map.len() == 1) {
+                let key = map.keys().next().expect("Should never fail: has an entry");
+                let expanded_key = ExpandIriOptions::constant(env.active_context)
+                    .vocab(true)
+                    .expand_str(processor, key)
+                    .await?;
+                if let (Some("@id"), Some(id)) = (expanded_key.as_deref(), expanded_id) {
+                    compacted_item = compact_element(
+                        processor,
+                        env.active_context,
+                        env.inverse_context,
+                        Some(&item_active_property),
+                        &Value::Object(single_entry_map("@id", id)),
+                    )
+                    .await?;
+                }
+            }
+            map_key
+        };
+        // Step 12.8.9.9
+        let map_key = match map_key {
+            Some(v) => v,
+            None => env.compact_iri("@none", true)?,
+        };
+        // Step 12.8.9.1, 12.8.9.10
+        let map_object = get_map_object(nest_result, &item_active_property)?;
+        add_value(map_object, &map_key, compacted_item, as_array);
+    } else {
+        // Step 12.8.10
+        add_value(nest_result, &item_active_property, compacted_item, as_array);
+    }
+
+    Ok(())
+}
+
+/// Returns the map to which the compacted values of the property should be added.
+// Step 12.7.2, 12.7.3, 12.8.2, 12.8.3
+async fn get_nest_result<'r, L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    result: &'r mut JsonMap<String, Value>,
+    item_active_property: &str,
+) -> Result<&'r mut JsonMap<String, Value>> {
+    let nest_term = match env
+        .definition(item_active_property)
+        .and_then(Definition::nest)
+    {
+        Some(v) => v,
+        None => return Ok(result),
+    };
+    // Step 12.8.2.1
+    if nest_term != "@nest" {
+        let expanded = ExpandIriOptions::constant(env.active_context)
+            .vocab(true)
+            .expand_str(env.processor, nest_term)
+            .await?;
+        if expanded.as_deref() != Some("@nest") {
+            return Err(ErrorCode::InvalidNestValue.and_source(anyhow!(
+                "Nest value {:?} of the term {:?} is not `@nest` or its alias",
+                nest_term,
+                item_active_property
+            )));
+        }
+    }
+    // Step 12.8.2.2, 12.8.2.3
+    result
+        .entry(nest_term)
+        .or_insert_with(|| Value::Object(JsonMap::new()))
+        .as_object_mut()
+        .ok_or_else(|| {
+            ErrorCode::InvalidNestValue.and_source(anyhow!(
+                "Nest value {:?} is used for a non-map entry",
+                nest_term
+            ))
+        })
+}
+
+/// Returns the map object for the property, creating a new empty map if necessary.
+// Step 12.8.8.1.1, 12.8.8.2.1, 12.8.9.1
+fn get_map_object<'r>(
+    nest_result: &'r mut JsonMap<String, Value>,
+    item_active_property: &str,
+) -> Result<&'r mut JsonMap<String, Value>> {
+    nest_result
+        .entry(item_active_property)
+        .or_insert_with(|| Value::Object(JsonMap::new()))
+        .as_object_mut()
+        .ok_or_else(|| {
+            ErrorCode::Uncategorized.and_source(anyhow!(
+                "Expected a map object for the property {:?}",
+                item_active_property
+            ))
+        })
+}
+
+/// Removes the first string value of the entry from the compacted item, and returns it.
+///
+/// Remaining values are left in the entry. If the first value is not a string, the entry is
+/// left untouched and `None` is returned.
+// Step 12.8.9.6.2-12.8.9.6.4, 12.8.9.8.1-12.8.9.8.3
+fn take_first_string(compacted_item: &mut Value, key: &str) -> Option<String> {
+    let map = compacted_item.as_object_mut()?;
+    let mut values = match map.remove(key)? {
+        Value::Array(arr) => arr,
+        v => vec![v],
+    };
+    let first = match values.first() {
+        Some(Value::String(s)) => s.clone(),
+        _ => {
+            add_value(map, key, Value::Array(values), false);
+            return None;
+        }
+    };
+    values.remove(0);
+    add_value(map, key, Value::Array(values), false);
+
+    Some(first)
+}
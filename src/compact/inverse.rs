@@ -0,0 +1,313 @@
+//! Inverse context.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#inverse-context-creation>.
+
+use std::collections::HashMap;
+
+use crate::{
+    context::{ContainerItem, Context, Definition},
+    json::Nullable,
+};
+
+/// Key of a type/language map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TypeOrLanguage {
+    /// `@language`.
+    Language,
+    /// `@type`.
+    Type,
+    /// `@any`.
+    Any,
+}
+
+/// Type/language map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TypeLanguageMap {
+    /// `@language` entry.
+    language: HashMap<String, String>,
+    /// `@type` entry.
+    ty: HashMap<String, String>,
+    /// `@any` entry.
+    any: HashMap<String, String>,
+}
+
+impl TypeLanguageMap {
+    /// Creates a new type/language map for the given term.
+    // Step 3.6
+    fn new(term: &str) -> Self {
+        let mut any = HashMap::new();
+        any.insert("@none".to_owned(), term.to_owned());
+        Self {
+            language: HashMap::new(),
+            ty: HashMap::new(),
+            any,
+        }
+    }
+
+    /// Returns the value map for the given key.
+    fn get(&self, key: TypeOrLanguage) -> &HashMap<String, String> {
+        match key {
+            TypeOrLanguage::Language => &self.language,
+            TypeOrLanguage::Type => &self.ty,
+            TypeOrLanguage::Any => &self.any,
+        }
+    }
+}
+
+/// Inverse context.
+///
+/// This maps an IRI to terms, keyed by containers, type/language, and type or language values.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#inverse-context-creation>.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InverseContext {
+    /// Container maps for IRIs.
+    container_maps: HashMap<String, HashMap<String, TypeLanguageMap>>,
+}
+
+impl InverseContext {
+    /// Runs inverse context creation algorithm and returns the inverse context.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#inverse-context-creation>.
+    pub(crate) fn new(active_context: &Context) -> Self {
+        // Step 1
+        let mut result = Self::default();
+        // Step 2
+        let default_language = active_context
+            .default_language()
+            .map(str::to_ascii_lowercase);
+        // Step 3
+        let mut definitions = active_context
+            .term_definitions()
+            // Step 3.1
+            .filter_map(|(term, def)| def.map(|def| (term, def)))
+            .collect::<Vec<_>>();
+        definitions.sort_unstable_by(|(l, _), (r, _)| l.len().cmp(&r.len()).then(l.cmp(r)));
+        for (term, definition) in definitions {
+            // Step 3.2
+            let container = container_key(definition);
+            // Step 3.3-3.5
+            let container_map = result
+                .container_maps
+                .entry(definition.iri().to_owned())
+                .or_default();
+            // Step 3.6, 3.7
+            let type_language_map = container_map
+                .entry(container)
+                .or_insert_with(|| TypeLanguageMap::new(term));
+            // Step 3.8, 3.9
+            let TypeLanguageMap {
+                language: language_map,
+                ty: type_map,
+                ..
+            } = type_language_map;
+            let add = |map: &mut HashMap<String, String>, key: &str| {
+                map.entry(key.to_owned()).or_insert_with(|| term.to_owned());
+            };
+
+            if definition.is_reverse() {
+                // Step 3.10
+                add(type_map, "@reverse");
+            } else if definition.ty() == Some("@none") {
+                // Step 3.11
+                add(language_map, "@any");
+                add(type_map, "@any");
+            } else if let Some(ty) = definition.ty() {
+                // Step 3.12
+                add(type_map, ty);
+            } else if let (Some(language), Some(direction)) =
+                (definition.language(), definition.direction())
+            {
+                // Step 3.13
+                let lang_dir = match (language, direction) {
+                    (Nullable::Value(language), Nullable::Value(direction)) => {
+                        format!("{}_{}", language, direction.as_str()).to_ascii_lowercase()
+                    }
+                    (Nullable::Value(language), Nullable::Null) => language.to_ascii_lowercase(),
+                    (Nullable::Null, Nullable::Value(direction)) => {
+                        format!("_{}", direction.as_str())
+                    }
+                    (Nullable::Null, Nullable::Null) => "@null".to_owned(),
+                };
+                add(language_map, &lang_dir);
+            } else if let Some(language) = definition.language() {
+                // Step 3.14
+                let language = match language {
+                    Nullable::Value(language) => language.to_ascii_lowercase(),
+                    Nullable::Null => "@null".to_owned(),
+                };
+                add(language_map, &language);
+            } else if let Some(direction) = definition.direction() {
+                // Step 3.15
+                let direction = match direction {
+                    Nullable::Value(direction) => format!("_{}", direction.as_str()),
+                    Nullable::Null => "@none".to_owned(),
+                };
+                add(language_map, &direction);
+            } else if let Some(direction) = active_context.default_base_direction() {
+                // Step 3.16
+                let lang_dir = format!(
+                    "{}_{}",
+                    default_language.as_deref().unwrap_or(""),
+                    direction.as_str()
+                );
+                add(language_map, &lang_dir);
+                add(language_map, "@none");
+                add(type_map, "@none");
+            } else {
+                // Step 3.17
+                add(language_map, default_language.as_deref().unwrap_or("@none"));
+                add(language_map, "@none");
+                add(type_map, "@none");
+            }
+        }
+
+        // Step 4
+        result
+    }
+
+    /// Checks whether the inverse context has an entry for the given IRI.
+    pub(crate) fn contains_iri(&self, iri: &str) -> bool {
+        self.container_maps.contains_key(iri)
+    }
+
+    /// Runs term selection algorithm and returns the selected term.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#term-selection>.
+    pub(crate) fn select_term<C: AsRef<str>, P: AsRef<str>>(
+        &self,
+        var: &str,
+        containers: &[C],
+        type_language: TypeOrLanguage,
+        preferred_values: &[P],
+    ) -> Option<&str> {
+        // Step 1, 2: The inverse context is already created.
+        // Step 3
+        let container_map = self.container_maps.get(var)?;
+        // Step 4
+        containers
+            .iter()
+            // Step 4.1, 4.2
+            .filter_map(|container| container_map.get(container.as_ref()))
+            .find_map(|type_language_map| {
+                // Step 4.3
+                let value_map = type_language_map.get(type_language);
+                // Step 4.4
+                preferred_values
+                    .iter()
+                    .find_map(|item| value_map.get(item.as_ref()))
+            })
+            .map(String::as_str)
+        // Step 5: `None` if not found.
+    }
+}
+
+/// Returns the container key of the given term definition.
+// Step 3.2
+fn container_key(definition: &Definition) -> String {
+    match definition.container() {
+        Some(container) if container.len() != 0 => {
+            // `ContainerItem`s are iterated in lexicographical order of their string forms.
+            container.iter().map(ContainerItem::as_str).collect()
+        }
+        _ => "@none".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::context;
+
+    #[test]
+    fn prefer_shortest_term() {
+        let ctx = context(json!({
+            "name": "http://xmlns.com/foaf/0.1/name",
+            "nm": "http://xmlns.com/foaf/0.1/name",
+            "aa": "http://xmlns.com/foaf/0.1/name",
+            "fullName": "http://xmlns.com/foaf/0.1/name"
+        }));
+        let inverse = InverseContext::new(&ctx);
+        assert!(inverse.contains_iri("http://xmlns.com/foaf/0.1/name"));
+        assert_eq!(
+            inverse.select_term(
+                "http://xmlns.com/foaf/0.1/name",
+                &["@none"],
+                TypeOrLanguage::Language,
+                &["@none"],
+            ),
+            Some("aa")
+        );
+    }
+
+    #[test]
+    fn select_by_container_and_type_language() {
+        let ctx = context(json!({
+            "@language": "en",
+            "@vocab": "http://example.org/",
+            "label": "http://example.org/label",
+            "labelJa": { "@id": "http://example.org/label", "@language": "ja" },
+            "labelRtl": { "@id": "http://example.org/label", "@language": "ar", "@direction": "rtl" },
+            "labelMap": { "@id": "http://example.org/label", "@container": "@language" },
+            "date": { "@id": "http://example.org/label", "@type": "http://www.w3.org/2001/XMLSchema#date" },
+            "labels": { "@id": "http://example.org/label", "@container": ["@set", "@index"] },
+            "labelOf": { "@reverse": "http://example.org/label" }
+        }));
+        let inverse = InverseContext::new(&ctx);
+        let iri = "http://example.org/label";
+        let select = |containers: &[&str], tl, preferred: &[&str]| {
+            inverse.select_term(iri, containers, tl, preferred)
+        };
+        assert_eq!(
+            select(&["@none"], TypeOrLanguage::Language, &["en", "@none"]),
+            Some("label")
+        );
+        assert_eq!(
+            select(&["@none"], TypeOrLanguage::Language, &["ja", "@none"]),
+            Some("labelJa")
+        );
+        assert_eq!(
+            select(&["@none"], TypeOrLanguage::Language, &["ar_rtl", "@none"]),
+            Some("labelRtl")
+        );
+        assert_eq!(
+            select(
+                &["@language", "@none"],
+                TypeOrLanguage::Language,
+                &["fr", "@none"]
+            ),
+            Some("labelMap")
+        );
+        assert_eq!(
+            select(
+                &["@none"],
+                TypeOrLanguage::Type,
+                &["http://www.w3.org/2001/XMLSchema#date", "@none"]
+            ),
+            Some("date")
+        );
+        assert_eq!(
+            select(&["@index@set"], TypeOrLanguage::Language, &["@none"]),
+            Some("labels")
+        );
+        assert_eq!(
+            select(&["@none"], TypeOrLanguage::Type, &["@reverse"]),
+            Some("labelOf")
+        );
+        assert_eq!(
+            select(&["@list"], TypeOrLanguage::Language, &["@none"]),
+            None
+        );
+        assert_eq!(
+            inverse.select_term(
+                "http://example.org/unknown",
+                &["@none"],
+                TypeOrLanguage::Any,
+                &["@none"]
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,499 @@
+//! IRI compaction.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#iri-compaction>.
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    compact::inverse::{InverseContext, TypeOrLanguage},
+    context::Context,
+    error::{ErrorCode, Result},
+    iri::{relativize, to_prefix_and_suffix},
+    json::Nullable,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+    syntax::{is_graph_object, is_list_object, is_value_object},
+};
+
+/// Options for IRI compaction algorithm.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompactIriOptions<'a> {
+    /// Active context.
+    active_context: &'a Context,
+    /// Inverse context of the active context.
+    inverse_context: &'a InverseContext,
+    /// Value.
+    value: Option<&'a Value>,
+    /// Vocab.
+    vocab: bool,
+    /// Reverse.
+    reverse: bool,
+}
+
+impl<'a> CompactIriOptions<'a> {
+    /// Creates a new `CompactIriOptions`.
+    ///
+    /// `inverse_context` should be created from `active_context`.
+    pub(crate) fn new(active_context: &'a Context, inverse_context: &'a InverseContext) -> Self {
+        Self {
+            active_context,
+            inverse_context,
+            value: None,
+            vocab: false,
+            reverse: false,
+        }
+    }
+
+    /// Sets the value.
+    pub(crate) fn value(self, value: Option<&'a Value>) -> Self {
+        Self { value, ..self }
+    }
+
+    /// Sets "vocab" flag.
+    pub(crate) fn vocab(self, vocab: bool) -> Self {
+        Self { vocab, ..self }
+    }
+
+    /// Sets "reverse" flag.
+    pub(crate) fn reverse(self, reverse: bool) -> Self {
+        Self { reverse, ..self }
+    }
+
+    /// Runs IRI compaction algorithm.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#iri-compaction>.
+    pub(crate) fn compact<L: LoadRemoteDocument>(
+        self,
+        processor: &Processor<L>,
+        var: &str,
+    ) -> Result<String> {
+        compact_iri(self, processor, var)
+    }
+}
+
+/// Runs IRI compaction algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#iri-compaction>.
+fn compact_iri<L: LoadRemoteDocument>(
+    options: CompactIriOptions<'_>,
+    processor: &Processor<L>,
+    var: &str,
+) -> Result<String> {
+    let active_context = options.active_context;
+    // Step 1: `var` is not null.
+    // Step 2, 3: The inverse context is given.
+    // Step 4
+    if options.vocab && options.inverse_context.contains_iri(var) {
+        if let Some(term) = select_term(options, processor, var)? {
+            return Ok(term);
+        }
+    }
+    // Step 5
+    if options.vocab {
+        if let Nullable::Value(vocab) = active_context.vocab() {
+            // Step 5.1
+            if var.len() > vocab.len() && var.starts_with(vocab) {
+                let suffix = &var[vocab.len()..];
+                if active_context.raw_term_definition(suffix).is_none() {
+                    return Ok(suffix.to_owned());
+                }
+            }
+        }
+    }
+    // Step 6
+    let mut compact_iri: Option<String> = None;
+    // Step 7
+    for (term, definition) in active_context.term_definitions() {
+        // Step 7.1
+        let definition = match definition {
+            Some(def) if def.is_prefix() && def.iri() != var && var.starts_with(def.iri()) => def,
+            _ => continue,
+        };
+        // Step 7.2
+        let candidate = format!("{}:{}", term, &var[definition.iri().len()..]);
+        // Step 7.3
+        let is_shorter_or_least = compact_iri.as_ref().map_or(true, |compact_iri| {
+            (candidate.len(), &candidate) < (compact_iri.len(), compact_iri)
+        });
+        let is_usable = match active_context.raw_term_definition(&candidate) {
+            None => true,
+            Some(Nullable::Value(def)) => def.iri() == var && options.value.is_none(),
+            Some(Nullable::Null) => false,
+        };
+        if is_shorter_or_least && is_usable {
+            compact_iri = Some(candidate);
+        }
+    }
+    // Step 8
+    if let Some(compact_iri) = compact_iri {
+        return Ok(compact_iri);
+    }
+    // Step 9
+    if let Some((scheme, suffix)) = to_prefix_and_suffix(var) {
+        if !suffix.starts_with("//")
+            && active_context
+                .term_definition(scheme)
+                .is_some_and(|def| def.is_prefix())
+        {
+            return Err(ErrorCode::IriConfusedWithPrefix.and_source(anyhow!(
+                "IRI {:?} has the scheme {:?} which is defined as a prefix",
+                var,
+                scheme
+            )));
+        }
+    }
+    // Step 10
+    if !options.vocab && processor.options().compact_to_relative() {
+        if let Some(base) = processor.base(active_context) {
+            return Ok(relativize(&base, var));
+        }
+    }
+
+    // Step 11
+    Ok(var.to_owned())
+}
+
+/// Selects the term for the IRI.
+///
+/// Returns `Ok(None)` if no terms are available.
+// Step 4
+fn select_term<L: LoadRemoteDocument>(
+    options: CompactIriOptions<'_>,
+    processor: &Processor<L>,
+    var: &str,
+) -> Result<Option<String>> {
+    let active_context = options.active_context;
+    // Step 4.1
+    let default_language = match active_context.default_base_direction() {
+        Some(direction) => format!(
+            "{}_{}",
+            active_context.default_language().unwrap_or(""),
+            direction.as_str()
+        )
+        .to_ascii_lowercase(),
+        None => active_context
+            .default_language()
+            .map_or_else(|| "@none".to_owned(), str::to_ascii_lowercase),
+    };
+    // Step 4.2
+    let value = match options.value {
+        Some(Value::Object(map)) => match map.get("@preserve") {
+            Some(Value::Array(arr)) => arr.first(),
+            Some(v) => Some(v),
+            None => options.value,
+        },
+        v => v,
+    };
+    let value_map = value.and_then(Value::as_object);
+    let contains = |key: &str| value_map.is_some_and(|map| map.contains_key(key));
+    // Step 4.3
+    let mut containers: Vec<&str> = Vec::new();
+    // Step 4.4
+    let mut type_language = TypeOrLanguage::Language;
+    let mut type_language_value: Option<String> = None;
+    // Step 4.5
+    if contains("@index") && !value.is_some_and(is_graph_object) {
+        containers.extend(&["@index", "@index@set"]);
+    }
+    if options.reverse {
+        // Step 4.6
+        type_language = TypeOrLanguage::Type;
+        type_language_value = Some("@reverse".to_owned());
+        containers.push("@set");
+    } else if let Some(list) = value.filter(|v| is_list_object(v)).map(|v| &v["@list"]) {
+        // Step 4.7
+        // Step 4.7.1
+        if !contains("@index") {
+            containers.push("@list");
+        }
+        // Step 4.7.2-4.7.8
+        let (common_type, common_language) = list_common_type_and_language(list, &default_language);
+        if common_type != "@none" {
+            // Step 4.7.7
+            type_language = TypeOrLanguage::Type;
+            type_language_value = Some(common_type);
+        } else {
+            // Step 4.7.8
+            type_language_value = Some(common_language);
+        }
+    } else if value.is_some_and(is_graph_object) {
+        // Step 4.8
+        // Step 4.8.1
+        if contains("@index") {
+            containers.extend(&["@graph@index", "@graph@index@set"]);
+        }
+        // Step 4.8.2
+        if contains("@id") {
+            containers.extend(&["@graph@id", "@graph@id@set"]);
+        }
+        // Step 4.8.3
+        containers.extend(&["@graph", "@graph@set", "@set"]);
+        // Step 4.8.4
+        if !contains("@index") {
+            containers.extend(&["@graph@index", "@graph@index@set"]);
+        }
+        // Step 4.8.5
+        if !contains("@id") {
+            containers.extend(&["@graph@id", "@graph@id@set"]);
+        }
+        // Step 4.8.6
+        containers.extend(&["@index", "@index@set"]);
+        // Step 4.8.7
+        type_language = TypeOrLanguage::Type;
+        type_language_value = Some("@id".to_owned());
+    } else {
+        // Step 4.9
+        match value_map.filter(|_| value.is_some_and(is_value_object)) {
+            // Step 4.9.1
+            Some(value) => {
+                let language = value.get("@language").and_then(Value::as_str);
+                let direction = value.get("@direction").and_then(Value::as_str);
+                if let (Some(direction), false) = (direction, contains("@index")) {
+                    // Step 4.9.1.1
+                    type_language_value = Some(
+                        format!("{}_{}", language.unwrap_or(""), direction).to_ascii_lowercase(),
+                    );
+                    containers.extend(&["@language", "@language@set"]);
+                } else if let (Some(language), false) = (language, contains("@index")) {
+                    // Step 4.9.1.2
+                    type_language_value = Some(language.to_ascii_lowercase());
+                    containers.extend(&["@language", "@language@set"]);
+                } else if let Some(ty) = value.get("@type").and_then(Value::as_str) {
+                    // Step 4.9.1.3
+                    type_language = TypeOrLanguage::Type;
+                    type_language_value = Some(ty.to_owned());
+                }
+            }
+            // Step 4.9.2
+            None => {
+                type_language = TypeOrLanguage::Type;
+                type_language_value = Some("@id".to_owned());
+                containers.extend(&["@id", "@id@set", "@type", "@set@type"]);
+            }
+        }
+        // Step 4.9.3
+        containers.push("@set");
+    }
+    // Step 4.10
+    containers.push("@none");
+    if !processor.is_processing_mode_1_0() {
+        // Step 4.11
+        if !contains("@index") {
+            containers.extend(&["@index", "@index@set"]);
+        }
+        // Step 4.12
+        if value_map.is_some_and(|map| map.len() == 1 && map.contains_key("@value")) {
+            containers.extend(&["@language", "@language@set"]);
+        }
+    }
+    // Step 4.13
+    let type_language_value = type_language_value.unwrap_or_else(|| "@null".to_owned());
+    // Step 4.14
+    let mut preferred_values: Vec<String> = Vec::new();
+    // Step 4.15
+    if type_language_value == "@reverse" {
+        preferred_values.push("@reverse".to_owned());
+    }
+    let id = value_map
+        .and_then(|map| map.get("@id"))
+        .and_then(Value::as_str);
+    match id {
+        // Step 4.16
+        Some(id) if type_language_value == "@id" || type_language_value == "@reverse" => {
+            let compacted_id = CompactIriOptions {
+                value: None,
+                vocab: true,
+                reverse: false,
+                ..options
+            }
+            .compact(processor, id)?;
+            let is_vocab_term = active_context
+                .term_definition(&compacted_id)
+                .is_some_and(|def| def.iri() == id);
+            if is_vocab_term {
+                // Step 4.16.1
+                preferred_values.extend(vec!["@vocab".into(), "@id".into(), "@none".into()]);
+            } else {
+                // Step 4.16.2
+                preferred_values.extend(vec!["@id".into(), "@vocab".into(), "@none".into()]);
+            }
+        }
+        // Step 4.17
+        _ => {
+            preferred_values.push(type_language_value);
+            preferred_values.push("@none".to_owned());
+            let is_empty_list = value_map
+                .and_then(|map| map.get("@list"))
+                .and_then(Value::as_array)
+                .is_some_and(Vec::is_empty);
+            if is_empty_list {
+                type_language = TypeOrLanguage::Any;
+            }
+        }
+    }
+    // Step 4.18
+    preferred_values.push("@any".to_owned());
+    // Step 4.19
+    let underscored = preferred_values
+        .iter()
+        .filter_map(|v| v.find('_').map(|pos| v[pos..].to_owned()))
+        .collect::<Vec<_>>();
+    preferred_values.extend(underscored);
+    // Step 4.20, 4.21
+    Ok(options
+        .inverse_context
+        .select_term(var, &containers, type_language, &preferred_values)
+        .map(ToOwned::to_owned))
+}
+
+/// Returns the common type and the common language of the list items.
+// Step 4.7.2-4.7.6
+fn list_common_type_and_language(list: &Value, default_language: &str) -> (String, String) {
+    // Step 4.7.2
+    let list = match list {
+        Value::Array(arr) => arr.as_slice(),
+        v => std::slice::from_ref(v),
+    };
+    // Step 4.7.3
+    let mut common_type: Option<String> = None;
+    let mut common_language: Option<String> = None;
+    if list.is_empty() {
+        common_language = Some(default_language.to_owned());
+    }
+    // Step 4.7.4
+    for item in list {
+        let item_map = item.as_object();
+        // Step 4.7.4.1, 4.7.4.2, 4.7.4.3
+        let (item_language, item_type) = item_language_and_type(item_map);
+        let has_value = item_map.is_some_and(|map| map.contains_key("@value"));
+        // Step 4.7.4.4, 4.7.4.5
+        match &common_language {
+            None => common_language = Some(item_language),
+            Some(lang) if *lang != item_language && has_value => {
+                common_language = Some("@none".to_owned())
+            }
+            Some(_) => {}
+        }
+        // Step 4.7.4.6, 4.7.4.7
+        match &common_type {
+            None => common_type = Some(item_type),
+            Some(ty) if *ty != item_type => common_type = Some("@none".to_owned()),
+            Some(_) => {}
+        }
+        // Step 4.7.4.8
+        if common_language.as_deref() == Some("@none") && common_type.as_deref() == Some("@none") {
+            break;
+        }
+    }
+    // Step 4.7.5, 4.7.6
+    (
+        common_type.unwrap_or_else(|| "@none".to_owned()),
+        common_language.unwrap_or_else(|| "@none".to_owned()),
+    )
+}
+
+/// Returns the language and the type of the list item.
+// Step 4.7.4.1-4.7.4.3
+fn item_language_and_type(item: Option<&JsonMap<String, Value>>) -> (String, String) {
+    let none = || "@none".to_owned();
+    let item = match item.filter(|map| map.contains_key("@value")) {
+        Some(item) => item,
+        // Step 4.7.4.3
+        None => return (none(), "@id".to_owned()),
+    };
+    let language = item.get("@language").and_then(Value::as_str);
+    // Step 4.7.4.2.1
+    if let Some(direction) = item.get("@direction").and_then(Value::as_str) {
+        let lang_dir = format!("{}_{}", language.unwrap_or(""), direction).to_ascii_lowercase();
+        return (lang_dir, none());
+    }
+    // Step 4.7.4.2.2
+    if let Some(language) = language {
+        return (language.to_ascii_lowercase(), none());
+    }
+    // Step 4.7.4.2.3
+    if let Some(ty) = item.get("@type").and_then(Value::as_str) {
+        return (none(), ty.to_owned());
+    }
+
+    // Step 4.7.4.2.4
+    ("@null".to_owned(), none())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{context, processor};
+
+    #[test]
+    fn compact_to_term_or_compact_iri() {
+        let ctx = context(json!({
+            "@vocab": "http://example.org/",
+            "foaf": "http://xmlns.com/foaf/0.1/",
+            "name": "http://xmlns.com/foaf/0.1/name",
+            "id": "@id"
+        }));
+        let compact = |iri, vocab| ctx.compact_iri(&processor(), iri, vocab).unwrap();
+        assert_eq!(compact("http://xmlns.com/foaf/0.1/name", true), "name");
+        assert_eq!(
+            compact("http://xmlns.com/foaf/0.1/knows", true),
+            "foaf:knows"
+        );
+        assert_eq!(compact("http://example.org/Person", true), "Person");
+        assert_eq!(compact("@id", true), "id");
+        assert_eq!(
+            compact("http://example.net/x", true),
+            "http://example.net/x"
+        );
+        // Terms and vocabulary mapping are not used for document-relative IRIs.
+        assert_eq!(
+            compact("http://xmlns.com/foaf/0.1/name", false),
+            "foaf:name"
+        );
+        assert_eq!(
+            compact("http://example.org/Person", false),
+            "http://example.org/Person"
+        );
+        assert_eq!(compact("http://example.com/other#me", false), "other#me");
+    }
+
+    #[test]
+    fn select_term_by_value() {
+        let ctx = context(json!({
+            "label": "http://example.org/label",
+            "labelEn": { "@id": "http://example.org/label", "@language": "en" },
+            "labels": { "@id": "http://example.org/label", "@container": "@list" },
+            "ref": { "@id": "http://example.org/label", "@type": "@id" }
+        }));
+        let inverse = InverseContext::new(&ctx);
+        let processor = processor();
+        let compact = |value| {
+            CompactIriOptions::new(&ctx, &inverse)
+                .value(Some(&value))
+                .vocab(true)
+                .compact(&processor, "http://example.org/label")
+                .unwrap()
+        };
+        assert_eq!(compact(json!({ "@value": "x" })), "label");
+        assert_eq!(
+            compact(json!({ "@value": "x", "@language": "en" })),
+            "labelEn"
+        );
+        assert_eq!(compact(json!({ "@list": [{ "@value": "x" }] })), "labels");
+        assert_eq!(compact(json!({ "@id": "http://example.org/x" })), "ref");
+    }
+
+    #[test]
+    fn iri_confused_with_prefix() {
+        let ctx = context(json!({
+            "tag": { "@id": "http://example.org/tag/", "@prefix": true }
+        }));
+        let err = ctx
+            .compact_iri(&processor(), "tag:example.org,2019:x", true)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::IriConfusedWithPrefix);
+    }
+}
@@ -0,0 +1,103 @@
+//! Value compaction.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#value-compaction>.
+
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    compact::{inverse::InverseContext, iri::CompactIriOptions},
+    context::{ContainerItem, Context, Definition},
+    error::Result,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+/// Runs value compaction algorithm.
+///
+/// `inverse_context` should be created from `active_context`.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#value-compaction>.
+pub(crate) fn compact_value<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    inverse_context: &InverseContext,
+    active_property: Option<&str>,
+    value: &JsonMap<String, Value>,
+) -> Result<Value> {
+    let compact_iri = |iri: &str, vocab: bool| {
+        CompactIriOptions::new(active_context, inverse_context)
+            .vocab(vocab)
+            .compact(processor, iri)
+    };
+    let definition = active_property.and_then(|prop| active_context.term_definition(prop));
+    let type_mapping = definition.and_then(Definition::ty);
+    // Step 1
+    let mut result = value.clone();
+    // Step 2, 3: The inverse context is given.
+    // Step 4
+    let language = match definition.and_then(Definition::language) {
+        Some(language) => language.into(),
+        None => active_context.default_language(),
+    };
+    // Step 5
+    let direction = match definition.and_then(Definition::direction) {
+        Some(direction) => direction.into(),
+        None => active_context.default_base_direction(),
+    };
+    // Whether the `@index` entry can be dropped.
+    let is_index_droppable = !value.contains_key("@index")
+        || definition.is_some_and(|def| def.container_contains(ContainerItem::Index));
+    let value_type = value.get("@type").and_then(Value::as_str);
+
+    if value.contains_key("@id") && value.keys().all(|k| k == "@id" || k == "@index") {
+        // Step 6
+        let id = value["@id"].as_str();
+        match (type_mapping, id) {
+            // Step 6.1
+            (Some("@id"), Some(id)) => return compact_iri(id, false).map(Value::String),
+            // Step 6.2
+            (Some("@vocab"), Some(id)) => return compact_iri(id, true).map(Value::String),
+            _ => {}
+        }
+    } else if !value.contains_key("@value") {
+        // `value` is a node object, which is not compacted to a scalar.
+    } else if value_type.is_some() && value_type == type_mapping && is_index_droppable {
+        // Step 7
+        return Ok(value["@value"].clone());
+    } else if type_mapping == Some("@none") || value_type.is_some() {
+        // Step 8
+        // Step 8.1
+        if let Some(value_type) = value_type {
+            result.insert("@type".into(), compact_iri(value_type, true)?.into());
+        }
+    } else if !value.get("@value").is_some_and(Value::is_string) {
+        // Step 9
+        // Step 9.1
+        if is_index_droppable {
+            return Ok(value["@value"].clone());
+        }
+    } else {
+        // Step 10
+        let value_language = value.get("@language").and_then(Value::as_str);
+        let is_language_matched = match (value_language, language) {
+            (Some(value_language), Some(language)) => value_language.eq_ignore_ascii_case(language),
+            (None, None) => true,
+            _ => false,
+        };
+        let value_direction = value.get("@direction").and_then(Value::as_str);
+        let is_direction_matched = value_direction == direction.map(|dir| dir.as_str());
+        // Step 10.1
+        if is_language_matched && is_direction_matched && is_index_droppable {
+            return Ok(value["@value"].clone());
+        }
+    }
+
+    // Step 11
+    let mut compacted = JsonMap::new();
+    for (key, value) in result {
+        compacted.insert(compact_iri(&key, true)?, value);
+    }
+
+    // Step 12
+    Ok(Value::Object(compacted))
+}
@@ -7,9 +7,18 @@ use std::collections::HashMap;
 use iri_string::types::{IriStr, IriString};
 use serde_json::{Map as JsonMap, Value};
 
-use crate::{error::Result, json::Nullable, processor::Processor, remote::LoadRemoteDocument};
+use anyhow::anyhow;
 
-pub(crate) use self::definition::Definition;
+use crate::{
+    compact::{CompactIriOptions, InverseContext},
+    error::{ErrorCode, Result},
+    expand,
+    json::Nullable,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+pub(crate) use self::definition::{ContainerItem, Definition, Direction};
 use self::{
     create_term_def::{create_term_definition, OptionalParams as CreateTermDefOptionalParams},
     merge::OptionalParams as MergeOptionalParams,
@@ -74,11 +83,21 @@ impl Context {
         self.vocab = vocab.into();
     }
 
+    /// Returns the default language.
+    pub(crate) fn default_language(&self) -> Option<&str> {
+        self.default_language.as_ref().map(AsRef::as_ref)
+    }
+
     /// Sets the default language.
     pub(crate) fn set_default_language(&mut self, lang: Option<String>) {
         self.default_language = lang;
     }
 
+    /// Returns the default base direction.
+    pub(crate) fn default_base_direction(&self) -> Option<definition::Direction> {
+        self.default_base_direction
+    }
+
     /// Sets the default base direction.
     pub(crate) fn set_default_base_direction(&mut self, dir: Option<definition::Direction>) {
         self.default_base_direction = dir;
@@ -100,6 +119,15 @@ impl Context {
             .and_then(|v| v.as_ref().into())
     }
 
+    /// Returns an iterator of terms and their term definitions.
+    ///
+    /// Terms set to explicit `null` are yielded with `None`.
+    pub(crate) fn term_definitions(&self) -> impl Iterator<Item = (&str, Option<&Definition>)> {
+        self.term_definitions
+            .iter()
+            .map(|(term, def)| (term.as_str(), def.as_ref().into()))
+    }
+
     /// Removes the given term definition.
     ///
     /// This does nothing if the given term is not in the context.
@@ -133,6 +161,11 @@ impl Context {
         self.previous_context.is_some()
     }
 
+    /// Returns the previous context.
+    pub(crate) fn previous_context(&self) -> Option<&Self> {
+        self.previous_context.as_deref()
+    }
+
     /// Checks whether the context has any protected term definition.
     pub(crate) fn has_protected_term_definition(&self) -> bool {
         self.term_definitions
@@ -164,6 +197,29 @@ impl Context {
         .await
     }
 
+    /// Runs context processing algorithm for a scoped context and returns a new context.
+    ///
+    /// This is used to apply property-scoped and type-scoped contexts.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#context-processing-algorithm>.
+    pub(crate) async fn join_scoped_context<L: LoadRemoteDocument>(
+        &self,
+        processor: &Processor<L>,
+        local_context: ValueWithBase<'_, &Value>,
+        override_protected: bool,
+        propagate: bool,
+    ) -> Result<Self> {
+        merge::join_value(
+            processor,
+            self,
+            local_context,
+            MergeOptionalParams::new()
+                .override_protected(override_protected)
+                .propagate(propagate),
+        )
+        .await
+    }
+
     /// Runs context processing algorithm and returns a new context.
     ///
     /// This receives a JSON value which contains `@context` entry.
@@ -190,6 +246,59 @@ impl Context {
             Ok(self.clone())
         }
     }
+
+    /// Runs value expansion algorithm for the given scalar and returns the expanded value.
+    ///
+    /// The type mapping, language mapping, and direction mapping of the term definition for
+    /// `active_property` are applied, falling back to the default language and the default base
+    /// direction of the context.
+    /// The result is a value object, or a node reference (a map with `@id` entry) if the type
+    /// mapping is `@id` or `@vocab`.
+    ///
+    /// Returns `InvalidValueObjectValue` error if `value` is not a scalar.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#value-expansion>.
+    pub async fn expand_value<L: LoadRemoteDocument>(
+        &self,
+        processor: &Processor<L>,
+        active_property: Option<&str>,
+        value: &Value,
+    ) -> Result<JsonMap<String, Value>> {
+        match value {
+            Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+            v => {
+                return Err(ErrorCode::InvalidValueObjectValue
+                    .and_source(anyhow!("Expected a scalar, but got {:?}", v)))
+            }
+        }
+
+        expand::expand_value(processor, self, active_property, value).await
+    }
+
+    /// Runs IRI compaction algorithm and returns the compacted IRI.
+    ///
+    /// If `vocab` is true, the IRI is compacted to a term, a compact IRI, or a suffix relative to
+    /// the vocabulary mapping, as for properties and types.
+    /// If `vocab` is false, the IRI is compacted to a compact IRI or a reference relative to the
+    /// base IRI, as for `@id` values.
+    ///
+    /// Returns `IriConfusedWithPrefix` error if the IRI cannot be left as is because its scheme
+    /// is defined as a prefix.
+    ///
+    /// Note that this creates an inverse context for each call.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#iri-compaction>.
+    pub fn compact_iri<L: LoadRemoteDocument>(
+        &self,
+        processor: &Processor<L>,
+        iri: &str,
+        vocab: bool,
+    ) -> Result<String> {
+        let inverse_context = InverseContext::new(self);
+        CompactIriOptions::new(self, &inverse_context)
+            .vocab(vocab)
+            .compact(processor, iri)
+    }
 }
 
 /// A value with the base IRI of the document containing that value.
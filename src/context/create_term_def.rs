@@ -22,14 +22,12 @@ mod non_reverse;
 mod reverse;
 
 /// Optional parameters (arguments) for create term definition algorithm.
-#[derive(Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy)]
 pub(crate) struct OptionalParams {
     /// Protected.
     protected: bool,
     /// Override protected.
     override_protected: bool,
-    /// Propagate.
-    propagate: bool,
 }
 
 impl OptionalParams {
@@ -41,18 +39,11 @@ impl OptionalParams {
         }
     }
 
-    /// Sets the `propagate` option if available.
-    pub(crate) fn propagate(self, propagate: bool) -> Self {
-        Self { propagate, ..self }
-    }
-}
-
-impl Default for OptionalParams {
-    fn default() -> Self {
+    /// Sets the `override_protected` option.
+    pub(crate) fn override_protected(self, override_protected: bool) -> Self {
         Self {
-            protected: false,
-            override_protected: false,
-            propagate: true,
+            override_protected,
+            ..self
         }
     }
 }
@@ -303,7 +294,7 @@ async fn process_type<L: LoadRemoteDocument>(
                 )));
             }
             // Step 13.4, 13.5
-            if ty == "@id" || ty == "@vocab" || is_absolute_iri_ref(&ty) {
+            if matches!(&*ty, "@id" | "@json" | "@none" | "@vocab") || is_absolute_iri_ref(&ty) {
                 definition.set_ty(ty);
             } else {
                 return Err(
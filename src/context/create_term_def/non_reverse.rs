@@ -14,8 +14,8 @@ use crate::{
     error::{ErrorCode, Result},
     expand::iri::ExpandIriOptions,
     iri::{
-        is_absolute_iri_ref, is_absolute_ref_or_blank_node_ident, is_compact_iri,
-        is_gen_delims_byte, to_prefix_and_suffix,
+        is_absolute_iri_ref, is_absolute_ref_or_blank_node_ident, is_blank_node_ident,
+        is_compact_iri, is_gen_delims_byte, to_prefix_and_suffix,
     },
     json::Nullable,
     processor::{Processor, ProcessorOptions},
@@ -81,8 +81,8 @@ pub(crate) async fn run_for_non_reverse<L: LoadRemoteDocument>(
     // Step 28
     for key in value.keys() {
         match key.as_str() {
-            "@id" | "@reverse" | "@container" | "@context" | "@language" | "@nest" | "@prefix"
-            | "@type" => {}
+            "@id" | "@reverse" | "@container" | "@context" | "@direction" | "@index"
+            | "@language" | "@nest" | "@prefix" | "@protected" | "@type" => {}
             v => {
                 return Err(ErrorCode::InvalidTermDefinition
                     .and_source(anyhow!("Unexpected entry: key={:?}", v)))
@@ -194,15 +194,19 @@ async fn process_iri<L: LoadRemoteDocument>(
                 definition.set_iri(id);
                 let id = definition.iri();
                 // Step 16.5
-                if (!term.is_empty() && term[1..(term.len() - 1)].contains(':'))
+                if term
+                    .char_indices()
+                    .any(|(i, c)| c == ':' && i != 0 && i != term.len() - 1)
                     || term.contains('/')
                 {
+                    // Mark the term as defined to avoid it being detected as a cyclic IRI mapping.
+                    defined.insert(term.to_owned(), true);
                     let expanded =
                         ExpandIriOptions::mutable(active_context, local_context, defined)
                             .vocab(true)
                             .expand_str(processor, term)
                             .await?;
-                    if expanded.as_ref().map(|s| &**s) != Some(id) {
+                    if expanded.as_deref() != Some(id) {
                         return Err(ErrorCode::InvalidIriMapping.and_source(anyhow!(
                             "expanded={:?}, term={:?}",
                             expanded,
@@ -214,7 +218,7 @@ async fn process_iri<L: LoadRemoteDocument>(
                 if !term.contains(':')
                     && !term.contains('/')
                     && simple_term
-                    && is_gen_delims_byte(id.as_bytes()[id.len() - 1])
+                    && (is_gen_delims_byte(id.as_bytes()[id.len() - 1]) || is_blank_node_ident(id))
                 {
                     definition.set_prefix(true);
                 }
@@ -410,12 +414,15 @@ async fn process_local_context<L: LoadRemoteDocument>(
         }
         // Step 23.2: `context` is already the value associated with the `@context` entry.
         // Step 23.3
-        let context: Context = active_context
+        // Note that the processed context is used only for validation.
+        // The scoped context should be processed against the active context at the time the term
+        // is used, so the raw value is stored to the definition.
+        active_context
             .join_context_value(processor, context, value.base(), true)
             .await
             .map_err(|e| ErrorCode::InvalidScopedContext.and_source(e))?;
         // Step 23.4
-        definition.set_local_context(context);
+        definition.set_local_context(context.clone(), value.base().to_owned());
     }
 
     Ok(())
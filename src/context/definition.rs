@@ -1,6 +1,9 @@
 //! Term definition.
 
-use crate::{context::Context, json::Nullable};
+use iri_string::types::{IriStr, IriString};
+use serde_json::Value;
+
+use crate::{context::ValueWithBase, json::Nullable};
 
 pub(crate) use self::{
     builder::DefinitionBuilder,
@@ -30,9 +33,16 @@ pub(crate) struct Definition {
     /// Lanugage mapping (optional).
     language: Option<Nullable<String>>,
     /// Direction mapping (optional).
-    direction: Option<Direction>,
-    /// Context (optional).
-    context: Option<Context>,
+    ///
+    /// This property distinguishes explicit `null`.
+    direction: Option<Nullable<Direction>>,
+    /// Local context (optional).
+    ///
+    /// This is a raw (unprocessed) context value, because it should be processed against the
+    /// active context at the time the term is used.
+    context: Option<Value>,
+    /// Base URL of the local context (optional).
+    base_url: Option<IriString>,
     /// Nest value (optional).
     nest: Option<String>,
     /// Prefix flag (optoinal).
@@ -51,6 +61,66 @@ impl Definition {
         &self.iri
     }
 
+    /// Returns the reverse property flag.
+    pub(crate) fn is_reverse(&self) -> bool {
+        self.reverse
+    }
+
+    /// Returns the type mapping.
+    pub(crate) fn ty(&self) -> Option<&str> {
+        self.ty.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the language mapping.
+    ///
+    /// This distinguishes absence and explicit `null`.
+    pub(crate) fn language(&self) -> Option<Nullable<&str>> {
+        self.language
+            .as_ref()
+            .map(|lang| lang.as_ref().map(AsRef::as_ref))
+    }
+
+    /// Returns the direction mapping.
+    ///
+    /// `Some(Nullable::Null)` means the direction is explicitly set to `null`.
+    pub(crate) fn direction(&self) -> Option<Nullable<Direction>> {
+        self.direction
+    }
+
+    /// Returns the local context and its base URL.
+    pub(crate) fn local_context(&self) -> Option<ValueWithBase<'_, &Value>> {
+        let context = self.context.as_ref()?;
+        let base: &IriStr = self
+            .base_url
+            .as_ref()
+            .expect("Should never fail: base URL is always set with the local context")
+            .as_ref();
+        Some(ValueWithBase::new(context, base))
+    }
+
+    /// Returns the nest value.
+    pub(crate) fn nest(&self) -> Option<&str> {
+        self.nest.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the index mapping.
+    pub(crate) fn index(&self) -> Option<&str> {
+        self.index.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the container mapping.
+    pub(crate) fn container(&self) -> Option<Container> {
+        self.container
+    }
+
+    /// Checks if the container mapping contains the given value.
+    ///
+    /// Returns `false` if the container mapping is not set.
+    pub(crate) fn container_contains(&self, v: ContainerItem) -> bool {
+        self.container
+            .is_some_and(|container| container.contains(v))
+    }
+
     /// Returns the prefix flag.
     pub(crate) fn is_prefix(&self) -> bool {
         self.prefix.unwrap_or(false)
@@ -71,6 +141,7 @@ impl Definition {
             && self.language == other.language
             && self.direction == other.direction
             && self.context == other.context
+            && self.base_url == other.base_url
             && self.nest == other.nest
             && self.prefix == other.prefix
             && self.index == other.index
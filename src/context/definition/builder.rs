@@ -1,9 +1,12 @@
 //! Definition builder.
 
+use iri_string::types::IriString;
+use serde_json::Value;
+
 use crate::{
     context::{
         definition::{Container, ContainerItem, Direction},
-        Context, Definition,
+        Definition,
     },
     json::Nullable,
 };
@@ -23,9 +26,13 @@ pub(crate) struct DefinitionBuilder {
     /// This property distinguishes explicit `null`.
     language: Option<Nullable<String>>,
     /// Direction mapping (optional).
-    direction: Option<Direction>,
-    /// Context (optional).
-    context: Option<Context>,
+    ///
+    /// This property distinguishes explicit `null`.
+    direction: Option<Nullable<Direction>>,
+    /// Local context (optional).
+    context: Option<Value>,
+    /// Base URL of the local context (optional).
+    base_url: Option<IriString>,
     /// Nest value (optional).
     nest: Option<String>,
     /// Prefix flag (optoinal).
@@ -59,6 +66,7 @@ impl DefinitionBuilder {
             language: self.language,
             direction: self.direction,
             context: self.context,
+            base_url: self.base_url,
             nest: self.nest,
             prefix: self.prefix,
             index: self.index,
@@ -103,12 +111,13 @@ impl DefinitionBuilder {
 
     /// Sets the direction mapping.
     pub(crate) fn set_direction(&mut self, v: Nullable<Direction>) {
-        self.direction = v.into();
+        self.direction = Some(v);
     }
 
-    /// Sets the local context.
-    pub(crate) fn set_local_context(&mut self, v: Context) {
+    /// Sets the local context and its base URL.
+    pub(crate) fn set_local_context(&mut self, v: Value, base_url: IriString) {
         self.context = Some(v);
+        self.base_url = Some(base_url);
     }
 
     /// Sets the nest value.
@@ -142,7 +151,7 @@ impl DefinitionBuilder {
     pub(crate) fn container_contains(&self, v: ContainerItem) -> bool {
         self.container
             .as_ref()
-            .map_or(false, |container| container.contains(v))
+            .is_some_and(|container| container.contains(v))
     }
 
     /// Returns the container mapping.
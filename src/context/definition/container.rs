@@ -27,6 +27,19 @@ pub enum ContainerItem {
 }
 
 impl ContainerItem {
+    /// Returns the string representation.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Graph => "@graph",
+            Self::Id => "@id",
+            Self::Index => "@index",
+            Self::Language => "@language",
+            Self::List => "@list",
+            Self::Set => "@set",
+            Self::Type => "@type",
+        }
+    }
+
     /// Returns an integer with distinct single bit set.
     fn single_bit(self) -> u8 {
         let shift = match self {
@@ -200,7 +213,7 @@ impl iter::FromIterator<ContainerItem> for Container {
         T: IntoIterator<Item = ContainerItem>,
     {
         let mut v = Container::new();
-        v.extend(iter.into_iter());
+        v.extend(iter);
         v
     }
 }
@@ -27,7 +27,7 @@ mod ctx_def;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OptionalParams {
     /// Remote contexts.
-    remote_contexts: HashSet<IriString>,
+    remote_contexts: RemoteContexts,
     /// "Override protected" flag.
     override_protected: bool,
     /// "Propagate" flag.
@@ -47,6 +47,22 @@ impl OptionalParams {
             ..self
         }
     }
+
+    /// Sets the "propagate" flag.
+    pub(crate) fn propagate(self, propagate: bool) -> Self {
+        Self { propagate, ..self }
+    }
+}
+
+/// Remote contexts.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct RemoteContexts {
+    /// Remote contexts dereferenced so far, which count toward the limit.
+    ///
+    /// Note that the same context can appear more than once.
+    dereferenced: Vec<IriString>,
+    /// Remote contexts which (directly or indirectly) include the context being processed.
+    including: HashSet<IriString>,
 }
 
 impl Default for OptionalParams {
@@ -97,7 +113,7 @@ fn join_value_impl_recursive<'a, L: LoadRemoteDocument>(
     processor: &'a Processor<L>,
     active_context: &'a Context,
     local_context: ValueWithBase<'a, &'a Value>,
-    remote_contexts: HashSet<IriString>,
+    remote_contexts: RemoteContexts,
     override_protected: bool,
     propagate: bool,
     remote_contexts_cache: &'a mut HashMap<IriString, Arc<RemoteDocument>>,
@@ -123,7 +139,7 @@ async fn join_value_impl<L: LoadRemoteDocument>(
     processor: &Processor<L>,
     active_context: &Context,
     local_context: ValueWithBase<'_, &Value>,
-    mut remote_contexts: HashSet<IriString>,
+    mut remote_contexts: RemoteContexts,
     override_protected: bool,
     propagate: bool,
     remote_contexts_cache: &mut HashMap<IriString, Arc<RemoteDocument>>,
@@ -142,7 +158,7 @@ async fn join_value_impl<L: LoadRemoteDocument>(
         .and_then(Value::as_bool)
         .unwrap_or(propagate);
     // Step 3
-    if !propagate && result.has_previous_context() {
+    if !propagate && !result.has_previous_context() {
         result.previous_context = Some(Box::new(active_context.clone()));
     }
     // Step 4
@@ -154,8 +170,13 @@ async fn join_value_impl<L: LoadRemoteDocument>(
             // Step 5.1
             Value::Null => {
                 // Step 5.1.1, 5.1.2
-                result =
-                    process_single_null(active_context, override_protected, propagate, result)?;
+                result = process_single_null(
+                    processor,
+                    active_context,
+                    override_protected,
+                    propagate,
+                    result,
+                )?;
             }
             // Step 5.2
             Value::String(context) => {
@@ -177,9 +198,8 @@ async fn join_value_impl<L: LoadRemoteDocument>(
             Value::Object(context) => {
                 result = process_context_definition(
                     processor,
-                    active_context,
-                    &mut remote_contexts,
-                    propagate,
+                    &remote_contexts.dereferenced,
+                    override_protected,
                     result,
                     local_context.with_new_value(context),
                 )
@@ -199,7 +219,8 @@ async fn join_value_impl<L: LoadRemoteDocument>(
 }
 
 /// Processes single context which is `null`.
-fn process_single_null(
+fn process_single_null<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
     active_context: &Context,
     override_protected: bool,
     propagate: bool,
@@ -212,7 +233,11 @@ fn process_single_null(
     // Step 5.1.2
     // > set result to a newly-initialized _active context_, setting _previous context_
     // > in _result_ to the previous value of _result_ if propagate is `false`.
-    let previous_context = std::mem::replace(&mut result, Context::new());
+    // Note that a newly-initialized active context has the original base IRI.
+    let previous_context = std::mem::replace(
+        &mut result,
+        Context::with_base(processor.options().document_iri().to_owned()),
+    );
     if !propagate {
         result.previous_context = Some(Box::new(previous_context));
     }
@@ -224,7 +249,7 @@ fn process_single_null(
 #[allow(clippy::too_many_arguments)] // TODO: FIXME
 async fn process_single_string<L: LoadRemoteDocument>(
     processor: &Processor<L>,
-    remote_contexts: &mut HashSet<IriString>,
+    remote_contexts: &mut RemoteContexts,
     override_protected: bool,
     propagate: bool,
     remote_contexts_cache: &mut HashMap<IriString, Arc<RemoteDocument>>,
@@ -244,13 +269,22 @@ async fn process_single_string<L: LoadRemoteDocument>(
         context.resolve_against(base.to_absolute())
     };
     // Step 5.2.2
-    if !processor.is_remote_context_limit_exceeded(remote_contexts.len()) {
+    // NOTE: Recursive inclusion would never end if the number of remote contexts is not limited,
+    // so it is detected separately and reported as an overflow.
+    if remote_contexts.including.contains(&context) {
+        return Err(ErrorCode::ContextOverflow.and_source(anyhow!(
+            "Recursive inclusion of the remote context {:?}",
+            context
+        )));
+    }
+    remote_contexts.dereferenced.push(context.clone());
+    if processor.is_remote_context_limit_exceeded(remote_contexts.dereferenced.len()) {
         return Err(ErrorCode::ContextOverflow.and_source(anyhow!(
-            "Current number of remote contexts = {:?}",
-            remote_contexts.len()
+            "Too many remote contexts: limit = {:?}, context = {:?}",
+            processor.options().allowed_max_remote_context(),
+            context
         )));
     }
-    remote_contexts.insert(context.clone());
     // Step 5.2.3-5.2.4
     // > If _context_ was previously dereferenced, then the processor MUST NOT do a
     // > further dereference, and _context_ is set to the previously established
@@ -271,17 +305,29 @@ async fn process_single_string<L: LoadRemoteDocument>(
             entry.insert(doc).clone()
         }
     };
+    let requested_iri = context;
     // Step 5.2.5
-    let context_iri = context;
+    // NOTE: Step 5.2.6 uses `documentUrl` of the context document as the base URL, which can
+    // differ from _context_ after redirects.
+    let context_iri = IriStr::new(remote_doc.base_iri()).map_err(|e| {
+        ErrorCode::LoadingRemoteContextFailed
+            .and_source(e)
+            .context(format!(
+                "Invalid document URL of the remote context {:?}",
+                requested_iri
+            ))
+    })?;
     let context = remote_doc.document().get("@context").ok_or_else(|| {
         ErrorCode::InvalidRemoteContext.and_source(anyhow!("doc = {:?}", remote_doc))
     })?;
     // Step 5.2.6
+    let mut nested_remote_contexts = remote_contexts.clone();
+    nested_remote_contexts.including.insert(requested_iri);
     result = join_value_impl_recursive(
         processor,
         &result,
-        ValueWithBase::new(context, &context_iri),
-        remote_contexts.clone(),
+        ValueWithBase::new(context, context_iri),
+        nested_remote_contexts,
         override_protected,
         propagate,
         remote_contexts_cache,
@@ -290,3 +336,145 @@ async fn process_single_string<L: LoadRemoteDocument>(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use iri_string::types::IriStr;
+    use serde_json::{json, Value};
+
+    use crate::{
+        remote::{LoadDocumentOptions, LoadRemoteDocument, RemoteDocument},
+        test_utils::{block_on, processor, MapLoader, NoLoaderError},
+        ErrorCode,
+    };
+
+    /// Expands the input with the given remote contexts and the limit.
+    fn expand(
+        contexts: &[(&str, Value)],
+        limit: Option<usize>,
+        input: Value,
+    ) -> crate::Result<Value> {
+        let loader = MapLoader(
+            contexts
+                .iter()
+                .map(|(iri, context)| ((*iri).to_owned(), context.clone()))
+                .collect(),
+        );
+        let mut options = processor().options().clone();
+        options.set_max_remote_contexts(limit);
+        block_on(options.build(loader).expand(&input))
+    }
+
+    #[test]
+    fn remote_context_limit() {
+        let contexts = [
+            (
+                "http://example.com/a.jsonld",
+                json!({ "@context": ["http://example.com/b.jsonld", { "a": "http://example.org/a" }] }),
+            ),
+            (
+                "http://example.com/b.jsonld",
+                json!({ "@context": { "b": "http://example.org/b" } }),
+            ),
+        ];
+        let input = json!({ "@context": "http://example.com/a.jsonld", "a": 1, "b": 2 });
+        let expected = json!([{
+            "http://example.org/a": [{ "@value": 1 }],
+            "http://example.org/b": [{ "@value": 2 }]
+        }]);
+        assert_eq!(expand(&contexts, Some(2), input.clone()).unwrap(), expected);
+        assert_eq!(expand(&contexts, None, input.clone()).unwrap(), expected);
+        assert_eq!(
+            expand(&contexts, Some(1), input).unwrap_err().code(),
+            ErrorCode::ContextOverflow
+        );
+    }
+
+    #[test]
+    fn recursive_remote_context() {
+        let contexts = [(
+            "http://example.com/loop.jsonld",
+            json!({ "@context": "http://example.com/loop.jsonld" }),
+        )];
+        let input = json!({ "@context": "http://example.com/loop.jsonld" });
+        assert_eq!(
+            expand(&contexts, Some(10), input.clone())
+                .unwrap_err()
+                .code(),
+            ErrorCode::ContextOverflow
+        );
+        assert_eq!(
+            expand(&contexts, None, input).unwrap_err().code(),
+            ErrorCode::ContextOverflow
+        );
+
+        // Repeated contexts are not recursive.
+        let contexts = [(
+            "http://example.com/a.jsonld",
+            json!({ "@context": { "a": "http://example.org/a" } }),
+        )];
+        let input = json!({
+            "@context": ["http://example.com/a.jsonld", "http://example.com/a.jsonld"],
+            "a": 1
+        });
+        assert_eq!(
+            expand(&contexts, None, input).unwrap(),
+            json!([{ "http://example.org/a": [{ "@value": 1 }] }])
+        );
+    }
+
+    /// Loader which redirects `http://example.com/` to `http://example.org/moved/`.
+    struct RedirectLoader;
+
+    #[async_trait]
+    impl LoadRemoteDocument for RedirectLoader {
+        type Error = NoLoaderError;
+
+        async fn load(
+            &self,
+            iri: &IriStr,
+            _options: LoadDocumentOptions,
+        ) -> Result<Arc<RemoteDocument>, Self::Error> {
+            let document = match iri.as_str() {
+                "http://example.com/a.jsonld" => json!({ "@context": "b.jsonld" }),
+                "http://example.org/moved/b.jsonld" => {
+                    json!({ "@context": { "b": "http://example.org/b" } })
+                }
+                "http://example.com/c.jsonld" => json!({ "@context": { "@import": "d.jsonld" } }),
+                "http://example.org/moved/d.jsonld" => {
+                    json!({ "@context": { "d": "http://example.org/d" } })
+                }
+                _ => return Err(NoLoaderError(iri.as_str().to_owned())),
+            };
+            let document_url = iri
+                .as_str()
+                .replace("http://example.com/", "http://example.org/moved/");
+            Ok(Arc::new(
+                RemoteDocument::builder(document_url).json(document),
+            ))
+        }
+    }
+
+    #[test]
+    fn redirected_remote_context() {
+        let input = json!({ "@context": "http://example.com/a.jsonld", "b": 1 });
+        let processor = processor().options().clone().build(RedirectLoader);
+        assert_eq!(
+            block_on(processor.expand(&input)).unwrap(),
+            json!([{ "http://example.org/b": [{ "@value": 1 }] }])
+        );
+    }
+
+    #[test]
+    fn redirected_remote_context_import() {
+        let input = json!({ "@context": "http://example.com/c.jsonld", "d": 1 });
+        let processor = processor().options().clone().build(RedirectLoader);
+        assert_eq!(
+            block_on(processor.expand(&input)).unwrap(),
+            json!([{ "http://example.org/d": [{ "@value": 1 }] }])
+        );
+    }
+}
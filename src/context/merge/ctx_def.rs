@@ -1,11 +1,6 @@
 //! Processing function for a context definition.
 
-use std::{
-    borrow::Cow,
-    collections::{HashMap, HashSet},
-    convert::TryInto,
-    sync::Arc,
-};
+use std::{borrow::Cow, collections::HashMap, convert::TryInto, sync::Arc};
 
 use anyhow::anyhow;
 use iri_string::types::{IriReferenceStr, IriStr, IriString, RelativeIriStr};
@@ -27,9 +22,8 @@ use crate::{
 /// Processes single context which is a map.
 pub(crate) async fn process_context_definition<L: LoadRemoteDocument>(
     processor: &Processor<L>,
-    active_context: &Context,
-    remote_contexts: &mut HashSet<IriString>,
-    propagate: bool,
+    remote_contexts: &[IriString],
+    override_protected: bool,
     mut result: Context,
     context: ValueWithBase<'_, &JsonMap<String, Value>>,
 ) -> Result<Context> {
@@ -39,7 +33,7 @@ pub(crate) async fn process_context_definition<L: LoadRemoteDocument>(
     // Step 5.6
     let context: ValueWithBase<'_, Cow<'_, _>> = {
         let new_context: Cow<JsonMap<String, Value>> =
-            process_ctxdef_import(processor, active_context, context.value()).await?;
+            process_ctxdef_import(processor, context.base(), context.value()).await?;
         context.with_new_value(new_context)
     };
     let context: ValueWithBase<'_, &JsonMap<_, _>> = context.with_new_value(context.value());
@@ -59,6 +53,11 @@ pub(crate) async fn process_context_definition<L: LoadRemoteDocument>(
     // Step 5.13.
     let protected = match context.value().get("@protected") {
         None => None,
+        Some(_) if processor.is_processing_mode_1_0() => {
+            return Err(ErrorCode::InvalidContextEntry.and_source(anyhow!(
+                "Found `@protected` while processing mode is `json-ld-1.0`"
+            )))
+        }
         Some(Value::Bool(v)) => Some(*v),
         Some(v) => {
             return Err(ErrorCode::Uncategorized
@@ -66,7 +65,7 @@ pub(crate) async fn process_context_definition<L: LoadRemoteDocument>(
         }
     };
     let options = OptionalParams::new()
-        .propagate(propagate)
+        .override_protected(override_protected)
         .protected_opt(protected);
     for key in context.value().keys().map(String::as_str) {
         match key {
@@ -88,7 +87,7 @@ fn process_ctxdef_version(
     // Step 5.5
     if let Some(version) = context.get("@version") {
         // Step 5.5.1
-        let is_1_1 = version.as_f64().map_or(false, |v| v >= 1.09 && v <= 1.11);
+        let is_1_1 = version.as_f64().is_some_and(|v| (1.09..=1.11).contains(&v));
         if !is_1_1 {
             return Err(
                 ErrorCode::InvalidVersionValue.and_source(anyhow!("`@version` = {:?}", version))
@@ -108,7 +107,7 @@ fn process_ctxdef_version(
 /// Processes `@import` entry of the context definition.
 async fn process_ctxdef_import<'a, L: LoadRemoteDocument>(
     processor: &Processor<L>,
-    active_context: &Context,
+    base: &IriStr,
     context: &'a JsonMap<String, Value>,
 ) -> Result<Cow<'a, JsonMap<String, Value>>> {
     // Step 5.6
@@ -129,10 +128,6 @@ async fn process_ctxdef_import<'a, L: LoadRemoteDocument>(
     })?;
     // Step 5.6.3
     let import = {
-        let base = match processor.base(&active_context) {
-            Some(v) => v,
-            None => unimplemented!("FIXME: What to do if no base IRI available?"),
-        };
         let import = IriReferenceStr::new(import).map_err(|e| {
             ErrorCode::Uncategorized.and_source(e).context(format!(
                 "Cannot resolve `@import` IRI ({:?}) because it is not an IRI reference",
@@ -202,7 +197,7 @@ async fn process_ctxdef_import<'a, L: LoadRemoteDocument>(
 
 /// Processes `@base` entry of the context definition.
 fn process_ctxdef_base(
-    remote_contexts: &HashSet<IriString>,
+    remote_contexts: &[IriString],
     result: &mut Context,
     context: &JsonMap<String, Value>,
 ) -> Result<()> {
@@ -364,7 +359,7 @@ fn process_ctxdef_propagate(
     context: &JsonMap<String, Value>,
 ) -> Result<()> {
     // Step 5.11.
-    if let Some(value) = context.get("@direction") {
+    if let Some(value) = context.get("@propagate") {
         // Step 5.11.1
         if processor.is_processing_mode_1_0() {
             return Err(ErrorCode::InvalidContextEntry.and_source(anyhow!(
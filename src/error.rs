@@ -2,8 +2,6 @@
 
 use std::fmt;
 
-use thiserror;
-
 /// JSON-LD processing result.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -52,6 +50,14 @@ pub enum ErrorCode {
     ///
     /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonlderrorcode-invalid-default-language>.
     InvalidDefaultLanguage,
+    /// Invalid `@embed` value.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldframingerrorcode-invalid-embed-value>.
+    InvalidEmbedValue,
+    /// Invalid frame.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldframingerrorcode-invalid-frame>.
+    InvalidFrame,
     /// Invalid `@id` value.
     ///
     /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonlderrorcode-invalid-@id-value>.
@@ -228,6 +234,8 @@ impl ErrorCode {
             Self::InvalidContextEntry => "invalid context entry",
             Self::InvalidContextNullification => "invalid context nullification",
             Self::InvalidDefaultLanguage => "invalid default language",
+            Self::InvalidEmbedValue => "invalid @embed value",
+            Self::InvalidFrame => "invalid frame",
             Self::InvalidIdValue => "invalid @id value",
             Self::InvalidImportValue => "invalid @import value",
             Self::InvalidIncludedValue => "invalid @included value",
@@ -355,6 +363,7 @@ impl From<ErrorCode> for Error {
 }
 
 /// Extension trait for JSON-LD processing result.
+#[allow(dead_code)]
 pub(crate) trait ResultExt<T> {
     /// Wraps the error value with the additional context.
     fn context<C>(self, context: C) -> Result<T>
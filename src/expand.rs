@@ -2,4 +2,263 @@
 //!
 //! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#expansion-algorithms>.
 
+use serde_json::Value;
+
+use crate::{context::Context, error::Result, processor::Processor, remote::LoadRemoteDocument};
+
+pub(crate) use self::value::expand_value;
+
+use self::element::{expand_element, OptionalParams};
+
+mod element;
 pub(crate) mod iri;
+mod value;
+
+/// Expands the given document.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-expand>.
+pub(crate) async fn expand<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+) -> Result<Value> {
+    let optional = OptionalParams::new().set_frame_expansion(processor.options().frame_expansion());
+    let expand_context = processor.options().expand_context();
+    expand_impl(processor, input, expand_context, optional).await
+}
+
+/// Expands the given frame.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldprocessor-frame>.
+pub(crate) async fn expand_frame<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    frame: &Value,
+) -> Result<Value> {
+    // The `expandContext` option is for the input document, not for frames.
+    expand_impl(
+        processor,
+        frame,
+        None,
+        OptionalParams::new().set_frame_expansion(true),
+    )
+    .await
+}
+
+/// Expands the given document with the given `expandContext` and optional parameters.
+async fn expand_impl<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+    expand_context: Option<&Value>,
+    optional: OptionalParams,
+) -> Result<Value> {
+    let base_url = processor.options().document_iri();
+    // Step 5
+    let mut active_context = Context::with_base(base_url.to_owned());
+    // Step 6
+    if let Some(expand_context) = expand_context {
+        active_context = active_context
+            .join_context_value(processor, expand_context, base_url, false)
+            .await?;
+    }
+    // Step 8
+    let expanded =
+        expand_element(processor, &active_context, None, input, base_url, optional).await?;
+    // Step 9
+    let expanded = match expanded {
+        Value::Object(mut map) if map.len() == 1 && map.contains_key("@graph") => map
+            .remove("@graph")
+            .expect("Should never fail: already checked"),
+        v => v,
+    };
+    // Step 10, 11
+    Ok(match expanded {
+        Value::Null => Value::Array(Vec::new()),
+        v @ Value::Array(_) => v,
+        v => Value::Array(vec![v]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        test_utils::{block_on, processor, MapLoader, NoLoader},
+        ErrorCode, ProcessingMode,
+    };
+
+    #[test]
+    fn expand_node_object() {
+        let input = json!({
+            "@context": {
+                "@vocab": "http://example.org/vocab#",
+                "knows": { "@id": "http://xmlns.com/foaf/0.1/knows", "@type": "@id" },
+                "label": { "@id": "http://www.w3.org/2000/01/rdf-schema#label", "@container": "@language" }
+            },
+            "@id": "#me",
+            "@type": "Person",
+            "knows": "alice",
+            "label": { "en": "Me", "ja": ["私", "僕"] },
+            "age": 42
+        });
+        let expected = json!([{
+            "@id": "http://example.com/doc.jsonld#me",
+            "@type": ["http://example.org/vocab#Person"],
+            "http://example.org/vocab#age": [{ "@value": 42 }],
+            "http://xmlns.com/foaf/0.1/knows": [{ "@id": "http://example.com/alice" }],
+            "http://www.w3.org/2000/01/rdf-schema#label": [
+                { "@value": "Me", "@language": "en" },
+                { "@value": "私", "@language": "ja" },
+                { "@value": "僕", "@language": "ja" }
+            ]
+        }]);
+        assert_eq!(block_on(processor().expand(&input)).unwrap(), expected);
+    }
+
+    #[test]
+    fn expand_scoped_contexts() {
+        let input = json!({
+            "@context": {
+                "@vocab": "http://example.org/",
+                "Person": { "@context": { "name": "http://xmlns.com/foaf/0.1/name" } },
+                "meta": { "@context": { "@vocab": "http://example.org/meta#" } }
+            },
+            "@type": "Person",
+            "name": "Alice",
+            "friend": { "name": "Bob" },
+            "meta": { "source": "x" }
+        });
+        let expected = json!([{
+            "@type": ["http://example.org/Person"],
+            "http://xmlns.com/foaf/0.1/name": [{ "@value": "Alice" }],
+            // Type-scoped contexts do not propagate to embedded node objects.
+            "http://example.org/friend": [{ "http://example.org/name": [{ "@value": "Bob" }] }],
+            "http://example.org/meta": [{ "http://example.org/meta#source": [{ "@value": "x" }] }]
+        }]);
+        assert_eq!(block_on(processor().expand(&input)).unwrap(), expected);
+    }
+
+    #[test]
+    fn expand_containers_and_reverse() {
+        let input = json!({
+            "@context": {
+                "@vocab": "http://example.org/",
+                "list": { "@container": "@list" },
+                "byIndex": { "@container": "@index" },
+                "byType": { "@container": "@type" },
+                "parentOf": { "@reverse": "http://example.org/parent" },
+                "data": "@nest"
+            },
+            "@id": "http://example.org/root",
+            "list": [1, [2, 3]],
+            "byIndex": { "a": { "@id": "http://example.org/a" } },
+            "byType": { "Thing": { "@id": "http://example.org/t" } },
+            "parentOf": { "@id": "http://example.org/child" },
+            "data": { "nested": true }
+        });
+        let expected = json!([{
+            "@id": "http://example.org/root",
+            "@reverse": {
+                "http://example.org/parent": [{ "@id": "http://example.org/child" }]
+            },
+            "http://example.org/list": [{ "@list": [
+                { "@value": 1 },
+                { "@list": [{ "@value": 2 }, { "@value": 3 }] }
+            ] }],
+            "http://example.org/byIndex": [{ "@id": "http://example.org/a", "@index": "a" }],
+            "http://example.org/byType": [{
+                "@id": "http://example.org/t",
+                "@type": ["http://example.org/Thing"]
+            }],
+            "http://example.org/nested": [{ "@value": true }]
+        }]);
+        assert_eq!(block_on(processor().expand(&input)).unwrap(), expected);
+    }
+
+    #[test]
+    fn drop_free_floating_values() {
+        let input = json!([1, "free", { "@value": "x" }, { "@id": "http://example.org/a" }]);
+        assert_eq!(block_on(processor().expand(&input)).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn invalid_value_object() {
+        let input = json!({
+            "http://example.org/p": { "@value": 1, "@language": "en" }
+        });
+        let err = block_on(processor().expand(&input)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidLanguageTaggedValue);
+    }
+
+    #[test]
+    fn processing_mode_1_0() {
+        let mut options = processor().options().clone();
+        options.set_processing_mode(ProcessingMode::JsonLd10);
+        let processor = options.build(NoLoader);
+
+        // Keywords introduced in JSON-LD 1.1 are ignored.
+        let input = json!({
+            "@context": { "@vocab": "http://example.org/" },
+            "@nest": { "nested": true },
+            "name": "x"
+        });
+        let expected = json!([{ "http://example.org/name": [{ "@value": "x" }] }]);
+        assert_eq!(block_on(processor.expand(&input)).unwrap(), expected);
+
+        let expand_err = |context| {
+            block_on(processor.expand(&json!({ "@context": context })))
+                .expect_err("Should fail: JSON-LD 1.1 feature in `json-ld-1.0` mode")
+                .code()
+        };
+        assert_eq!(
+            expand_err(json!({ "@version": 1.1 })),
+            ErrorCode::ProcessingModeConflict
+        );
+        assert_eq!(
+            expand_err(json!({ "@protected": true })),
+            ErrorCode::InvalidContextEntry
+        );
+        assert_eq!(
+            expand_err(json!({ "@propagate": false })),
+            ErrorCode::InvalidContextEntry
+        );
+        assert_eq!(
+            expand_err(json!({ "ex": { "@id": "http://example.org/", "@prefix": true } })),
+            ErrorCode::InvalidTermDefinition
+        );
+        assert_eq!(
+            expand_err(json!({ "ex": { "@id": "http://example.org/", "@container": ["@set"] } })),
+            ErrorCode::InvalidContainerMapping
+        );
+    }
+
+    #[test]
+    fn expand_context() {
+        let input = json!({ "name": "Alice", "knows": "bob" });
+        let expected = json!([{
+            "http://xmlns.com/foaf/0.1/knows": [{ "@id": "http://example.com/bob" }],
+            "http://xmlns.com/foaf/0.1/name": [{ "@value": "Alice" }]
+        }]);
+        let context = json!({
+            "@vocab": "http://xmlns.com/foaf/0.1/",
+            "knows": { "@type": "@id" }
+        });
+        for expand_context in &[context.clone(), json!({ "@context": context.clone() })] {
+            let mut options = processor().options().clone();
+            options.set_expand_context(Some(expand_context.clone()));
+            let processor = options.build(NoLoader);
+            assert_eq!(block_on(processor.expand(&input)).unwrap(), expected);
+        }
+
+        // Remote context.
+        let context_iri = "http://example.com/context.jsonld";
+        let loader = MapLoader(
+            vec![(context_iri.to_owned(), json!({ "@context": context }))]
+                .into_iter()
+                .collect(),
+        );
+        let mut options = processor().options().clone();
+        options.set_expand_context(Some(context_iri.into()));
+        let processor = options.build(loader);
+        assert_eq!(block_on(processor.expand(&input)).unwrap(), expected);
+    }
+}
@@ -0,0 +1,427 @@
+//! Expansion algorithm.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#expansion-algorithm>.
+
+use std::{borrow::Cow, future::Future, pin::Pin};
+
+use anyhow::anyhow;
+use iri_string::types::IriStr;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    context::{ContainerItem, Context, Definition, ValueWithBase},
+    error::{ErrorCode, Result},
+    expand::{iri::ExpandIriOptions, value::expand_value},
+    iri::is_absolute_iri_ref,
+    json::{single_entry_map, to_ref_array},
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+use self::entries::{expand_entries, EntriesEnv};
+
+mod entries;
+mod keyword;
+
+/// Optional parameters for expansion algorithm.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OptionalParams {
+    /// "From map" flag.
+    from_map: bool,
+    /// "Frame expansion" flag.
+    frame_expansion: bool,
+}
+
+impl OptionalParams {
+    /// Creates a new default `OptionalParams`.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the "from map" flag.
+    pub(crate) fn set_from_map(mut self, from_map: bool) -> Self {
+        self.from_map = from_map;
+        self
+    }
+
+    /// Sets the "frame expansion" flag.
+    pub(crate) fn set_frame_expansion(mut self, frame_expansion: bool) -> Self {
+        self.frame_expansion = frame_expansion;
+        self
+    }
+}
+
+/// Runs expansion algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#expansion-algorithm>.
+pub(crate) fn expand_element<'a, L: LoadRemoteDocument>(
+    processor: &'a Processor<L>,
+    active_context: &'a Context,
+    active_property: Option<&'a str>,
+    element: &'a Value,
+    base_url: &'a IriStr,
+    optional: OptionalParams,
+) -> Pin<Box<dyn Future<Output = Result<Value>> + 'a + Send>> {
+    Box::pin(async move {
+        expand_element_impl(
+            processor,
+            active_context,
+            active_property,
+            element,
+            base_url,
+            optional,
+        )
+        .await
+    })
+}
+
+/// Runs expansion algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#expansion-algorithm>.
+async fn expand_element_impl<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    active_property: Option<&str>,
+    element: &Value,
+    base_url: &IriStr,
+    optional: OptionalParams,
+) -> Result<Value> {
+    // Step 1
+    if element.is_null() {
+        return Ok(Value::Null);
+    }
+    // Step 2
+    let optional = if active_property == Some("@default") {
+        optional.set_frame_expansion(false)
+    } else {
+        optional
+    };
+    // Step 3
+    let property_scoped_context = active_property
+        .and_then(|prop| active_context.term_definition(prop))
+        .and_then(Definition::local_context);
+    match element {
+        // Step 4
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+            // Step 4.1
+            if active_property.is_none() || active_property == Some("@graph") {
+                return Ok(Value::Null);
+            }
+            // Step 4.2
+            let active_context = match property_scoped_context {
+                Some(property_scoped_context) => Cow::Owned(
+                    active_context
+                        .join_scoped_context(processor, property_scoped_context, true, true)
+                        .await?,
+                ),
+                None => Cow::Borrowed(active_context),
+            };
+            // Step 4.3
+            expand_value(processor, &active_context, active_property, element)
+                .await
+                .map(Value::Object)
+        }
+        // Step 5
+        Value::Array(element) => {
+            expand_array(
+                processor,
+                active_context,
+                active_property,
+                element,
+                base_url,
+                optional,
+            )
+            .await
+        }
+        // Step 6
+        Value::Object(element) => {
+            expand_map(
+                processor,
+                active_context,
+                active_property,
+                element,
+                base_url,
+                property_scoped_context,
+                optional,
+            )
+            .await
+        }
+        Value::Null => unreachable!("Should never fail: already handled"),
+    }
+}
+
+/// Expands an array.
+async fn expand_array<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    active_property: Option<&str>,
+    element: &[Value],
+    base_url: &IriStr,
+    optional: OptionalParams,
+) -> Result<Value> {
+    let is_list_container = active_property
+        .and_then(|prop| active_context.term_definition(prop))
+        .is_some_and(|def| def.container_contains(ContainerItem::List));
+
+    // Step 5.1
+    let mut result = Vec::new();
+    // Step 5.2
+    for item in element {
+        // Step 5.2.1
+        let expanded_item = expand_element(
+            processor,
+            active_context,
+            active_property,
+            item,
+            base_url,
+            optional,
+        )
+        .await?;
+        // Step 5.2.2
+        let expanded_item = match expanded_item {
+            Value::Array(arr) if is_list_container => Value::Object(single_entry_map("@list", arr)),
+            v => v,
+        };
+        // Step 5.2.3
+        match expanded_item {
+            Value::Array(arr) => result.extend(arr),
+            Value::Null => {}
+            v => result.push(v),
+        }
+    }
+
+    // Step 5.3
+    Ok(Value::Array(result))
+}
+
+/// Expands a map.
+async fn expand_map<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    active_property: Option<&str>,
+    element: &JsonMap<String, Value>,
+    base_url: &IriStr,
+    property_scoped_context: Option<ValueWithBase<'_, &Value>>,
+    optional: OptionalParams,
+) -> Result<Value> {
+    let mut active_context = Cow::Borrowed(active_context);
+    // Step 7
+    if active_context.has_previous_context()
+        && !optional.from_map
+        && !is_value_or_subject_reference(processor, &active_context, element).await?
+    {
+        let previous_context = active_context
+            .previous_context()
+            .expect("Should never fail: already checked")
+            .clone();
+        active_context = Cow::Owned(previous_context);
+    }
+    // Step 8
+    if let Some(property_scoped_context) = property_scoped_context {
+        active_context = Cow::Owned(
+            active_context
+                .join_scoped_context(processor, property_scoped_context, true, true)
+                .await?,
+        );
+    }
+    // Step 9
+    if let Some(local_context) = element.get("@context") {
+        active_context = Cow::Owned(
+            active_context
+                .join_context_value(processor, local_context, base_url, false)
+                .await?,
+        );
+    }
+    // Step 10
+    let type_scoped_context = active_context.into_owned();
+    let mut active_context = Cow::Borrowed(&type_scoped_context);
+    // Step 11
+    for (key, value) in element {
+        let expanded = ExpandIriOptions::constant(&type_scoped_context)
+            .vocab(true)
+            .expand_str(processor, key)
+            .await?;
+        if expanded.as_deref() != Some("@type") {
+            continue;
+        }
+        // Step 11.1
+        let mut terms = to_ref_array(value)
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+        terms.sort_unstable();
+        // Step 11.2
+        for term in terms {
+            if let Some(local_context) = type_scoped_context
+                .term_definition(term)
+                .and_then(Definition::local_context)
+            {
+                active_context = Cow::Owned(
+                    active_context
+                        .join_scoped_context(processor, local_context, false, false)
+                        .await?,
+                );
+            }
+        }
+    }
+    // Step 12
+    let input_type = get_input_type(processor, &active_context, element).await?;
+    let mut result = JsonMap::new();
+    let env = EntriesEnv {
+        processor,
+        active_context: &active_context,
+        type_scoped_context: &type_scoped_context,
+        active_property,
+        base_url,
+        input_type: input_type.as_deref(),
+        frame_expansion: optional.frame_expansion,
+    };
+    // Step 13, 14
+    expand_entries(&env, element, &mut result).await?;
+
+    // Step 15-20
+    postprocess_map(active_property, result, optional.frame_expansion)
+}
+
+/// Checks whether the given map has an entry expanding to `@value`, or consists of a single
+/// entry expanding to `@id`.
+// Step 7
+async fn is_value_or_subject_reference<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    element: &JsonMap<String, Value>,
+) -> Result<bool> {
+    let mut expanded_keys = Vec::with_capacity(element.len());
+    for key in element.keys() {
+        let expanded = ExpandIriOptions::constant(active_context)
+            .vocab(true)
+            .expand_str(processor, key)
+            .await?;
+        expanded_keys.push(expanded);
+    }
+    if expanded_keys.iter().any(|k| k.as_deref() == Some("@value")) {
+        return Ok(true);
+    }
+
+    Ok(expanded_keys.len() == 1 && expanded_keys[0].as_deref() == Some("@id"))
+}
+
+/// Returns the input type.
+// Step 12
+async fn get_input_type<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    element: &JsonMap<String, Value>,
+) -> Result<Option<String>> {
+    for (key, value) in element {
+        let expanded = ExpandIriOptions::constant(active_context)
+            .vocab(true)
+            .expand_str(processor, key)
+            .await?;
+        if expanded.as_deref() != Some("@type") {
+            continue;
+        }
+        let input_type = match to_ref_array(value).last().and_then(Value::as_str) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        return Ok(ExpandIriOptions::constant(active_context)
+            .vocab(true)
+            .expand_str(processor, input_type)
+            .await?
+            .map(Cow::into_owned));
+    }
+
+    Ok(None)
+}
+
+/// Validates and normalizes the expanded map.
+// Step 15-20
+fn postprocess_map(
+    active_property: Option<&str>,
+    mut result: JsonMap<String, Value>,
+    frame_expansion: bool,
+) -> Result<Value> {
+    // Step 15
+    if let Some(value) = result.get("@value") {
+        // Step 15.1
+        if let Some(key) = result.keys().find(|k| {
+            !matches!(
+                k.as_str(),
+                "@direction" | "@index" | "@language" | "@type" | "@value"
+            )
+        }) {
+            return Err(ErrorCode::InvalidValueObject
+                .and_source(anyhow!("Unexpected entry {:?} in value object", key)));
+        }
+        if result.contains_key("@type")
+            && (result.contains_key("@language") || result.contains_key("@direction"))
+        {
+            return Err(ErrorCode::InvalidValueObject.and_source(anyhow!(
+                "Value object has both `@type` and `@language` or `@direction`"
+            )));
+        }
+        match result.get("@type") {
+            // Step 15.2
+            Some(Value::String(ty)) if ty == "@json" => {}
+            // Step 15.3
+            _ if value.is_null() || value.as_array().is_some_and(Vec::is_empty) => {
+                return Ok(Value::Null)
+            }
+            // Value patterns in frames can have wildcards and multiple candidates.
+            _ if frame_expansion => {}
+            // Step 15.4
+            _ if !value.is_string() && result.contains_key("@language") => {
+                return Err(ErrorCode::InvalidLanguageTaggedValue
+                    .and_source(anyhow!("Non-string value {:?} has `@language`", value)))
+            }
+            // Step 15.5
+            Some(ty) if !ty.as_str().is_some_and(is_absolute_iri_ref) => {
+                return Err(ErrorCode::InvalidTypedValue
+                    .and_source(anyhow!("Expected an IRI as `@type`, but got {:?}", ty)))
+            }
+            _ => {}
+        }
+    } else if let Some(ty) = result.get_mut("@type") {
+        // Step 16
+        if !ty.is_array() {
+            *ty = Value::Array(vec![ty.take()]);
+        }
+    } else if result.contains_key("@set") || result.contains_key("@list") {
+        // Step 17
+        // Step 17.1
+        if result.len() > 2 || (result.len() == 2 && !result.contains_key("@index")) {
+            return Err(ErrorCode::InvalidSetOrListObject.and_source(anyhow!(
+                "Unexpected entries in set or list object: keys={:?}",
+                result.keys().collect::<Vec<_>>()
+            )));
+        }
+        // Step 17.2
+        if let Some(set) = result.remove("@set") {
+            match set {
+                Value::Object(set) => result = set,
+                set => return Ok(set),
+            }
+        }
+    }
+    // Step 18
+    if result.len() == 1 && result.contains_key("@language") {
+        return Ok(Value::Null);
+    }
+    // Step 19
+    // Frames are kept even if they are empty or have only `@id`, because they are not free-floating
+    // values but patterns to match.
+    if !frame_expansion && (active_property.is_none() || active_property == Some("@graph")) {
+        // Step 19.1
+        if result.is_empty() || result.contains_key("@value") || result.contains_key("@list") {
+            return Ok(Value::Null);
+        }
+        // Step 19.2
+        if result.len() == 1 && result.contains_key("@id") {
+            return Ok(Value::Null);
+        }
+    }
+
+    // Step 20
+    Ok(Value::Object(result))
+}
@@ -0,0 +1,456 @@
+//! Expansion of map entries.
+
+use std::{borrow::Cow, future::Future, pin::Pin};
+
+use anyhow::anyhow;
+use iri_string::types::IriStr;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    context::{ContainerItem, Context, Definition},
+    error::{ErrorCode, Result},
+    expand::{
+        element::{expand_element, keyword::expand_keyword_entry, OptionalParams},
+        iri::ExpandIriOptions,
+        value::expand_value,
+    },
+    json::{add_value, single_entry_map, to_ref_array},
+    processor::Processor,
+    remote::LoadRemoteDocument,
+    syntax::{is_graph_object, is_list_object, is_value_object},
+};
+
+/// Environment to expand entries of a map.
+pub(super) struct EntriesEnv<'a, L> {
+    /// Processor.
+    pub(super) processor: &'a Processor<L>,
+    /// Active context.
+    pub(super) active_context: &'a Context,
+    /// Type-scoped context.
+    pub(super) type_scoped_context: &'a Context,
+    /// Active property.
+    pub(super) active_property: Option<&'a str>,
+    /// Base URL.
+    pub(super) base_url: &'a IriStr,
+    /// Input type.
+    pub(super) input_type: Option<&'a str>,
+    /// "Frame expansion" flag.
+    pub(super) frame_expansion: bool,
+}
+
+impl<L> EntriesEnv<'_, L> {
+    /// Returns the optional parameters for recursive expansion of entry values.
+    pub(super) fn nested_params(&self) -> OptionalParams {
+        OptionalParams::new().set_frame_expansion(self.frame_expansion)
+    }
+}
+
+/// Expands entries of the given map and adds them to `result`.
+// Step 13, 14
+pub(super) fn expand_entries<'a, L: LoadRemoteDocument>(
+    env: &'a EntriesEnv<'a, L>,
+    element: &'a JsonMap<String, Value>,
+    result: &'a mut JsonMap<String, Value>,
+) -> Pin<Box<dyn Future<Output = Result<()>> + 'a + Send>> {
+    Box::pin(async move { expand_entries_impl(env, element, result).await })
+}
+
+/// Expands entries of the given map and adds them to `result`.
+// Step 13, 14
+async fn expand_entries_impl<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    element: &JsonMap<String, Value>,
+    result: &mut JsonMap<String, Value>,
+) -> Result<()> {
+    let processor = env.processor;
+    let mut nests = Vec::new();
+    // Step 13
+    for (key, value) in element {
+        // Step 13.1
+        if key == "@context" {
+            continue;
+        }
+        // Step 13.2
+        let expanded_property = match ExpandIriOptions::constant(env.active_context)
+            .vocab(true)
+            .expand_str(processor, key)
+            .await?
+        {
+            Some(v) => v,
+            None => continue,
+        };
+        // Step 13.3
+        let is_keyword = processor.is_keyword(&expanded_property);
+        if !is_keyword && !expanded_property.contains(':') {
+            continue;
+        }
+        if is_keyword {
+            // Step 13.4
+            expand_keyword_entry(env, key, &expanded_property, value, result, &mut nests).await?;
+        } else {
+            // Step 13.5-13.14
+            expand_property_entry(env, key, &expanded_property, value, result).await?;
+        }
+    }
+    // Step 14
+    for nesting_key in nests {
+        // Step 14.1
+        let nested_values = to_ref_array(&element[nesting_key]);
+        // Step 14.2
+        for nested_value in nested_values {
+            // Step 14.2.1
+            let nested_value = match nested_value {
+                Value::Object(map) => map,
+                v => {
+                    return Err(ErrorCode::InvalidNestValue
+                        .and_source(anyhow!("Expected a map as nested value, but got {:?}", v)))
+                }
+            };
+            for key in nested_value.keys() {
+                let expanded = ExpandIriOptions::constant(env.active_context)
+                    .vocab(true)
+                    .expand_str(processor, key)
+                    .await?;
+                if expanded.as_deref() == Some("@value") {
+                    return Err(ErrorCode::InvalidNestValue.and_source(anyhow!(
+                        "Nested value has an entry {:?} expanding to `@value`",
+                        key
+                    )));
+                }
+            }
+            // Step 14.2.2
+            expand_entries(env, nested_value, result).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Expands the entry whose key is not expanded to a keyword.
+// Step 13.5-13.14
+async fn expand_property_entry<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    key: &str,
+    expanded_property: &str,
+    value: &Value,
+    result: &mut JsonMap<String, Value>,
+) -> Result<()> {
+    let definition = env.active_context.term_definition(key);
+    // Step 13.5
+    let container = definition.and_then(Definition::container);
+    let container_contains =
+        |item: ContainerItem| container.is_some_and(|container| container.contains(item));
+    let expanded_value = match value {
+        // Step 13.6
+        _ if definition.and_then(Definition::ty) == Some("@json") => {
+            let mut map = single_entry_map("@value", value.clone());
+            map.insert("@type".into(), "@json".into());
+            Value::Object(map)
+        }
+        // Step 13.7
+        Value::Object(value) if container_contains(ContainerItem::Language) => {
+            expand_language_map(env, definition, value).await?
+        }
+        // Step 13.8
+        Value::Object(value)
+            if container_contains(ContainerItem::Index)
+                || container_contains(ContainerItem::Type)
+                || container_contains(ContainerItem::Id) =>
+        {
+            expand_index_map(env, key, definition, value).await?
+        }
+        // Step 13.9
+        value => {
+            expand_element(
+                env.processor,
+                env.active_context,
+                Some(key),
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?
+        }
+    };
+    // Step 13.10
+    if expanded_value.is_null() {
+        return Ok(());
+    }
+    // Step 13.11
+    let expanded_value =
+        if container_contains(ContainerItem::List) && !is_list_object(&expanded_value) {
+            let list = match expanded_value {
+                Value::Array(arr) => arr,
+                v => vec![v],
+            };
+            Value::Object(single_entry_map("@list", list))
+        } else {
+            expanded_value
+        };
+    // Step 13.12
+    let expanded_value = if container_contains(ContainerItem::Graph)
+        && !container_contains(ContainerItem::Id)
+        && !container_contains(ContainerItem::Index)
+    {
+        let graphs = match expanded_value {
+            Value::Array(arr) => arr,
+            v => vec![v],
+        };
+        // Step 13.12.1
+        graphs
+            .into_iter()
+            .map(|ev| {
+                let ev = match ev {
+                    Value::Array(arr) => arr,
+                    v => vec![v],
+                };
+                Value::Object(single_entry_map("@graph", ev))
+            })
+            .collect::<Vec<_>>()
+            .into()
+    } else {
+        expanded_value
+    };
+    if definition.is_some_and(Definition::is_reverse) {
+        // Step 13.13
+        // Step 13.13.1, 13.13.2
+        let reverse_map = result
+            .entry("@reverse")
+            .or_insert_with(|| Value::Object(JsonMap::new()))
+            .as_object_mut()
+            .ok_or_else(|| {
+                ErrorCode::Uncategorized.and_source(anyhow!("`@reverse` entry is not a map"))
+            })?;
+        // Step 13.13.3
+        let expanded_value = match expanded_value {
+            Value::Array(arr) => arr,
+            v => vec![v],
+        };
+        // Step 13.13.4
+        for item in expanded_value {
+            // Step 13.13.4.1
+            if is_value_object(&item) || is_list_object(&item) {
+                return Err(ErrorCode::InvalidReversePropertyValue.and_source(anyhow!(
+                    "Value of reverse property {:?} is a value object or a list object: {:?}",
+                    key,
+                    item
+                )));
+            }
+            // Step 13.13.4.2, 13.13.4.3
+            add_value(reverse_map, expanded_property, item, true);
+        }
+    } else {
+        // Step 13.14
+        add_value(result, expanded_property, expanded_value, true);
+    }
+
+    Ok(())
+}
+
+/// Expands the language map.
+// Step 13.7
+async fn expand_language_map<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    definition: Option<&Definition>,
+    value: &JsonMap<String, Value>,
+) -> Result<Value> {
+    // Step 13.7.1
+    let mut expanded_value = Vec::new();
+    // Step 13.7.2, 13.7.3
+    let direction = match definition.and_then(Definition::direction) {
+        Some(direction) => direction.into(),
+        None => env.active_context.default_base_direction(),
+    };
+    // Step 13.7.4
+    for (language, language_value) in value {
+        let expanded_language = ExpandIriOptions::constant(env.active_context)
+            .vocab(true)
+            .expand_str(env.processor, language)
+            .await?;
+        let is_none = language == "@none" || expanded_language.as_deref() == Some("@none");
+        // Step 13.7.4.1, 13.7.4.2
+        for item in to_ref_array(language_value) {
+            let item = match item {
+                // Step 13.7.4.2.1
+                Value::Null => continue,
+                // Step 13.7.4.2.2
+                Value::String(s) => s,
+                v => {
+                    return Err(ErrorCode::InvalidLanguageMapValue.and_source(anyhow!(
+                        "Expected a string as a language map value, but got {:?}",
+                        v
+                    )))
+                }
+            };
+            // Step 13.7.4.2.3
+            // TODO: Issue a warning if `language` is not well-formed.
+            let mut v = single_entry_map("@value", item.clone());
+            // Step 13.7.4.2.4
+            if !is_none {
+                v.insert("@language".into(), language.clone().into());
+            }
+            // Step 13.7.4.2.5
+            if let Some(direction) = direction {
+                v.insert("@direction".into(), direction.as_str().into());
+            }
+            // Step 13.7.4.2.6
+            expanded_value.push(Value::Object(v));
+        }
+    }
+
+    Ok(Value::Array(expanded_value))
+}
+
+/// Expands the index map, id map, or type map.
+// Step 13.8
+async fn expand_index_map<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    key: &str,
+    definition: Option<&Definition>,
+    value: &JsonMap<String, Value>,
+) -> Result<Value> {
+    let processor = env.processor;
+    let active_context = env.active_context;
+    let container_contains =
+        |item: ContainerItem| definition.is_some_and(|def| def.container_contains(item));
+
+    // Step 13.8.1
+    let mut expanded_value = Vec::new();
+    // Step 13.8.2
+    let index_key = definition.and_then(Definition::index).unwrap_or("@index");
+    // Step 13.8.3
+    for (index, index_value) in value {
+        // Step 13.8.3.1-13.8.3.3
+        let map_context =
+            if container_contains(ContainerItem::Id) || container_contains(ContainerItem::Type) {
+                // Step 13.8.3.1
+                let map_context = active_context.previous_context().unwrap_or(active_context);
+                // Step 13.8.3.2
+                match map_context
+                    .term_definition(index)
+                    .and_then(Definition::local_context)
+                {
+                    Some(local_context) if container_contains(ContainerItem::Type) => Cow::Owned(
+                        map_context
+                            .join_scoped_context(processor, local_context, false, true)
+                            .await?,
+                    ),
+                    _ => Cow::Borrowed(map_context),
+                }
+            } else {
+                // Step 13.8.3.3
+                Cow::Borrowed(active_context)
+            };
+        // Step 13.8.3.4
+        let expanded_index = ExpandIriOptions::constant(active_context)
+            .vocab(true)
+            .expand_str(processor, index)
+            .await?;
+        let is_none = expanded_index.as_deref() == Some("@none");
+        // Step 13.8.3.5
+        let index_value = match index_value {
+            Value::Array(_) => Cow::Borrowed(index_value),
+            v => Cow::Owned(Value::Array(vec![v.clone()])),
+        };
+        // Step 13.8.3.6
+        let index_value = expand_element(
+            processor,
+            &map_context,
+            Some(key),
+            &index_value,
+            env.base_url,
+            env.nested_params().set_from_map(true),
+        )
+        .await?;
+        // Step 13.8.3.7
+        for item in to_ref_array(&index_value) {
+            // Step 13.8.3.7.1
+            let item = if container_contains(ContainerItem::Graph) && !is_graph_object(item) {
+                let graph = match item {
+                    Value::Array(arr) => arr.clone(),
+                    v => vec![v.clone()],
+                };
+                single_entry_map("@graph", graph)
+            } else {
+                match item {
+                    Value::Object(map) => map.clone(),
+                    v => {
+                        expanded_value.push(v.clone());
+                        continue;
+                    }
+                }
+            };
+            let mut item = item;
+            if container_contains(ContainerItem::Index) && index_key != "@index" && !is_none {
+                // Step 13.8.3.7.2
+                // Step 13.8.3.7.2.1
+                let re_expanded_index = expand_value(
+                    processor,
+                    active_context,
+                    Some(index_key),
+                    &Value::String(index.clone()),
+                )
+                .await?;
+                // Step 13.8.3.7.2.2
+                let expanded_index_key = ExpandIriOptions::constant(active_context)
+                    .vocab(true)
+                    .expand_str(processor, index_key)
+                    .await?
+                    .ok_or_else(|| {
+                        ErrorCode::InvalidTermDefinition
+                            .and_source(anyhow!("Index key {:?} is expanded to `null`", index_key))
+                    })?;
+                // Step 13.8.3.7.2.3
+                let mut index_property_values = vec![Value::Object(re_expanded_index)];
+                if let Some(existing) = item.remove(&*expanded_index_key) {
+                    match existing {
+                        Value::Array(arr) => index_property_values.extend(arr),
+                        v => index_property_values.push(v),
+                    }
+                }
+                // Step 13.8.3.7.2.5
+                if item.contains_key("@value") {
+                    return Err(ErrorCode::InvalidValueObject.and_source(anyhow!(
+                        "Value object cannot have an index property {:?}",
+                        expanded_index_key
+                    )));
+                }
+                // Step 13.8.3.7.2.4
+                item.insert(
+                    expanded_index_key.into_owned(),
+                    Value::Array(index_property_values),
+                );
+            } else if container_contains(ContainerItem::Index)
+                && !item.contains_key("@index")
+                && !is_none
+            {
+                // Step 13.8.3.7.3
+                item.insert("@index".into(), index.clone().into());
+            } else if container_contains(ContainerItem::Id) && !item.contains_key("@id") && !is_none
+            {
+                // Step 13.8.3.7.4
+                let expanded_index = ExpandIriOptions::constant(active_context)
+                    .document_relative(true)
+                    .expand_to_json(processor, index)
+                    .await?;
+                item.insert("@id".into(), expanded_index);
+            } else if container_contains(ContainerItem::Type) && !is_none {
+                // Step 13.8.3.7.5
+                let mut types = vec![expanded_index
+                    .as_deref()
+                    .map_or(Value::Null, |v| Value::String(v.to_owned()))];
+                if let Some(existing) = item.remove("@type") {
+                    match existing {
+                        Value::Array(arr) => types.extend(arr),
+                        v => types.push(v),
+                    }
+                }
+                item.insert("@type".into(), Value::Array(types));
+            }
+            // Step 13.8.3.7.6
+            expanded_value.push(Value::Object(item));
+        }
+    }
+
+    Ok(Value::Array(expanded_value))
+}
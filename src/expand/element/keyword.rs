@@ -0,0 +1,498 @@
+//! Expansion of map entries whose keys are keywords.
+
+use std::convert::TryFrom;
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    context::Direction,
+    error::{ErrorCode, Result},
+    expand::{
+        element::{entries::EntriesEnv, expand_element},
+        iri::ExpandIriOptions,
+    },
+    json::{add_value, single_entry_map},
+    remote::LoadRemoteDocument,
+    syntax::{is_list_object, is_node_object, is_value_object},
+};
+
+/// Expands the entry whose key is expanded to a keyword.
+// Step 13.4
+pub(super) async fn expand_keyword_entry<'a, L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    key: &'a str,
+    expanded_property: &str,
+    value: &Value,
+    result: &mut JsonMap<String, Value>,
+    nests: &mut Vec<&'a str>,
+) -> Result<()> {
+    let processor = env.processor;
+    let active_context = env.active_context;
+
+    // Step 13.4.1
+    if env.active_property == Some("@reverse") {
+        return Err(ErrorCode::InvalidReversePropertyMap.and_source(anyhow!(
+            "Reverse property map has a keyword entry {:?}",
+            key
+        )));
+    }
+    // Step 13.4.2
+    if result.contains_key(expanded_property)
+        && (processor.is_processing_mode_1_0()
+            || !matches!(expanded_property, "@included" | "@type"))
+    {
+        return Err(ErrorCode::CollidingKeywords.and_source(anyhow!(
+            "Keyword {:?} is specified twice",
+            expanded_property
+        )));
+    }
+    let expanded_value = match expanded_property {
+        // Step 13.4.3
+        "@id" if env.frame_expansion => expand_frame_id(env, value).await?,
+        "@id" => {
+            // Step 13.4.3.1
+            let value = value.as_str().ok_or_else(|| {
+                ErrorCode::InvalidIdValue
+                    .and_source(anyhow!("Expected string as `@id`, but got {:?}", value))
+            })?;
+            // Step 13.4.3.2
+            ExpandIriOptions::constant(active_context)
+                .document_relative(true)
+                .expand_to_json(processor, value)
+                .await?
+        }
+        // Step 13.4.4
+        "@type" => {
+            let type_scoped_context = env.type_scoped_context;
+            // Step 13.4.4.1-13.4.4.4
+            let expanded_value = match value {
+                Value::String(s) => {
+                    ExpandIriOptions::constant(type_scoped_context)
+                        .vocab(true)
+                        .document_relative(true)
+                        .expand_to_json(processor, s)
+                        .await?
+                }
+                Value::Array(arr) => {
+                    let mut expanded = Vec::with_capacity(arr.len());
+                    for v in arr {
+                        let v = v.as_str().ok_or_else(|| {
+                            ErrorCode::InvalidTypeValue.and_source(anyhow!(
+                                "Expected string as an element of `@type` array, but got {:?}",
+                                v
+                            ))
+                        })?;
+                        let v = ExpandIriOptions::constant(type_scoped_context)
+                            .vocab(true)
+                            .document_relative(true)
+                            .expand_to_json(processor, v)
+                            .await?;
+                        if !v.is_null() {
+                            expanded.push(v);
+                        }
+                    }
+                    Value::Array(expanded)
+                }
+                // Step 13.4.4.2
+                Value::Object(map) if env.frame_expansion && map.is_empty() => value.clone(),
+                // Step 13.4.4.3
+                Value::Object(map) if env.frame_expansion && is_default_object(map) => {
+                    let default = map["@default"].as_str().ok_or_else(|| {
+                        ErrorCode::InvalidTypeValue.and_source(anyhow!(
+                            "Expected string as `@default` of `@type`, but got {:?}",
+                            map["@default"]
+                        ))
+                    })?;
+                    let default = ExpandIriOptions::constant(type_scoped_context)
+                        .vocab(true)
+                        .document_relative(true)
+                        .expand_to_json(processor, default)
+                        .await?;
+                    Value::Object(single_entry_map("@default", default))
+                }
+                v => {
+                    return Err(ErrorCode::InvalidTypeValue.and_source(anyhow!(
+                        "Expected a string or an array of strings as `@type`, but got {:?}",
+                        v
+                    )))
+                }
+            };
+            // Step 13.4.4.5
+            match result.remove("@type") {
+                Some(existing) => {
+                    let mut types = match existing {
+                        Value::Array(arr) => arr,
+                        v => vec![v],
+                    };
+                    match expanded_value {
+                        Value::Array(arr) => types.extend(arr),
+                        Value::Null => {}
+                        v => types.push(v),
+                    }
+                    Value::Array(types)
+                }
+                None => expanded_value,
+            }
+        }
+        // Step 13.4.5
+        "@graph" => {
+            let expanded = expand_element(
+                processor,
+                active_context,
+                Some("@graph"),
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?;
+            into_array(expanded)
+        }
+        // Step 13.4.6
+        "@included" => {
+            // Step 13.4.6.1
+            if processor.is_processing_mode_1_0() {
+                return Ok(());
+            }
+            // Step 13.4.6.2
+            let expanded = expand_element(
+                processor,
+                active_context,
+                None,
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?;
+            let mut expanded = match into_array(expanded) {
+                Value::Array(arr) => arr,
+                _ => unreachable!("Should never fail: `into_array` always returns an array"),
+            };
+            // Step 13.4.6.3
+            if let Some(v) = expanded.iter().find(|v| !is_node_object(v)) {
+                return Err(ErrorCode::InvalidIncludedValue
+                    .and_source(anyhow!("Expected node objects, but got {:?}", v)));
+            }
+            // Step 13.4.6.4
+            if let Some(existing) = result.remove("@included") {
+                let mut included = match existing {
+                    Value::Array(arr) => arr,
+                    v => vec![v],
+                };
+                included.append(&mut expanded);
+                expanded = included;
+            }
+            Value::Array(expanded)
+        }
+        // Step 13.4.7
+        "@value" => {
+            let expanded = if env.input_type == Some("@json") {
+                // Step 13.4.7.1
+                if processor.is_processing_mode_1_0() {
+                    return Err(ErrorCode::InvalidValueObjectValue.and_source(anyhow!(
+                        "JSON literal is not supported with processing mode `json-ld-1.0`"
+                    )));
+                }
+                value.clone()
+            } else if env.frame_expansion {
+                // Step 13.4.7.2
+                match value {
+                    Value::Object(map) if map.is_empty() => Value::Array(vec![value.clone()]),
+                    Value::Array(arr) if arr.iter().all(is_scalar) => value.clone(),
+                    v if is_scalar(v) => Value::Array(vec![v.clone()]),
+                    v => {
+                        return Err(ErrorCode::InvalidValueObjectValue.and_source(anyhow!(
+                            "Expected a scalar, an array of scalars, or an empty map as \
+                             `@value` in a frame, but got {:?}",
+                            v
+                        )))
+                    }
+                }
+            } else {
+                // Step 13.4.7.2, 13.4.7.3
+                match value {
+                    Value::Array(_) | Value::Object(_) => {
+                        return Err(ErrorCode::InvalidValueObjectValue.and_source(anyhow!(
+                            "Expected a scalar or `null` as `@value`, but got {:?}",
+                            value
+                        )))
+                    }
+                    v => v.clone(),
+                }
+            };
+            // Step 13.4.7.4
+            if expanded.is_null() {
+                result.insert("@value".into(), Value::Null);
+                return Ok(());
+            }
+            expanded
+        }
+        // Step 13.4.8
+        "@language" if env.frame_expansion => {
+            frame_string_pattern(value, |_| true).ok_or_else(|| {
+                ErrorCode::InvalidLanguageTaggedString.and_source(anyhow!(
+                    "Expected a string, an array of strings, or an empty map as `@language` \
+                     in a frame, but got {:?}",
+                    value
+                ))
+            })?
+        }
+        "@language" => match value {
+            // Step 13.4.8.2
+            // TODO: Issue a warning if `value` is not well-formed.
+            Value::String(_) => value.clone(),
+            // Step 13.4.8.1
+            v => {
+                return Err(ErrorCode::InvalidLanguageTaggedString
+                    .and_source(anyhow!("Expected string as `@language`, but got {:?}", v)))
+            }
+        },
+        // Step 13.4.9
+        "@direction" => {
+            // Step 13.4.9.1
+            if processor.is_processing_mode_1_0() {
+                return Ok(());
+            }
+            if env.frame_expansion {
+                frame_string_pattern(value, |s| Direction::try_from(s).is_ok()).ok_or_else(
+                    || {
+                        ErrorCode::InvalidBaseDirection.and_source(anyhow!(
+                            "Expected a direction, an array of directions, or an empty map as \
+                             `@direction` in a frame, but got {:?}",
+                            value
+                        ))
+                    },
+                )?
+            } else {
+                // Step 13.4.9.2, 13.4.9.3
+                match value {
+                    Value::String(s) => {
+                        Direction::try_from(s.as_str())
+                            .map_err(|e| ErrorCode::InvalidBaseDirection.and_source(e))?;
+                        value.clone()
+                    }
+                    v => {
+                        return Err(ErrorCode::InvalidBaseDirection.and_source(anyhow!(
+                            "Expected string as `@direction`, but got {:?}",
+                            v
+                        )))
+                    }
+                }
+            }
+        }
+        // Step 13.4.10
+        "@index" => match value {
+            // Step 13.4.10.2
+            Value::String(_) => value.clone(),
+            // Step 13.4.10.1
+            v => {
+                return Err(ErrorCode::InvalidIndexValue
+                    .and_source(anyhow!("Expected string as `@index`, but got {:?}", v)))
+            }
+        },
+        // Step 13.4.11
+        "@list" => {
+            // Step 13.4.11.1
+            if env.active_property.is_none() || env.active_property == Some("@graph") {
+                return Ok(());
+            }
+            // Step 13.4.11.2
+            let expanded = expand_element(
+                processor,
+                active_context,
+                env.active_property,
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?;
+            into_array(expanded)
+        }
+        // Step 13.4.12
+        "@set" => {
+            expand_element(
+                processor,
+                active_context,
+                env.active_property,
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?
+        }
+        // Step 13.4.13
+        "@reverse" => {
+            expand_reverse(env, value, result).await?;
+            // Step 13.4.13.5
+            return Ok(());
+        }
+        // Step 13.4.14
+        "@nest" => {
+            nests.push(key);
+            return Ok(());
+        }
+        // Step 13.4.15
+        "@default" | "@embed" | "@explicit" | "@omitDefault" | "@requireAll"
+            if env.frame_expansion =>
+        {
+            let expanded = expand_element(
+                processor,
+                active_context,
+                Some(expanded_property),
+                value,
+                env.base_url,
+                env.nested_params(),
+            )
+            .await?;
+            into_array(expanded)
+        }
+        // Other keywords are ignored.
+        _ => return Ok(()),
+    };
+    // Step 13.4.16
+    if !expanded_value.is_null() {
+        result.insert(expanded_property.to_owned(), expanded_value);
+    }
+
+    // Step 13.4.17
+    Ok(())
+}
+
+/// Expands `@reverse` entry.
+// Step 13.4.13
+async fn expand_reverse<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    value: &Value,
+    result: &mut JsonMap<String, Value>,
+) -> Result<()> {
+    // Step 13.4.13.1
+    if !value.is_object() {
+        return Err(ErrorCode::InvalidReverseValue
+            .and_source(anyhow!("Expected a map as `@reverse`, but got {:?}", value)));
+    }
+    // Step 13.4.13.2
+    let expanded_value = expand_element(
+        env.processor,
+        env.active_context,
+        Some("@reverse"),
+        value,
+        env.base_url,
+        env.nested_params(),
+    )
+    .await?;
+    let mut expanded_value = match expanded_value {
+        Value::Object(map) => map,
+        _ => return Ok(()),
+    };
+    // Step 13.4.13.3
+    if let Some(Value::Object(reversed_twice)) = expanded_value.remove("@reverse") {
+        // Step 13.4.13.3.1
+        for (property, item) in reversed_twice {
+            add_value(result, &property, item, true);
+        }
+    }
+    // Step 13.4.13.4
+    if !expanded_value.is_empty() {
+        // Step 13.4.13.4.1
+        let reverse_map = result
+            .entry("@reverse")
+            .or_insert_with(|| Value::Object(JsonMap::new()))
+            .as_object_mut()
+            .ok_or_else(|| {
+                ErrorCode::Uncategorized.and_source(anyhow!("`@reverse` entry is not a map"))
+            })?;
+        // Step 13.4.13.4.2
+        for (property, items) in expanded_value {
+            let items = match items {
+                Value::Array(arr) => arr,
+                v => vec![v],
+            };
+            // Step 13.4.13.4.2.1
+            for item in items {
+                // Step 13.4.13.4.2.1.1
+                if is_value_object(&item) || is_list_object(&item) {
+                    return Err(ErrorCode::InvalidReversePropertyValue.and_source(anyhow!(
+                        "Value of reverse property {:?} is a value object or a list object: {:?}",
+                        property,
+                        item
+                    )));
+                }
+                // Step 13.4.13.4.2.1.2
+                add_value(reverse_map, &property, item, true);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Expands `@id` entry of a frame.
+///
+/// The result is always an array of IRIs, or an array with an empty map (wildcard).
+// Step 13.4.3
+async fn expand_frame_id<L: LoadRemoteDocument>(
+    env: &EntriesEnv<'_, L>,
+    value: &Value,
+) -> Result<Value> {
+    // Step 13.4.3.1
+    let ids = match value {
+        Value::Object(map) if map.is_empty() => return Ok(Value::Array(vec![value.clone()])),
+        Value::String(_) => std::slice::from_ref(value),
+        Value::Array(arr) if !arr.is_empty() && arr.iter().all(Value::is_string) => arr,
+        v => {
+            return Err(ErrorCode::InvalidIdValue.and_source(anyhow!(
+                "Expected a string, an array of strings, or an empty map as `@id` in a frame, \
+                 but got {:?}",
+                v
+            )))
+        }
+    };
+    // Step 13.4.3.2
+    let mut expanded = Vec::with_capacity(ids.len());
+    for id in ids.iter().filter_map(Value::as_str) {
+        let id = ExpandIriOptions::constant(env.active_context)
+            .document_relative(true)
+            .expand_to_json(env.processor, id)
+            .await?;
+        if !id.is_null() {
+            expanded.push(id);
+        }
+    }
+
+    Ok(Value::Array(expanded))
+}
+
+/// Checks if the given map is a default object.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#dfn-default-object>.
+fn is_default_object(map: &JsonMap<String, Value>) -> bool {
+    map.len() == 1 && map.contains_key("@default")
+}
+
+/// Checks if the given value is a scalar.
+fn is_scalar(v: &Value) -> bool {
+    matches!(v, Value::Bool(_) | Value::Number(_) | Value::String(_))
+}
+
+/// Normalizes a frame pattern which is a string, an array of strings, or an empty map
+/// (wildcard) into an array.
+///
+/// Returns `None` if the value is not a valid pattern, or some strings are rejected by `is_valid`.
+fn frame_string_pattern(value: &Value, is_valid: impl Fn(&str) -> bool) -> Option<Value> {
+    let is_valid = |v: &Value| v.as_str().is_some_and(&is_valid);
+    match value {
+        Value::Object(map) if map.is_empty() => Some(Value::Array(vec![value.clone()])),
+        Value::Array(arr) if arr.iter().all(is_valid) => Some(value.clone()),
+        v if is_valid(v) => Some(Value::Array(vec![v.clone()])),
+        _ => None,
+    }
+}
+
+/// Converts the given value into an array, if necessary.
+fn into_array(v: Value) -> Value {
+    match v {
+        Value::Array(_) => v,
+        Value::Null => Value::Array(Vec::new()),
+        v => Value::Array(vec![v]),
+    }
+}
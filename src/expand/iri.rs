@@ -307,12 +307,8 @@ async fn expand_str<'a, L: LoadRemoteDocument>(
         // See <https://github.com/w3c/json-ld-api/issues/180#issuecomment-547177451>.
         let base = match options.active_context().base() {
             Nullable::Value(base) => base,
-            Nullable::Null => {
-                // Not sure what to do when the base is explicitly nullified.
-                return Err(ErrorCode::Uncategorized.and_source(anyhow::anyhow!(
-                    "`document_relative` is true but base IRI from the active context is `null`",
-                )));
-            }
+            // If the base is explicitly nullified, relative IRIs are left as is.
+            Nullable::Null => return Ok(Some(Cow::Borrowed(value))),
         };
         let value: &IriReferenceStr = IriReferenceStr::new(value).map_err(|e| {
             ErrorCode::Uncategorized.and_source(anyhow::anyhow!(
@@ -0,0 +1,164 @@
+//! Value expansion.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#value-expansion>.
+
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    context::{Context, Definition},
+    error::Result,
+    expand::iri::ExpandIriOptions,
+    json::single_entry_map,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+/// Runs value expansion algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#value-expansion>.
+pub(crate) async fn expand_value<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    active_context: &Context,
+    active_property: Option<&str>,
+    value: &Value,
+) -> Result<JsonMap<String, Value>> {
+    let definition = active_property.and_then(|prop| active_context.term_definition(prop));
+    let type_mapping = definition.and_then(Definition::ty);
+    if let Value::String(s) = value {
+        match type_mapping {
+            // Step 1
+            Some("@id") => {
+                let id = ExpandIriOptions::constant(active_context)
+                    .document_relative(true)
+                    .expand_to_json(processor, s)
+                    .await?;
+                return Ok(single_entry_map("@id", id));
+            }
+            // Step 2
+            Some("@vocab") => {
+                let id = ExpandIriOptions::constant(active_context)
+                    .vocab(true)
+                    .document_relative(true)
+                    .expand_to_json(processor, s)
+                    .await?;
+                return Ok(single_entry_map("@id", id));
+            }
+            _ => {}
+        }
+    }
+    // Step 3
+    let mut result = single_entry_map("@value", value.clone());
+    match type_mapping {
+        // Step 4
+        Some(ty) if !matches!(ty, "@id" | "@vocab" | "@none") => {
+            result.insert("@type".into(), ty.into());
+        }
+        // Step 5
+        _ if value.is_string() => {
+            // Step 5.1
+            let language = match definition.and_then(Definition::language) {
+                Some(language) => language.into(),
+                None => active_context.default_language(),
+            };
+            // Step 5.2
+            let direction = match definition.and_then(Definition::direction) {
+                Some(direction) => direction.into(),
+                None => active_context.default_base_direction(),
+            };
+            // Step 5.3
+            if let Some(language) = language {
+                result.insert("@language".into(), language.into());
+            }
+            // Step 5.4
+            if let Some(direction) = direction {
+                result.insert("@direction".into(), direction.as_str().into());
+            }
+        }
+        _ => {}
+    }
+
+    // Step 6
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        context::Context,
+        test_utils::{block_on, context, processor},
+        ErrorCode,
+    };
+
+    /// Expands the given value using the context.
+    fn expand(ctx: &Context, prop: &str, value: Value) -> Value {
+        block_on(ctx.expand_value(&processor(), Some(prop), &value))
+            .map(Value::Object)
+            .expect("Should never fail: valid value")
+    }
+
+    #[test]
+    fn type_mappings() {
+        let ctx = context(json!({
+            "@vocab": "http://example.org/",
+            "id": { "@id": "http://example.org/id", "@type": "@id" },
+            "vocab": { "@id": "http://example.org/vocab", "@type": "@vocab" },
+            "date": {
+                "@id": "http://example.org/date",
+                "@type": "http://www.w3.org/2001/XMLSchema#date"
+            },
+            "json": { "@id": "http://example.org/json", "@type": "@json" },
+            "none": { "@id": "http://example.org/none", "@type": "@none" }
+        }));
+        assert_eq!(
+            expand(&ctx, "id", json!("foo")),
+            json!({ "@id": "http://example.com/foo" })
+        );
+        assert_eq!(
+            expand(&ctx, "vocab", json!("foo")),
+            json!({ "@id": "http://example.org/foo" })
+        );
+        assert_eq!(
+            expand(&ctx, "date", json!("2019-11-12")),
+            json!({ "@value": "2019-11-12", "@type": "http://www.w3.org/2001/XMLSchema#date" })
+        );
+        assert_eq!(
+            expand(&ctx, "json", json!(true)),
+            json!({ "@value": true, "@type": "@json" })
+        );
+        assert_eq!(expand(&ctx, "none", json!(42)), json!({ "@value": 42 }));
+        // `@id` type mapping is only applied to strings.
+        assert_eq!(expand(&ctx, "id", json!(42)), json!({ "@value": 42 }));
+    }
+
+    #[test]
+    fn language_and_direction() {
+        let ctx = context(json!({
+            "@language": "en",
+            "@direction": "ltr",
+            "ja": { "@id": "http://example.org/ja", "@language": "ja", "@direction": "rtl" },
+            "plain": { "@id": "http://example.org/plain", "@language": null, "@direction": null },
+            "default": "http://example.org/default"
+        }));
+        assert_eq!(
+            expand(&ctx, "default", json!("x")),
+            json!({ "@value": "x", "@language": "en", "@direction": "ltr" })
+        );
+        assert_eq!(
+            expand(&ctx, "ja", json!("x")),
+            json!({ "@value": "x", "@language": "ja", "@direction": "rtl" })
+        );
+        // Explicit `null` mappings override the defaults.
+        assert_eq!(expand(&ctx, "plain", json!("x")), json!({ "@value": "x" }));
+        // Language and direction are only applied to strings.
+        assert_eq!(expand(&ctx, "default", json!(1)), json!({ "@value": 1 }));
+    }
+
+    #[test]
+    fn reject_non_scalar() {
+        let ctx = context(json!({}));
+        let err = block_on(ctx.expand_value(&processor(), None, &json!({ "a": 1 }))).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidValueObjectValue);
+    }
+}
@@ -0,0 +1,257 @@
+//! Flattening algorithms and node maps.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#flattening-algorithms>.
+
+use serde_json::Value;
+
+use crate::{
+    compact, error::Result, expand, json::single_entry_map, processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+pub use self::{
+    blank_node::BlankNodeIdIssuer,
+    node_map::{Graph, NodeMap},
+};
+
+mod blank_node;
+mod node_map;
+
+/// Flattens the given document.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-flatten>.
+pub(crate) async fn flatten<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+    context: Option<&Value>,
+) -> Result<Value> {
+    let expanded_input = expand::expand(processor, input).await?;
+    let mut issuer = BlankNodeIdIssuer::default();
+    // Step 1-6 of the flattening algorithm.
+    let flattened = flatten_expanded(&mut issuer, &expanded_input)?;
+    match context {
+        // Step 7 of the flattening algorithm.
+        None => Ok(flattened),
+        // Step 8 of the flattening algorithm.
+        Some(context) => compact::compact_expanded(processor, &flattened, context, true).await,
+    }
+}
+
+/// Merges the given documents into a single graph.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#merge-node-maps>.
+pub(crate) async fn merge<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    inputs: &[Value],
+) -> Result<Value> {
+    let mut issuer = BlankNodeIdIssuer::default();
+    let mut node_map = NodeMap::new();
+    for input in inputs {
+        let expanded_input = expand::expand(processor, input).await?;
+        // Blank nodes in different documents are different nodes.
+        issuer.clear_identifier_map();
+        node_map.add_element(&mut issuer, &expanded_input)?;
+    }
+    Ok(Value::Array(to_node_array(&node_map.merge_graphs())))
+}
+
+/// Runs the flattening algorithm without compaction.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#flattening-algorithm>.
+pub(crate) fn flatten_expanded(issuer: &mut BlankNodeIdIssuer, element: &Value) -> Result<Value> {
+    // Step 1
+    let mut node_map = NodeMap::new();
+    // Step 2
+    node_map.add_element(issuer, element)?;
+    Ok(flatten_node_map(&node_map))
+}
+
+/// Creates the flattened document from the node map.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#flattening-algorithm>.
+// Step 3-6
+pub(crate) fn flatten_node_map(node_map: &NodeMap) -> Value {
+    // Step 3
+    let mut default_graph = node_map.default_graph().clone();
+    // Step 4
+    for (graph_name, graph) in node_map.named_graphs() {
+        // Step 4.1, 4.2
+        let entry = default_graph
+            .entry(graph_name.to_owned())
+            .or_insert_with(|| single_entry_map("@id", graph_name));
+        // Step 4.3, 4.4
+        entry.insert("@graph".into(), Value::Array(to_node_array(graph)));
+    }
+    // Step 5, 6
+    Value::Array(to_node_array(&default_graph))
+}
+
+/// Returns an array of nodes ordered by the identifiers, excluding nodes which only have `@id`.
+// Step 4.4, 6
+fn to_node_array(graph: &Graph) -> Vec<Value> {
+    graph
+        .values()
+        .filter(|node| !(node.len() == 1 && node.contains_key("@id")))
+        .cloned()
+        .map(Value::Object)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        test_utils::{block_on, processor},
+        ErrorCode,
+    };
+
+    /// Flattens the input.
+    fn flatten(input: Value, context: Option<Value>) -> Value {
+        block_on(processor().flatten(&input, context.as_ref()))
+            .expect("Should never fail: valid input")
+    }
+
+    #[test]
+    fn flatten_nested_nodes() {
+        let input = json!({
+            "@id": "http://example.org/a",
+            "@type": "http://example.org/T",
+            "http://example.org/knows": [
+                {
+                    "@id": "_:x",
+                    "http://example.org/name": "X",
+                    "http://example.org/knows": { "@id": "http://example.org/a" }
+                },
+                { "http://example.org/name": ["Y", "Y"] }
+            ],
+            "http://example.org/list": { "@list": [{ "@id": "_:x" }, 1] },
+            "@reverse": {
+                "http://example.org/parent": { "@id": "http://example.org/c" }
+            }
+        });
+        assert_eq!(
+            flatten(input, None),
+            json!([
+                {
+                    "@id": "_:b0",
+                    "http://example.org/knows": [{ "@id": "http://example.org/a" }],
+                    "http://example.org/name": [{ "@value": "X" }]
+                },
+                {
+                    "@id": "_:b1",
+                    "http://example.org/name": [{ "@value": "Y" }]
+                },
+                {
+                    "@id": "http://example.org/a",
+                    "@type": ["http://example.org/T"],
+                    "http://example.org/knows": [{ "@id": "_:b0" }, { "@id": "_:b1" }],
+                    "http://example.org/list": [{
+                        "@list": [{ "@id": "_:b0" }, { "@value": 1 }]
+                    }]
+                },
+                {
+                    "@id": "http://example.org/c",
+                    "http://example.org/parent": [{ "@id": "http://example.org/a" }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn flatten_named_graphs() {
+        let input = json!([
+            {
+                "@id": "http://example.org/g",
+                "@graph": { "@id": "http://example.org/a", "http://example.org/p": 1 }
+            },
+            {
+                "@graph": { "@id": "http://example.org/b", "http://example.org/p": [] }
+            }
+        ]);
+        assert_eq!(
+            flatten(input, None),
+            json!([
+                {
+                    "@id": "_:b0",
+                    "@graph": [{ "@id": "http://example.org/b", "http://example.org/p": [] }]
+                },
+                {
+                    "@id": "http://example.org/g",
+                    "@graph": [{
+                        "@id": "http://example.org/a",
+                        "http://example.org/p": [{ "@value": 1 }]
+                    }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn flatten_with_context() {
+        let input = json!({
+            "@id": "http://example.org/a",
+            "http://example.org/p": { "@id": "http://example.org/b" }
+        });
+        let context = json!({ "@vocab": "http://example.org/" });
+        assert_eq!(
+            flatten(input, Some(context.clone())),
+            json!({
+                "@context": context,
+                "@graph": [{
+                    "@id": "http://example.org/a",
+                    "p": { "@id": "http://example.org/b" }
+                }]
+            })
+        );
+        assert_eq!(flatten(json!([]), Some(json!({}))), json!({ "@graph": [] }));
+    }
+
+    #[test]
+    fn merge_documents() {
+        let inputs = [
+            json!({
+                "@id": "http://example.org/a",
+                "@type": "http://example.org/T",
+                "http://example.org/p": { "@id": "_:x", "http://example.org/n": 1 }
+            }),
+            json!({
+                "@id": "http://example.org/g",
+                "@graph": {
+                    "@id": "http://example.org/a",
+                    "@type": ["http://example.org/T", "http://example.org/U"],
+                    "http://example.org/p": { "@id": "_:x", "http://example.org/n": 2 }
+                }
+            }),
+        ];
+        assert_eq!(
+            block_on(processor().merge(&inputs)).expect("Should never fail: valid input"),
+            json!([
+                {
+                    "@id": "_:b0",
+                    "http://example.org/n": [{ "@value": 1 }]
+                },
+                {
+                    "@id": "_:b1",
+                    "http://example.org/n": [{ "@value": 2 }]
+                },
+                {
+                    "@id": "http://example.org/a",
+                    "@type": ["http://example.org/T", "http://example.org/U"],
+                    "http://example.org/p": [{ "@id": "_:b0" }, { "@id": "_:b1" }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn conflicting_indexes() {
+        let input = json!([
+            { "@id": "http://example.org/a", "@index": "x" },
+            { "@id": "http://example.org/a", "@index": "y" }
+        ]);
+        let err = block_on(processor().flatten(&input, None))
+            .expect_err("Should never fail: conflicting indexes");
+        assert_eq!(err.code(), ErrorCode::ConflictingIndexes);
+    }
+}
@@ -0,0 +1,90 @@
+//! Blank node identifier generation.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#generate-blank-node-identifier>.
+
+use std::collections::HashMap;
+
+/// Default prefix of blank node identifiers to be issued.
+const DEFAULT_PREFIX: &str = "_:b";
+
+/// Blank node identifier issuer.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#generate-blank-node-identifier>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlankNodeIdIssuer {
+    /// Prefix of identifiers to be issued.
+    prefix: String,
+    /// Counter.
+    counter: usize,
+    /// Identifier map (from old identifiers to issued identifiers).
+    identifier_map: HashMap<String, String>,
+}
+
+impl BlankNodeIdIssuer {
+    /// Creates a new issuer which issues identifiers with the given prefix.
+    ///
+    /// The prefix should start with `_:`.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            counter: 0,
+            identifier_map: HashMap::new(),
+        }
+    }
+
+    /// Runs generate blank node identifier algorithm.
+    ///
+    /// If `identifier` is `Some(_)`, the same identifier is always issued for the same old
+    /// identifier.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#generate-blank-node-identifier>.
+    pub fn issue(&mut self, identifier: Option<&str>) -> String {
+        // Step 1
+        if let Some(issued) = identifier.and_then(|id| self.identifier_map.get(id)) {
+            return issued.clone();
+        }
+        // Step 2
+        let issued = format!("{}{}", self.prefix, self.counter);
+        // Step 3
+        self.counter += 1;
+        // Step 4
+        if let Some(identifier) = identifier {
+            self.identifier_map
+                .insert(identifier.to_owned(), issued.clone());
+        }
+        // Step 5
+        issued
+    }
+
+    /// Clears the identifier map, while keeping the counter.
+    ///
+    /// After this, blank node identifiers seen so far are relabelled to new identifiers which
+    /// have not been issued yet.
+    /// This is useful to prevent blank nodes in different documents from being merged.
+    pub fn clear_identifier_map(&mut self) {
+        self.identifier_map.clear();
+    }
+}
+
+impl Default for BlankNodeIdIssuer {
+    fn default() -> Self {
+        Self::with_prefix(DEFAULT_PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue() {
+        let mut issuer = BlankNodeIdIssuer::default();
+        assert_eq!(issuer.issue(Some("_:x")), "_:b0");
+        assert_eq!(issuer.issue(None), "_:b1");
+        assert_eq!(issuer.issue(Some("_:y")), "_:b2");
+        assert_eq!(issuer.issue(Some("_:x")), "_:b0");
+        assert_eq!(issuer.issue(None), "_:b3");
+        issuer.clear_identifier_map();
+        assert_eq!(issuer.issue(Some("_:x")), "_:b4");
+    }
+}
@@ -0,0 +1,449 @@
+//! Node map generation.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#node-map-generation>.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    error::{ErrorCode, Result},
+    flatten::blank_node::BlankNodeIdIssuer,
+    iri::is_blank_node_ident,
+    json::{single_entry_map, to_ref_array},
+    syntax::has_form_of_keyword,
+};
+
+/// Name of the default graph.
+const DEFAULT_GRAPH: &str = "@default";
+
+/// Graph in a node map.
+///
+/// Keys are node identifiers, and values are node objects.
+pub type Graph = BTreeMap<String, JsonMap<String, Value>>;
+
+/// Node map.
+///
+/// Keys are graph names (`@default` for the default graph), and values are graphs.
+///
+/// To combine multiple documents into one node map, add the expanded documents one by one with
+/// the same issuer, and call [`BlankNodeIdIssuer::clear_identifier_map`] between them so that
+/// blank node identifiers in different documents do not collide.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dfn-node-map>.
+///
+/// [`BlankNodeIdIssuer::clear_identifier_map`]:
+/// struct.BlankNodeIdIssuer.html#method.clear_identifier_map
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMap {
+    /// Graphs.
+    graphs: BTreeMap<String, Graph>,
+}
+
+impl NodeMap {
+    /// Creates a new node map which only has an empty default graph.
+    pub fn new() -> Self {
+        let mut graphs = BTreeMap::new();
+        graphs.insert(DEFAULT_GRAPH.to_owned(), Graph::new());
+        Self { graphs }
+    }
+
+    /// Returns the default graph.
+    pub fn default_graph(&self) -> &Graph {
+        &self.graphs[DEFAULT_GRAPH]
+    }
+
+    /// Returns the graph with the given name.
+    ///
+    /// `@default` is the name of the default graph.
+    pub fn graph(&self, name: &str) -> Option<&Graph> {
+        self.graphs.get(name)
+    }
+
+    /// Returns all graphs (including the default graph) ordered by graph name.
+    ///
+    /// `@default` is the name of the default graph.
+    pub fn graphs(&self) -> impl Iterator<Item = (&str, &Graph)> {
+        self.graphs
+            .iter()
+            .map(|(name, graph)| (name.as_str(), graph))
+    }
+
+    /// Returns the named graphs ordered by graph name.
+    pub fn named_graphs(&self) -> impl Iterator<Item = (&str, &Graph)> {
+        self.graphs
+            .iter()
+            .filter(|(name, _)| name.as_str() != DEFAULT_GRAPH)
+            .map(|(name, graph)| (name.as_str(), graph))
+    }
+
+    /// Returns the graphs, consuming the node map.
+    pub(crate) fn into_graphs(self) -> BTreeMap<String, Graph> {
+        self.graphs
+    }
+
+    /// Adds nodes in the given expanded element to the node map.
+    ///
+    /// Blank node identifiers in the element are relabelled by the given issuer.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#node-map-generation>.
+    ///
+    /// # Failures
+    ///
+    /// Fails if the element is not in expanded document form, or if a node has conflicting
+    /// indexes.
+    pub fn add_element(&mut self, issuer: &mut BlankNodeIdIssuer, element: &Value) -> Result<()> {
+        Generator {
+            node_map: self,
+            issuer,
+        }
+        .generate(element, DEFAULT_GRAPH, None, None, None)
+    }
+
+    /// Merges all graphs in the node map into a single graph.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#merge-node-maps>.
+    pub fn merge_graphs(&self) -> Graph {
+        Self::merge_all(std::iter::once(self))
+    }
+
+    /// Merges all graphs in the given node maps into a single graph.
+    ///
+    /// Note that blank node identifiers are not relabelled here.
+    /// Node maps to be merged should be created with the same issuer (see [`NodeMap`]).
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#merge-node-maps>.
+    ///
+    /// [`NodeMap`]: struct.NodeMap.html
+    pub fn merge_all<'a>(node_maps: impl IntoIterator<Item = &'a NodeMap>) -> Graph {
+        // Step 1
+        let mut result = Graph::new();
+        // Step 2
+        let nodes = node_maps
+            .into_iter()
+            .flat_map(|node_map| node_map.graphs.values())
+            .flatten();
+        for (id, node) in nodes {
+            // Step 2.1
+            let merged_node = result
+                .entry(id.clone())
+                .or_insert_with(|| single_entry_map("@id", id.as_str()));
+            // Step 2.2
+            for (property, values) in node {
+                if property != "@type" && has_form_of_keyword(property) {
+                    // Step 2.2.1
+                    merged_node.insert(property.clone(), values.clone());
+                } else {
+                    // Step 2.2.2
+                    merged_node
+                        .entry(property.as_str())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    for value in to_ref_array(values) {
+                        append_unique(merged_node, property, value.clone());
+                    }
+                }
+            }
+        }
+
+        // Step 3
+        result
+    }
+
+    /// Returns the node in the graph, creating it if not exist.
+    fn node_mut(&mut self, graph: &str, id: &str) -> &mut JsonMap<String, Value> {
+        self.graphs
+            .entry(graph.to_owned())
+            .or_default()
+            .entry(id.to_owned())
+            .or_insert_with(|| single_entry_map("@id", id))
+    }
+}
+
+impl Default for NodeMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Active subject.
+#[derive(Debug, Clone, Copy)]
+enum Subject<'a> {
+    /// Node with the identifier.
+    Node(&'a str),
+    /// Node referenced by the reverse property.
+    ///
+    /// This corresponds to the referenced node (map) passed as an active subject in the spec.
+    Reverse(&'a str),
+}
+
+/// Node map generator.
+struct Generator<'a> {
+    /// Node map.
+    node_map: &'a mut NodeMap,
+    /// Blank node identifier issuer.
+    issuer: &'a mut BlankNodeIdIssuer,
+}
+
+impl Generator<'_> {
+    /// Runs node map generation algorithm.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#node-map-generation>.
+    fn generate(
+        &mut self,
+        element: &Value,
+        active_graph: &str,
+        active_subject: Option<Subject<'_>>,
+        active_property: Option<&str>,
+        mut list: Option<&mut Vec<Value>>,
+    ) -> Result<()> {
+        let element = match element {
+            // Step 1
+            Value::Array(arr) => {
+                // Step 1.1
+                for item in arr {
+                    self.generate(
+                        item,
+                        active_graph,
+                        active_subject,
+                        active_property,
+                        list.as_deref_mut(),
+                    )?;
+                }
+                return Ok(());
+            }
+            // Step 2
+            Value::Object(map) => map,
+            v => {
+                return Err(ErrorCode::Uncategorized.and_source(anyhow!(
+                    "Expected an array or a map in expanded document form, but got {:?}",
+                    v
+                )))
+            }
+        };
+        let mut element = element.clone();
+        // Step 3
+        if let Some(types) = element.get_mut("@type") {
+            match types {
+                Value::String(ty) if is_blank_node_ident(ty) => *ty = self.issuer.issue(Some(ty)),
+                Value::Array(arr) => {
+                    for ty in arr {
+                        if let Value::String(ty) = ty {
+                            if is_blank_node_ident(ty) {
+                                *ty = self.issuer.issue(Some(ty));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if element.contains_key("@value") {
+            // Step 4
+            self.add_to_subject(
+                active_graph,
+                active_subject,
+                active_property,
+                list,
+                Value::Object(element),
+            );
+        } else if let Some(list_value) = element.get("@list") {
+            // Step 5
+            // Step 5.1
+            let mut result = Vec::new();
+            // Step 5.2
+            self.generate(
+                list_value,
+                active_graph,
+                active_subject,
+                active_property,
+                Some(&mut result),
+            )?;
+            let result = Value::Object(single_entry_map("@list", result));
+            match (list, active_subject, active_property) {
+                // Step 5.3
+                (None, Some(Subject::Node(subject)), Some(property)) => {
+                    let node = self.node_map.node_mut(active_graph, subject);
+                    append(node, property, result);
+                }
+                // Step 5.4
+                (Some(list), _, _) => list.push(result),
+                _ => {}
+            }
+        } else {
+            // Step 6
+            self.generate_node(element, active_graph, active_subject, active_property, list)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs node map generation algorithm for a node object.
+    // Step 6
+    fn generate_node(
+        &mut self,
+        mut element: JsonMap<String, Value>,
+        active_graph: &str,
+        active_subject: Option<Subject<'_>>,
+        active_property: Option<&str>,
+        list: Option<&mut Vec<Value>>,
+    ) -> Result<()> {
+        let id = match element.remove("@id") {
+            // Step 6.1
+            Some(Value::String(id)) if is_blank_node_ident(&id) => self.issuer.issue(Some(&id)),
+            Some(Value::String(id)) => id,
+            // Step 6.2
+            _ => self.issuer.issue(None),
+        };
+        // Step 6.3, 6.4
+        self.node_map.node_mut(active_graph, &id);
+        match (active_subject, active_property) {
+            // Step 6.5
+            (Some(Subject::Reverse(subject)), Some(property)) => {
+                let node = self.node_map.node_mut(active_graph, &id);
+                append_unique(node, property, single_entry_map("@id", subject).into());
+            }
+            // Step 6.6
+            (_, Some(_)) => {
+                // Step 6.6.1
+                let reference = Value::Object(single_entry_map("@id", id.as_str()));
+                // Step 6.6.2, 6.6.3
+                self.add_to_subject(
+                    active_graph,
+                    active_subject,
+                    active_property,
+                    list,
+                    reference,
+                );
+            }
+            _ => {}
+        }
+        // Step 6.7
+        if let Some(types) = element.remove("@type") {
+            let node = self.node_map.node_mut(active_graph, &id);
+            for ty in to_ref_array(&types) {
+                append_unique(node, "@type", ty.clone());
+            }
+        }
+        // Step 6.8
+        if let Some(index) = element.remove("@index") {
+            let node = self.node_map.node_mut(active_graph, &id);
+            match node.get("@index") {
+                Some(existing) if *existing != index => {
+                    return Err(ErrorCode::ConflictingIndexes.and_source(anyhow!(
+                        "Node {:?} has conflicting indexes {} and {}",
+                        id,
+                        existing,
+                        index
+                    )))
+                }
+                _ => {
+                    node.insert("@index".into(), index);
+                }
+            }
+        }
+        // Step 6.9
+        if let Some(reverse_map) = element.remove("@reverse") {
+            // Step 6.9.1, 6.9.2
+            let reverse_map = match reverse_map {
+                Value::Object(map) => map,
+                v => {
+                    return Err(ErrorCode::InvalidReverseValue.and_source(anyhow!(
+                    "Expected a map as `@reverse` value in expanded document form, but got {:?}",
+                    v
+                )))
+                }
+            };
+            // Step 6.9.3
+            for (property, values) in &reverse_map {
+                // Step 6.9.3.1
+                for value in to_ref_array(values) {
+                    // Step 6.9.3.1.1
+                    self.generate(
+                        value,
+                        active_graph,
+                        Some(Subject::Reverse(&id)),
+                        Some(property),
+                        None,
+                    )?;
+                }
+            }
+            // Step 6.9.4: Already removed.
+        }
+        // Step 6.10
+        if let Some(graph) = element.remove("@graph") {
+            self.node_map.graphs.entry(id.clone()).or_default();
+            self.generate(&graph, &id, None, None, None)?;
+        }
+        // Step 6.11
+        if let Some(included) = element.remove("@included") {
+            self.generate(&included, active_graph, None, None, None)?;
+        }
+        // Step 6.12
+        for (property, value) in element {
+            // Step 6.12.1
+            let property = if is_blank_node_ident(&property) {
+                self.issuer.issue(Some(&property))
+            } else {
+                property
+            };
+            // Step 6.12.2
+            self.node_map
+                .node_mut(active_graph, &id)
+                .entry(property.as_str())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            // Step 6.12.3
+            self.generate(
+                &value,
+                active_graph,
+                Some(Subject::Node(&id)),
+                Some(&property),
+                None,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the value to the active property of the active subject, or to the list if available.
+    // Step 4, 6.6.2, 6.6.3
+    fn add_to_subject(
+        &mut self,
+        active_graph: &str,
+        active_subject: Option<Subject<'_>>,
+        active_property: Option<&str>,
+        list: Option<&mut Vec<Value>>,
+        value: Value,
+    ) {
+        match (list, active_subject, active_property) {
+            (Some(list), _, _) => list.push(value),
+            (None, Some(Subject::Node(subject)), Some(property)) => {
+                let node = self.node_map.node_mut(active_graph, subject);
+                append_unique(node, property, value);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Appends the value to the array entry of the node.
+fn append(node: &mut JsonMap<String, Value>, property: &str, value: Value) {
+    match node
+        .entry(property)
+        .or_insert_with(|| Value::Array(Vec::new()))
+    {
+        Value::Array(arr) => arr.push(value),
+        v => *v = Value::Array(vec![v.take(), value]),
+    }
+}
+
+/// Appends the value to the array entry of the node, if no equivalent values exist.
+fn append_unique(node: &mut JsonMap<String, Value>, property: &str, value: Value) {
+    let is_duplicate = node
+        .get(property)
+        .is_some_and(|values| to_ref_array(values).contains(&value));
+    if !is_duplicate {
+        append(node, property, value);
+    }
+}
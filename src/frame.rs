@@ -0,0 +1,452 @@
+//! Framing algorithms.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#framing-algorithms>.
+
+use std::collections::HashMap;
+
+use serde_json::{Map as JsonMap, Value};
+
+use crate::{
+    compact,
+    context::Context,
+    error::Result,
+    expand::{self, iri::ExpandIriOptions},
+    flatten::{BlankNodeIdIssuer, NodeMap},
+    iri::is_blank_node_ident,
+    processor::Processor,
+    remote::LoadRemoteDocument,
+};
+
+pub use self::embed::Embed;
+
+use self::framing::{Framer, MERGED_GRAPH};
+
+mod embed;
+mod framing;
+
+/// Frames the given document.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldprocessor-frame>.
+pub(crate) async fn frame<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+    frame: &Value,
+) -> Result<Value> {
+    // Step 2-6
+    let expanded_input = expand::expand(processor, input).await?;
+    // Step 7-11
+    let expanded_frame = expand::expand_frame(processor, frame).await?;
+    // Step 12
+    let context = frame.get("@context").unwrap_or(&Value::Null);
+    // Step 13-14
+    let frame_default = has_graph_entry(processor, frame, context).await?;
+    // Step 15
+    let mut issuer = BlankNodeIdIssuer::default();
+    let mut node_map = NodeMap::new();
+    node_map.add_element(&mut issuer, &expanded_input)?;
+    // Step 16
+    let graph_name = if frame_default {
+        "@default"
+    } else {
+        MERGED_GRAPH
+    };
+    let merged = if frame_default {
+        None
+    } else {
+        Some(node_map.merge_graphs())
+    };
+    let mut graph_map = node_map.into_graphs();
+    graph_map.entry("@default".into()).or_default();
+    if let Some(merged) = merged {
+        graph_map.insert(MERGED_GRAPH.into(), merged);
+    }
+    // Step 17
+    let subjects = graph_map[graph_name]
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let mut framed = Framer::new(processor.options(), &graph_map).frame(
+        graph_name,
+        false,
+        &subjects,
+        &expanded_frame,
+        true,
+    )?;
+    // Step 18
+    if !processor.is_processing_mode_1_0() {
+        prune_blank_node_identifiers(&mut framed);
+    }
+    // Step 19
+    let compacted = compact::compact_expanded(
+        processor,
+        &Value::Array(framed),
+        context,
+        !processor.options().omit_graph(),
+    )
+    .await?;
+    // Step 20
+    Ok(replace_preserved(compacted).unwrap_or(Value::Null))
+}
+
+/// Checks if the frame has an entry expanding to `@graph` at the top level.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldprocessor-frame>.
+async fn has_graph_entry<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    frame: &Value,
+    context: &Value,
+) -> Result<bool> {
+    let frame = match frame {
+        Value::Object(map) => map,
+        _ => return Ok(false),
+    };
+    let base = processor.options().document_iri();
+    let active_context = Context::with_base(base.to_owned())
+        .join_context_value(processor, context, base, false)
+        .await?;
+    for key in frame.keys() {
+        let expanded = ExpandIriOptions::constant(&active_context)
+            .vocab(true)
+            .expand_str(processor, key)
+            .await?;
+        if expanded.as_deref() == Some("@graph") {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Removes `@id` entries of blank nodes which are referenced only once.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldprocessor-frame>.
+fn prune_blank_node_identifiers(framed: &mut [Value]) {
+    /// Counts usages of blank node identifiers.
+    fn count<'a>(v: &'a Value, counts: &mut HashMap<&'a str, usize>) {
+        match v {
+            Value::Array(arr) => arr.iter().for_each(|v| count(v, counts)),
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        // Values of value objects are not identifiers.
+                        ("@value", _) => {}
+                        ("@id", Value::String(s)) if is_blank_node_ident(s) => {
+                            *counts.entry(s).or_default() += 1;
+                        }
+                        ("@type", Value::Array(types)) => types
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .filter(|s| is_blank_node_ident(s))
+                            .for_each(|s| *counts.entry(s).or_default() += 1),
+                        (_, value) => count(value, counts),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Removes `@id` entries used only once.
+    fn prune(v: &mut Value, once: &[String]) {
+        match v {
+            Value::Array(arr) => arr.iter_mut().for_each(|v| prune(v, once)),
+            Value::Object(map) => {
+                let used_once = map
+                    .get("@id")
+                    .and_then(Value::as_str)
+                    .is_some_and(|id| once.iter().any(|once| once == id));
+                if used_once {
+                    map.remove("@id");
+                }
+                map.iter_mut()
+                    .filter(|(key, _)| *key != "@value")
+                    .for_each(|(_, v)| prune(v, once));
+            }
+            _ => {}
+        }
+    }
+
+    let mut counts = HashMap::new();
+    framed.iter().for_each(|v| count(v, &mut counts));
+    let once = counts
+        .into_iter()
+        .filter(|&(_, n)| n == 1)
+        .map(|(id, _)| id.to_owned())
+        .collect::<Vec<_>>();
+    if !once.is_empty() {
+        framed.iter_mut().for_each(|v| prune(v, &once));
+    }
+}
+
+/// Replaces `@preserve` maps with their values, and `@null` with `null`.
+///
+/// Returns `None` if the value should be `null`.
+fn replace_preserved(v: Value) -> Option<Value> {
+    match v {
+        Value::String(s) if s == "@null" => None,
+        Value::Array(arr) => Some(Value::Array(
+            arr.into_iter().filter_map(replace_preserved).collect(),
+        )),
+        Value::Object(mut map) => {
+            if map.len() == 1 {
+                if let Some(preserved) = map.remove("@preserve") {
+                    return match preserved {
+                        Value::Array(mut arr) if arr.len() == 1 => replace_preserved(arr.remove(0)),
+                        v => replace_preserved(v),
+                    };
+                }
+            }
+            let map = map
+                .into_iter()
+                .map(|(k, v)| (k, replace_preserved(v).unwrap_or(Value::Null)))
+                .collect::<JsonMap<_, _>>();
+            Some(Value::Object(map))
+        }
+        v => Some(v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        frame::Embed,
+        processor::ProcessorOptions,
+        test_utils::{block_on, processor, NoLoader},
+        ErrorCode,
+    };
+
+    /// Frames the input with the given options.
+    fn frame_with(options: ProcessorOptions, input: Value, frame: Value) -> Value {
+        block_on(options.build(NoLoader).frame(&input, &frame))
+            .expect("Should never fail: valid input")
+    }
+
+    /// Frames the input with the default options.
+    fn frame(input: Value, frame: Value) -> Value {
+        frame_with(processor().options().clone(), input, frame)
+    }
+
+    /// Returns a library document with a book and its chapter.
+    fn library() -> Value {
+        json!({
+            "@context": {
+                "ex": "http://example.org/vocab#",
+                "contains": { "@id": "ex:contains", "@type": "@id" }
+            },
+            "@graph": [
+                {
+                    "@id": "http://example.org/library",
+                    "@type": "ex:Library",
+                    "contains": "http://example.org/library/book"
+                },
+                {
+                    "@id": "http://example.org/library/book",
+                    "@type": "ex:Book",
+                    "ex:title": "My Book",
+                    "contains": "http://example.org/library/book/chapter"
+                },
+                {
+                    "@id": "http://example.org/library/book/chapter",
+                    "@type": "ex:Chapter",
+                    "ex:title": "Chapter One"
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn embed_matched_nodes() {
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:Library",
+            "ex:contains": { "@type": "ex:Book" }
+        });
+        assert_eq!(
+            frame(library(), frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@id": "http://example.org/library",
+                "@type": "ex:Library",
+                "ex:contains": {
+                    "@id": "http://example.org/library/book",
+                    "@type": "ex:Book",
+                    "ex:contains": {
+                        "@id": "http://example.org/library/book/chapter",
+                        "@type": "ex:Chapter",
+                        "ex:title": "Chapter One"
+                    },
+                    "ex:title": "My Book"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn explicit_and_default() {
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:Book",
+            "@explicit": true,
+            "ex:title": {},
+            "ex:author": { "@default": "Anonymous" },
+            "ex:isbn": {}
+        });
+        assert_eq!(
+            frame(library(), frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@id": "http://example.org/library/book",
+                "@type": "ex:Book",
+                "ex:author": "Anonymous",
+                "ex:isbn": null,
+                "ex:title": "My Book"
+            })
+        );
+    }
+
+    #[test]
+    fn embed_never() {
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:Library",
+            "ex:contains": { "@embed": "@never" }
+        });
+        assert_eq!(
+            frame(library(), frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@id": "http://example.org/library",
+                "@type": "ex:Library",
+                "ex:contains": { "@id": "http://example.org/library/book" }
+            })
+        );
+    }
+
+    #[test]
+    fn embed_once() {
+        let input = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@id": "http://example.org/a",
+            "@type": "ex:T",
+            "ex:p": { "@id": "http://example.org/b", "ex:name": "B" },
+            "ex:q": { "@id": "http://example.org/b" }
+        });
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:T"
+        });
+        let framed = frame(input.clone(), frame_doc.clone());
+        assert_eq!(
+            framed["ex:p"],
+            json!({ "@id": "http://example.org/b", "ex:name": "B" })
+        );
+        assert_eq!(framed["ex:q"], json!({ "@id": "http://example.org/b" }));
+
+        let mut options = processor().options().clone();
+        options.set_embed(Embed::Always);
+        let framed = frame_with(options, input, frame_doc);
+        assert_eq!(framed["ex:p"], framed["ex:q"]);
+    }
+
+    #[test]
+    fn invalid_embed_value() {
+        let frame_doc = json!({ "@embed": "@sometimes" });
+        let err = block_on(processor().frame(&library(), &frame_doc))
+            .expect_err("Should fail: invalid `@embed` value");
+        assert_eq!(err.code(), ErrorCode::InvalidEmbedValue);
+    }
+
+    #[test]
+    fn omit_graph() {
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:Chapter"
+        });
+        let expected_node = json!({
+            "@id": "http://example.org/library/book/chapter",
+            "@type": "ex:Chapter",
+            "ex:title": "Chapter One"
+        });
+        let mut options = processor().options().clone();
+        options.set_omit_graph(Some(false));
+        assert_eq!(
+            frame_with(options, library(), frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@graph": [expected_node]
+            })
+        );
+    }
+
+    #[test]
+    fn prune_blank_node_identifiers() {
+        let input = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:T",
+            "ex:p": { "ex:name": "B" },
+            "ex:q": { "@id": "_:shared", "ex:name": "C" },
+            "ex:r": { "@id": "_:shared" }
+        });
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@type": "ex:T"
+        });
+        assert_eq!(
+            frame(input, frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@type": "ex:T",
+                "ex:p": { "ex:name": "B" },
+                "ex:q": { "@id": "_:b2", "ex:name": "C" },
+                "ex:r": { "@id": "_:b2" }
+            })
+        );
+    }
+
+    #[test]
+    fn require_all() {
+        let input = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@graph": [
+                { "@id": "http://example.org/a", "ex:p": "1", "ex:q": "2" },
+                { "@id": "http://example.org/b", "ex:p": "1" }
+            ]
+        });
+        let frame_doc = json!({
+            "@context": { "ex": "http://example.org/vocab#" },
+            "@requireAll": true,
+            "ex:p": {},
+            "ex:q": {}
+        });
+        assert_eq!(
+            frame(input, frame_doc),
+            json!({
+                "@context": { "ex": "http://example.org/vocab#" },
+                "@id": "http://example.org/a",
+                "ex:p": "1",
+                "ex:q": "2"
+            })
+        );
+    }
+
+    #[test]
+    fn expand_context_is_not_applied_to_frame() {
+        let mut options = processor().options().clone();
+        options.set_expand_context(Some(json!({ "@vocab": "http://example.org/vocab#" })));
+        let input = json!({ "@id": "http://example.org/a", "@type": "T" });
+        assert_eq!(
+            frame_with(options.clone(), input.clone(), json!({ "@type": "T" })),
+            json!({})
+        );
+        assert_eq!(
+            frame_with(
+                options,
+                input,
+                json!({ "@type": "http://example.org/vocab#T" })
+            ),
+            json!({ "@id": "http://example.org/a", "@type": "http://example.org/vocab#T" })
+        );
+    }
+}
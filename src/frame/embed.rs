@@ -0,0 +1,38 @@
+//! Embedding mode.
+
+use std::fmt;
+
+/// Value of `@embed` flag.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldembed>.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Embed {
+    /// `@always`: always embed node objects as property values, unless this would cause a
+    /// circular reference.
+    Always,
+    /// `@once`: only the first value of a node object is embedded, and other values are node
+    /// references.
+    ///
+    /// This is the default.
+    #[default]
+    Once,
+    /// `@never`: always use node references.
+    Never,
+}
+
+impl Embed {
+    /// Returns the keyword.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Embed::Always => "@always",
+            Embed::Once => "@once",
+            Embed::Never => "@never",
+        }
+    }
+}
+
+impl fmt::Display for Embed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,553 @@
+//! Framing algorithm.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#framing-algorithm>.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::anyhow;
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::{
+    error::{ErrorCode, Result},
+    flatten::Graph,
+    frame::Embed,
+    iri::{is_absolute_iri_ref, is_blank_node_ident},
+    json::{add_value, single_entry_map, to_ref_array},
+    processor::ProcessorOptions,
+    syntax::{has_form_of_keyword, is_list_object, is_node_object, is_value_object},
+};
+
+/// Name of the merged graph.
+pub(crate) const MERGED_GRAPH: &str = "@merged";
+
+/// Name of the default graph.
+const DEFAULT_GRAPH: &str = "@default";
+
+/// Framing state.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dfn-framing-state>.
+pub(crate) struct Framer<'a> {
+    /// Processor options.
+    options: &'a ProcessorOptions,
+    /// Graph map, which may have the merged graph as `@merged`.
+    graph_map: &'a BTreeMap<String, Graph>,
+    /// Graph names and identifiers of the nodes being embedded.
+    subject_stack: Vec<(&'a str, &'a str)>,
+    /// Identifiers of embedded nodes for each graph.
+    unique_embeds: HashMap<&'a str, HashSet<&'a str>>,
+}
+
+impl<'a> Framer<'a> {
+    /// Creates a new framing state.
+    pub(crate) fn new(
+        options: &'a ProcessorOptions,
+        graph_map: &'a BTreeMap<String, Graph>,
+    ) -> Self {
+        Self {
+            options,
+            graph_map,
+            subject_stack: Vec::new(),
+            unique_embeds: HashMap::new(),
+        }
+    }
+
+    /// Runs the framing algorithm and returns the framed nodes.
+    ///
+    /// `frame` should be an expanded frame, i.e. an array with a single map.
+    /// `top_level` should be true only for the initial call.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#framing-algorithm>.
+    pub(crate) fn frame(
+        &mut self,
+        graph: &'a str,
+        embedded: bool,
+        subjects: &[&str],
+        frame: &Value,
+        top_level: bool,
+    ) -> Result<Vec<Value>> {
+        let graph_map = self.graph_map;
+        let nodes = match graph_map.get(graph) {
+            Some(nodes) => nodes,
+            None => return Ok(Vec::new()),
+        };
+        let frame = validate_frame(frame)?;
+        // Step 1
+        let flags = Flags::new(self.options, frame)?;
+        // Step 2
+        let mut matches = Vec::new();
+        for id in subjects {
+            if let Some((id, subject)) = nodes.get_key_value(*id) {
+                if filter_subject(nodes, subject, frame, &flags)? {
+                    matches.push((id.as_str(), subject));
+                }
+            }
+        }
+        matches.sort_unstable_by_key(|&(id, _)| id);
+        matches.dedup_by_key(|&mut (id, _)| id);
+
+        // Step 3
+        let mut results = Vec::new();
+        for (id, subject) in matches {
+            // Each top-level match is a compartmentalized result.
+            if top_level {
+                self.unique_embeds.clear();
+            }
+            let unique_embeds = self.unique_embeds.entry(graph).or_default();
+            // Step 3.1
+            let mut output = single_entry_map("@id", id);
+            // Step 3.2
+            if !embedded && unique_embeds.contains(id) {
+                continue;
+            }
+            // Step 3.3
+            if embedded
+                && (flags.embed == Embed::Never || self.subject_stack.contains(&(graph, id)))
+            {
+                results.push(Value::Object(output));
+                continue;
+            }
+            // Step 3.4
+            if embedded && flags.embed == Embed::Once && unique_embeds.contains(id) {
+                results.push(Value::Object(output));
+                continue;
+            }
+            unique_embeds.insert(id);
+            self.subject_stack.push((graph, id));
+
+            // Step 3.5
+            if let Some((graph_name, graph_nodes)) = graph_map.get_key_value(id) {
+                let (recurse, subframe) = match frame.get("@graph") {
+                    // Step 3.5.1
+                    None => (graph != MERGED_GRAPH, json!([{}])),
+                    // Step 3.5.2
+                    Some(subframe) => {
+                        let subframe = match to_ref_array(subframe).first() {
+                            Some(v @ Value::Object(_)) => v.clone(),
+                            _ => json!({}),
+                        };
+                        (
+                            id != MERGED_GRAPH && id != DEFAULT_GRAPH,
+                            Value::Array(vec![subframe]),
+                        )
+                    }
+                };
+                // Step 3.5.3
+                if recurse {
+                    let graph_subjects = graph_nodes.keys().map(String::as_str).collect::<Vec<_>>();
+                    let framed =
+                        self.frame(graph_name, false, &graph_subjects, &subframe, false)?;
+                    add_frame_output(&mut output, "@graph", framed);
+                }
+            }
+            // Step 3.6
+            if let Some(included_frame) = frame.get("@included") {
+                let framed = self.frame(graph, false, subjects, included_frame, false)?;
+                add_frame_output(&mut output, "@included", framed);
+            }
+            // Step 3.7
+            for (property, objects) in subject {
+                // Step 3.7.1
+                if has_form_of_keyword(property) {
+                    output.insert(property.clone(), objects.clone());
+                    continue;
+                }
+                // Step 3.7.2
+                if flags.explicit && !frame.contains_key(property) {
+                    continue;
+                }
+                // Step 3.7.3
+                let implicit_frame = flags.implicit_frame();
+                let subframe = frame.get(property).unwrap_or(&implicit_frame);
+                for object in to_ref_array(objects) {
+                    if let Some(list) = object.get("@list").filter(|_| is_list_object(object)) {
+                        // Step 3.7.3.1
+                        let list_frame = subframe
+                            .get(0)
+                            .and_then(|v| v.get("@list"))
+                            .unwrap_or(&implicit_frame);
+                        let mut framed_list = Vec::new();
+                        for item in to_ref_array(list) {
+                            match subject_reference_id(item) {
+                                Some(item_id) => framed_list.extend(self.frame(
+                                    graph,
+                                    true,
+                                    &[item_id],
+                                    list_frame,
+                                    false,
+                                )?),
+                                None => framed_list.push(item.clone()),
+                            }
+                        }
+                        add_value(
+                            &mut output,
+                            property,
+                            Value::Object(single_entry_map("@list", framed_list)),
+                            true,
+                        );
+                    } else if let Some(object_id) = subject_reference_id(object) {
+                        // Step 3.7.3.2
+                        let framed = self.frame(graph, true, &[object_id], subframe, false)?;
+                        add_frame_output(&mut output, property, framed);
+                    } else if subframe
+                        .get(0)
+                        .and_then(Value::as_object)
+                        .is_some_and(|pattern| value_match(pattern, object))
+                    {
+                        // Step 3.7.3.3
+                        add_value(&mut output, property, object.clone(), true);
+                    }
+                }
+            }
+            // Step 3.8
+            for (property, property_frame) in frame {
+                if property == "@type" {
+                    // Step 3.8.1
+                    let default_types = to_ref_array(property_frame)
+                        .iter()
+                        .filter_map(|ty| ty.get("@default"))
+                        .flat_map(to_ref_array)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if !default_types.is_empty() && !output.contains_key("@type") {
+                        output.insert("@type".into(), Value::Array(default_types));
+                    }
+                    continue;
+                }
+                if has_form_of_keyword(property) {
+                    continue;
+                }
+                // Step 3.8.2
+                let next = to_ref_array(property_frame)
+                    .first()
+                    .and_then(Value::as_object);
+                let omit_default = next
+                    .and_then(|next| frame_flag(next, "@omitDefault"))
+                    .and_then(Value::as_bool)
+                    .unwrap_or_else(|| self.options.omit_default());
+                // Step 3.8.2.1
+                if !omit_default && !output.contains_key(property) {
+                    let default = next
+                        .and_then(|next| next.get("@default"))
+                        .cloned()
+                        .unwrap_or_else(|| "@null".into());
+                    let preserved = match default {
+                        v @ Value::Array(_) => v,
+                        v => Value::Array(vec![v]),
+                    };
+                    output.insert(
+                        property.clone(),
+                        json!([single_entry_map("@preserve", preserved)]),
+                    );
+                }
+            }
+            // Step 3.9
+            if let Some(reverse_frame) = frame.get("@reverse").and_then(Value::as_object) {
+                for (reverse_property, subframe) in reverse_frame {
+                    for (referrer_id, referrer) in nodes {
+                        let refers_to_subject = referrer
+                            .get(reverse_property)
+                            .map_or(&[][..], to_ref_array)
+                            .iter()
+                            .any(|v| v.get("@id").and_then(Value::as_str) == Some(id));
+                        if !refers_to_subject {
+                            continue;
+                        }
+                        let framed = self.frame(graph, true, &[referrer_id], subframe, false)?;
+                        let reverse = output
+                            .entry("@reverse")
+                            .or_insert_with(|| Value::Object(JsonMap::new()))
+                            .as_object_mut()
+                            .expect("Should never fail: `@reverse` entry is always a map");
+                        add_value(reverse, reverse_property, Value::Array(framed), true);
+                    }
+                }
+            }
+            // Step 3.10
+            results.push(Value::Object(output));
+            self.subject_stack.pop();
+        }
+
+        Ok(results)
+    }
+}
+
+/// Flags of a frame.
+#[derive(Debug, Clone, Copy)]
+struct Flags {
+    /// `@embed` flag.
+    embed: Embed,
+    /// `@explicit` flag.
+    explicit: bool,
+    /// `@requireAll` flag.
+    require_all: bool,
+}
+
+impl Flags {
+    /// Creates flags from the frame, using the options as the default.
+    fn new(options: &ProcessorOptions, frame: &JsonMap<String, Value>) -> Result<Self> {
+        let embed = match frame_flag(frame, "@embed") {
+            None => options.embed(),
+            Some(Value::Bool(true)) => Embed::Once,
+            Some(Value::Bool(false)) => Embed::Never,
+            Some(Value::String(s)) if s == "@always" => Embed::Always,
+            Some(Value::String(s)) if s == "@once" => Embed::Once,
+            Some(Value::String(s)) if s == "@never" => Embed::Never,
+            Some(v) => {
+                return Err(ErrorCode::InvalidEmbedValue
+                    .and_source(anyhow!("Unsupported `@embed` value {:?}", v)))
+            }
+        };
+        let bool_flag = |key: &str, default: bool| {
+            frame_flag(frame, key)
+                .and_then(Value::as_bool)
+                .unwrap_or(default)
+        };
+        Ok(Self {
+            embed,
+            explicit: bool_flag("@explicit", options.explicit()),
+            require_all: bool_flag("@requireAll", options.require_all()),
+        })
+    }
+
+    /// Creates an implicit frame, which matches any node and has the same flags.
+    fn implicit_frame(&self) -> Value {
+        json!([{
+            "@embed": [self.embed.as_str()],
+            "@explicit": [self.explicit],
+            "@requireAll": [self.require_all],
+        }])
+    }
+}
+
+/// Returns the value of the flag in the expanded frame.
+fn frame_flag<'a>(frame: &'a JsonMap<String, Value>, key: &str) -> Option<&'a Value> {
+    let value = to_ref_array(frame.get(key)?).first()?;
+    match value {
+        Value::Object(map) => map.get("@value"),
+        v => Some(v),
+    }
+}
+
+/// Checks if the expanded frame is valid, and returns the frame as a map.
+fn validate_frame(frame: &Value) -> Result<&JsonMap<String, Value>> {
+    let frame = match frame {
+        Value::Array(arr) if arr.len() == 1 => arr[0].as_object(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        ErrorCode::InvalidFrame.and_source(anyhow!("Expected a single map as a frame: {:?}", frame))
+    })?;
+    for key in &["@id", "@type"] {
+        let values = frame.get(*key).map_or(&[][..], to_ref_array);
+        if let Some(v) = values.iter().find(|v| match v {
+            Value::Object(_) => false,
+            Value::String(s) => !is_absolute_iri_ref(s) || is_blank_node_ident(s),
+            _ => true,
+        }) {
+            return Err(ErrorCode::InvalidFrame.and_source(anyhow!(
+                "Invalid {} value {:?} in a frame",
+                key,
+                v
+            )));
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Adds the framed nodes to the property of the output.
+fn add_frame_output(output: &mut JsonMap<String, Value>, property: &str, framed: Vec<Value>) {
+    if !framed.is_empty() {
+        add_value(output, property, Value::Array(framed), true);
+    }
+}
+
+/// Returns the identifier if the given value is a node reference.
+fn subject_reference_id(v: &Value) -> Option<&str> {
+    match v.as_object() {
+        Some(map) if map.len() == 1 => map.get("@id")?.as_str(),
+        _ => None,
+    }
+}
+
+/// Checks whether the node matches the frame.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#frame-matching>.
+fn filter_subject(
+    nodes: &Graph,
+    subject: &JsonMap<String, Value>,
+    frame: &JsonMap<String, Value>,
+    flags: &Flags,
+) -> Result<bool> {
+    let is_wildcard =
+        |v: Option<&Value>| v.and_then(Value::as_object).is_some_and(JsonMap::is_empty);
+    let mut wildcard = true;
+    let mut matches_some = false;
+    for (key, frame_values) in frame {
+        let frame_values = to_ref_array(frame_values);
+        let node_values = subject.get(key).map_or(&[][..], to_ref_array);
+        let match_this = match key.as_str() {
+            "@id" => {
+                // Step 2.1
+                let match_this = frame_values.is_empty()
+                    || is_wildcard(frame_values.first())
+                    || subject
+                        .get("@id")
+                        .is_some_and(|id| frame_values.contains(id));
+                if !flags.require_all {
+                    return Ok(match_this);
+                }
+                match_this
+            }
+            "@type" => {
+                // Step 2.2
+                wildcard = false;
+                if frame_values.is_empty() {
+                    // Match none.
+                    if !node_values.is_empty() {
+                        return Ok(false);
+                    }
+                    true
+                } else if frame_values.len() == 1 && is_wildcard(frame_values.first()) {
+                    // Match any type.
+                    !node_values.is_empty()
+                } else {
+                    let match_this = frame_values
+                        .iter()
+                        .any(|ty| ty.get("@default").is_some() || node_values.contains(ty));
+                    if !flags.require_all {
+                        return Ok(match_this);
+                    }
+                    match_this
+                }
+            }
+            key if has_form_of_keyword(key) => continue,
+            _ => {
+                // Step 2.3
+                let property_frame = frame_values.first();
+                let has_default = match property_frame {
+                    Some(Value::Object(map)) => map.contains_key("@default"),
+                    Some(v) => {
+                        return Err(ErrorCode::InvalidFrame
+                            .and_source(anyhow!("Expected a map as a property frame: {:?}", v)))
+                    }
+                    None => false,
+                };
+                wildcard = false;
+                if node_values.is_empty() && has_default {
+                    continue;
+                }
+                // Step 2.4
+                if !node_values.is_empty() && frame_values.is_empty() {
+                    return Ok(false);
+                }
+                match property_frame {
+                    None => true,
+                    // Step 2.5
+                    Some(pattern) if is_list_object(pattern) => {
+                        let list_pattern = pattern["@list"].get(0);
+                        let node_list = node_values
+                            .first()
+                            .filter(|v| is_list_object(v))
+                            .map_or(&[][..], |v| to_ref_array(&v["@list"]));
+                        match list_pattern.and_then(Value::as_object) {
+                            Some(lp) if is_value_object(&pattern["@list"][0]) => {
+                                node_list.iter().any(|v| value_match(lp, v))
+                            }
+                            Some(lp) if is_node_object(&pattern["@list"][0]) => {
+                                any_node_match(nodes, lp, node_list, flags)?
+                            }
+                            _ => false,
+                        }
+                    }
+                    Some(pattern) if is_value_object(pattern) => {
+                        let pattern = pattern.as_object().expect("Should never fail: map");
+                        node_values.iter().any(|v| value_match(pattern, v))
+                    }
+                    Some(pattern) if subject_reference_frame(pattern) => {
+                        let pattern = pattern.as_object().expect("Should never fail: map");
+                        any_node_match(nodes, pattern, node_values, flags)?
+                    }
+                    Some(_) => !node_values.is_empty(),
+                }
+            }
+        };
+        // Step 3
+        if !match_this && flags.require_all {
+            return Ok(false);
+        }
+        matches_some |= match_this;
+    }
+
+    Ok(wildcard || matches_some)
+}
+
+/// Checks whether the frame has only `@id` entry.
+fn subject_reference_frame(frame: &Value) -> bool {
+    frame
+        .as_object()
+        .is_some_and(|map| map.len() == 1 && map.contains_key("@id"))
+}
+
+/// Checks if any of the values refers to a node which matches the frame.
+fn any_node_match(
+    nodes: &Graph,
+    frame: &JsonMap<String, Value>,
+    values: &[Value],
+    flags: &Flags,
+) -> Result<bool> {
+    for value in values {
+        let node = match value
+            .get("@id")
+            .and_then(Value::as_str)
+            .and_then(|id| nodes.get(id))
+        {
+            Some(node) => node,
+            None => continue,
+        };
+        if filter_subject(nodes, node, frame, flags)? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Checks whether the value object matches the value pattern.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#value-pattern-matching>.
+fn value_match(pattern: &JsonMap<String, Value>, value: &Value) -> bool {
+    let value = match value.as_object() {
+        Some(v) => v,
+        None => return false,
+    };
+    let candidates = |key: &str| pattern.get(key).map_or(&[][..], to_ref_array);
+    let (values, types, languages) = (
+        candidates("@value"),
+        candidates("@type"),
+        candidates("@language"),
+    );
+    // Step 1
+    if values.is_empty() && types.is_empty() && languages.is_empty() {
+        return true;
+    }
+    // Step 2
+    let matches = |candidates: &[Value], v: Option<&Value>, eq: fn(&Value, &Value) -> bool| match v
+    {
+        None => candidates.is_empty(),
+        Some(v) => {
+            candidates
+                .first()
+                .and_then(Value::as_object)
+                .is_some_and(JsonMap::is_empty)
+                || candidates.iter().any(|c| eq(c, v))
+        }
+    };
+    let language_eq = |a: &Value, b: &Value| match (a.as_str(), b.as_str()) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => false,
+    };
+    matches(values, value.get("@value"), |a, b| a == b)
+        && matches(types, value.get("@type"), |a, b| a == b)
+        && matches(languages, value.get("@language"), language_eq)
+}
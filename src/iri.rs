@@ -1,5 +1,7 @@
 //! IRI-related helpers.
 
+use iri_string::types::{IriReferenceStr, IriStr};
+
 /// IRI category.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum IriCategory {
@@ -59,23 +61,144 @@ pub(crate) fn is_compact_iri(s: &str) -> bool {
 }
 
 /// Checks whether the given string is has the form of an IRI (absolute form).
+///
+/// Note that a compact IRI whose prefix is not defined (such as `foo:bar`) also has the form of
+/// an IRI.
 pub(crate) fn is_absolute_iri_ref(s: &str) -> bool {
-    IriCategory::from(s) == IriCategory::AbsoluteIriRef
+    IriStr::new(s).is_ok()
 }
 
-/// Checks whether the given string is has the form of an IRI (absolute form).
+/// Checks whether the given string is has the form of a blank node identifier.
+pub(crate) fn is_blank_node_ident(s: &str) -> bool {
+    s.starts_with("_:")
+}
+
+/// Checks whether the given string is has the form of an IRI (absolute form) or a blank node
+/// identifier.
 pub(crate) fn is_absolute_ref_or_blank_node_ident(s: &str) -> bool {
-    match IriCategory::from(s) {
-        IriCategory::AbsoluteIriRef | IriCategory::BlankNodeIdentifier => true,
-        _ => false,
-    }
+    is_blank_node_ident(s) || is_absolute_iri_ref(s)
 }
 
 /// Checks is the given ASCII byte is `gen-delims` character.
 pub(crate) fn is_gen_delims_byte(b: u8) -> bool {
-    match b {
-        b':' | b'/' | b'?' | b'#' | b'[' | b']' | b'@' => true,
-        _ => false,
+    matches!(b, b':' | b'/' | b'?' | b'#' | b'[' | b']' | b'@')
+}
+
+/// Components of an IRI reference.
+///
+/// See <https://tools.ietf.org/html/rfc3986#appendix-B>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Components<'a> {
+    /// Scheme.
+    scheme: Option<&'a str>,
+    /// Authority.
+    authority: Option<&'a str>,
+    /// Path.
+    path: &'a str,
+    /// Query.
+    query: Option<&'a str>,
+    /// Fragment.
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Components<'a> {
+    /// Splits the given IRI reference into components.
+    fn new(s: &'a str) -> Self {
+        let (s, fragment) = match s.find('#') {
+            Some(pos) => (&s[..pos], Some(&s[(pos + 1)..])),
+            None => (s, None),
+        };
+        let (s, query) = match s.find('?') {
+            Some(pos) => (&s[..pos], Some(&s[(pos + 1)..])),
+            None => (s, None),
+        };
+        let (scheme, s) = match s.find([':', '/']) {
+            Some(pos) if pos != 0 && s.as_bytes()[pos] == b':' => {
+                (Some(&s[..pos]), &s[(pos + 1)..])
+            }
+            _ => (None, s),
+        };
+        let (authority, path) = match s.strip_prefix("//") {
+            Some(s) => {
+                let pos = s.find('/').unwrap_or(s.len());
+                (Some(&s[..pos]), &s[pos..])
+            }
+            None => (None, s),
+        };
+
+        Self {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+/// Makes the given IRI relative to the base IRI, if possible.
+///
+/// Returns the IRI as is if it cannot be made relative to the base.
+/// It is guaranteed that the result is resolved to the original IRI against the base.
+pub(crate) fn relativize(base: &IriStr, iri: &str) -> String {
+    let base_comps = Components::new(base.as_str());
+    let iri_comps = Components::new(iri);
+    if iri_comps.scheme.is_none()
+        || base_comps.scheme != iri_comps.scheme
+        || base_comps.authority != iri_comps.authority
+    {
+        return iri.to_owned();
+    }
+
+    let mut base_segments = base_comps.path.split('/').collect::<Vec<_>>();
+    let mut iri_segments = iri_comps.path.split('/').collect::<Vec<_>>();
+    // Keep the last segment unless the query or fragment (with the base query) follows.
+    let last = if iri_comps.query.is_some()
+        || (iri_comps.fragment.is_some() && iri_comps.query == base_comps.query)
+    {
+        0
+    } else {
+        1
+    };
+    let num_common = base_segments
+        .iter()
+        .zip(&iri_segments[..(iri_segments.len().saturating_sub(last))])
+        .take_while(|(b, i)| b == i)
+        .count();
+    base_segments.drain(..num_common);
+    iri_segments.drain(..num_common);
+
+    let mut rel = String::new();
+    // The last segment of the base is not a directory.
+    for _ in 1..base_segments.len() {
+        rel.push_str("../");
+    }
+    let path = iri_segments.join("/");
+    let first_segment = path.split('/').next().unwrap_or("");
+    if rel.is_empty() && first_segment.contains(':') {
+        // Prevent the first segment from being parsed as a scheme.
+        rel.push_str("./");
+    }
+    rel.push_str(&path);
+    // The base query is inherited by a same-document reference.
+    let is_same_document =
+        rel.is_empty() && iri_comps.query == base_comps.query && iri_comps.fragment.is_some();
+    if let Some(query) = iri_comps.query.filter(|_| !is_same_document) {
+        rel.push('?');
+        rel.push_str(query);
+    }
+    if let Some(fragment) = iri_comps.fragment {
+        rel.push('#');
+        rel.push_str(fragment);
+    }
+    if rel.is_empty() {
+        rel.push_str("./");
+    }
+
+    // Make sure the relative reference is resolved to the original IRI.
+    match IriReferenceStr::new(&rel) {
+        Ok(rel_ref) if rel_ref.resolve_against(base.to_absolute()).as_str() == iri => rel,
+        _ => iri.to_owned(),
     }
 }
 
@@ -92,4 +215,26 @@ mod tests {
         assert_eq!(to_prefix_and_suffix(":foo:"), Some((":foo", "")));
         assert_eq!(to_prefix_and_suffix(":foo:bar:"), Some((":foo", "bar:")));
     }
+
+    #[test]
+    fn relativize_iri() {
+        let base = IriStr::new("http://example.com/a/b/c?q#f").unwrap();
+        let cases = &[
+            ("http://example.com/a/b/c", "c"),
+            ("http://example.com/a/b/d", "d"),
+            ("http://example.com/a/b/", "./"),
+            ("http://example.com/a/x/y", "../x/y"),
+            ("http://example.com/", "../../"),
+            ("http://example.com/a/b/c?q#g", "#g"),
+            ("http://example.com/a/b/c#g", "c#g"),
+            ("http://example.com/a/b/c?r", "?r"),
+            ("http://example.com/a/b/p:q", "./p:q"),
+            ("http://example.org/a/b/c", "http://example.org/a/b/c"),
+            ("https://example.com/a/b/c", "https://example.com/a/b/c"),
+            ("_:b0", "_:b0"),
+        ];
+        for (iri, expected) in cases {
+            assert_eq!(relativize(base, iri), *expected, "iri={:?}", iri);
+        }
+    }
 }
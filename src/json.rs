@@ -2,8 +2,9 @@
 
 use serde_json::{Map as JsonMap, Value};
 
-pub(crate) use self::nullable::Nullable;
+pub(crate) use self::{canonical::to_canonical_string, nullable::Nullable};
 
+mod canonical;
 mod nullable;
 
 /// Returns a map with single key-value entry.
@@ -23,3 +24,44 @@ pub(crate) fn to_ref_array(v: &Value) -> &[Value] {
         v => std::slice::from_ref(v),
     }
 }
+
+/// Adds the value to the entry of the map.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dfn-add-value>.
+pub(crate) fn add_value(map: &mut JsonMap<String, Value>, key: &str, value: Value, as_array: bool) {
+    // Step 1
+    if as_array {
+        match map.get_mut(key) {
+            Some(Value::Array(_)) => {}
+            Some(original) => {
+                let taken = original.take();
+                *original = Value::Array(vec![taken]);
+            }
+            None => {
+                map.insert(key.to_owned(), Value::Array(Vec::new()));
+            }
+        }
+    }
+    match value {
+        // Step 2
+        Value::Array(values) => {
+            for v in values {
+                add_value(map, key, v, as_array);
+            }
+        }
+        // Step 3
+        value => match map.get_mut(key) {
+            // Step 3.2.2
+            Some(Value::Array(arr)) => arr.push(value),
+            // Step 3.2.1, 3.2.2
+            Some(original) => {
+                let taken = original.take();
+                *original = Value::Array(vec![taken, value]);
+            }
+            // Step 3.1
+            None => {
+                map.insert(key.to_owned(), value);
+            }
+        },
+    }
+}
@@ -0,0 +1,157 @@
+//! JSON canonicalization.
+//!
+//! See <https://tools.ietf.org/html/rfc8785>.
+
+use std::fmt::Write;
+
+use serde_json::{Number, Value};
+
+/// Serializes the given JSON value into the canonical form.
+///
+/// See <https://tools.ietf.org/html/rfc8785>.
+pub(crate) fn to_canonical_string(value: &Value) -> String {
+    let mut buf = String::new();
+    write_canonical(&mut buf, value);
+    buf
+}
+
+/// Writes the given JSON value in the canonical form.
+fn write_canonical(buf: &mut String, value: &Value) {
+    match value {
+        Value::Null => buf.push_str("null"),
+        Value::Bool(v) => buf.push_str(if *v { "true" } else { "false" }),
+        Value::Number(v) => buf.push_str(&number_to_es_string(v)),
+        Value::String(v) => write_string(buf, v),
+        Value::Array(arr) => {
+            buf.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i != 0 {
+                    buf.push(',');
+                }
+                write_canonical(buf, item);
+            }
+            buf.push(']');
+        }
+        Value::Object(map) => {
+            // Keys are sorted by UTF-16 code units.
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(k1, _), (k2, _)| k1.encode_utf16().cmp(k2.encode_utf16()));
+            buf.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i != 0 {
+                    buf.push(',');
+                }
+                write_string(buf, key);
+                buf.push(':');
+                write_canonical(buf, item);
+            }
+            buf.push('}');
+        }
+    }
+}
+
+/// Writes the given string as a JSON string.
+fn write_string(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\u{08}' => buf.push_str("\\b"),
+            '\t' => buf.push_str("\\t"),
+            '\n' => buf.push_str("\\n"),
+            '\u{0C}' => buf.push_str("\\f"),
+            '\r' => buf.push_str("\\r"),
+            c if c < '\u{20}' => {
+                write!(buf, "\\u{:04x}", c as u32).expect("Should never fail: writing to string")
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Converts the number to a string in the same way as ECMAScript `Number.prototype.toString()`.
+///
+/// See <https://www.ecma-international.org/ecma-262/10.0/index.html#sec-tostring-applied-to-the-number-type>.
+fn number_to_es_string(v: &Number) -> String {
+    let v = v
+        .as_f64()
+        .expect("Should never fail: JSON number can be converted to `f64`");
+    if v == 0.0 {
+        return "0".into();
+    }
+    let sign = if v < 0.0 { "-" } else { "" };
+    // Shortest representation which can be round-tripped.
+    let exp_repr = format!("{:e}", v.abs());
+    let (mantissa, exponent) = exp_repr
+        .split_once('e')
+        .expect("Should never fail: `{:e}` format has an exponent");
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent
+        .parse::<i32>()
+        .expect("Should never fail: exponent is an integer")
+        + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..(n as usize)], &digits[(n as usize)..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let exp_sign = if n - 1 < 0 { '-' } else { '+' };
+        if k == 1 {
+            format!("{}e{}{}", digits, exp_sign, (n - 1).abs())
+        } else {
+            format!(
+                "{}.{}e{}{}",
+                &digits[..1],
+                &digits[1..],
+                exp_sign,
+                (n - 1).abs()
+            )
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn canonical_numbers() {
+        let cases = &[
+            (json!(0), "0"),
+            (json!(-0.0), "0"),
+            (json!(1), "1"),
+            (json!(-1.5), "-1.5"),
+            (json!(1.0), "1"),
+            (json!(1e21), "1e+21"),
+            (json!(1e20), "100000000000000000000"),
+            (json!(1.5e-7), "1.5e-7"),
+            (json!(0.000001), "0.000001"),
+            (json!(123.456), "123.456"),
+            (json!(9007199254740993u64), "9007199254740992"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(to_canonical_string(value), *expected, "value={:?}", value);
+        }
+    }
+
+    #[test]
+    fn canonical_structures() {
+        let value = json!({
+            "b": [true, null, "\u{1}\n\"\\"],
+            "a": { "\u{e000}": 1, "\u{10000}": 2 }
+        });
+        assert_eq!(
+            to_canonical_string(&value),
+            "{\"a\":{\"\u{10000}\":2,\"\u{e000}\":1},\"b\":[true,null,\"\\u0001\\n\\\"\\\\\"]}"
+        );
+    }
+}
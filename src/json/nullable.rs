@@ -5,9 +5,10 @@ use serde_json::Value;
 /// Nullable JSON value.
 ///
 /// Usually used in `Option<Nullable<T>>` form.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum Nullable<T> {
     /// Null.
+    #[default]
     Null,
     /// Non-null value.
     Value(T),
@@ -31,18 +32,14 @@ impl<T> Nullable<T> {
     }
 }
 
-impl<T> Default for Nullable<T> {
-    fn default() -> Self {
-        Nullable::Null
-    }
-}
-
 impl<T> From<T> for Nullable<T> {
     fn from(v: T) -> Self {
         Nullable::Value(v)
     }
 }
 
+// `From<Nullable<T>> for Option<T>` cannot be implemented due to the orphan rule.
+#[allow(clippy::from_over_into)]
 impl<T> Into<Option<T>> for Nullable<T> {
     fn into(self) -> Option<T> {
         match self {
@@ -61,9 +58,9 @@ impl<T> From<Option<T>> for Nullable<T> {
     }
 }
 
-impl<T: Into<serde_json::Value>> Into<Value> for Nullable<T> {
-    fn into(self) -> Value {
-        match self {
+impl<T: Into<Value>> From<Nullable<T>> for Value {
+    fn from(v: Nullable<T>) -> Self {
+        match v {
             Nullable::Null => Value::Null,
             Nullable::Value(v) => v.into(),
         }
@@ -10,14 +10,20 @@ pub use iri_string;
 pub use self::{
     context::Context,
     error::{Error, ErrorCode, Result},
-    processor::{Processor, ProcessorOptions},
+    processor::{ProcessingMode, Processor, ProcessorOptions, ProcessorOptionsBuilder},
 };
 
+pub(crate) mod compact;
 pub(crate) mod context;
 pub(crate) mod error;
 pub(crate) mod expand;
+pub mod flatten;
+pub mod frame;
 pub(crate) mod iri;
 pub(crate) mod json;
 pub(crate) mod processor;
+pub mod rdf;
 pub mod remote;
 pub(crate) mod syntax;
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -4,106 +4,21 @@
 
 use std::borrow::Cow;
 
-use iri_string::types::{IriStr, IriString};
+use iri_string::types::IriStr;
+use serde_json::Value;
 
-use crate::{context::Context, json::Nullable, remote::LoadRemoteDocument};
+use crate::{
+    compact,
+    context::Context,
+    error::Result,
+    expand, flatten, frame,
+    rdf::{self, Dataset},
+    remote::LoadRemoteDocument,
+};
 
-/// JSON-LD processor options.
-///
-/// See <https://www.w3.org/TR/2014/REC-json-ld-api-20140116/#the-jsonldoptions-type>.
-#[derive(Debug, Clone, PartialEq)]
-pub struct ProcessorOptions {
-    /// Base IRI (or document IRI).
-    document_iri: IriString,
-}
-
-impl ProcessorOptions {
-    /// Creates a new `ProcessorOptions` from the given base IRI (or document IRI).
-    pub fn with_base(document_iri: impl Into<IriString>) -> Self {
-        Self {
-            document_iri: document_iri.into(),
-        }
-    }
-
-    /// Returns the base IRI set by the processor.
-    pub(crate) fn document_iri(&self) -> &IriStr {
-        self.document_iri.as_ref()
-    }
-
-    /// Checks if the processing mode is `json-ld-1.0`.
-    pub(crate) fn is_processing_mode_1_0(&self) -> bool {
-        // Currently unsupported.
-        false
-    }
+pub use self::options::{ProcessingMode, ProcessorOptions, ProcessorOptionsBuilder};
 
-    /// Checks if the given string is a keyword.
-    ///
-    /// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#syntax-tokens-and-keywords>.
-    pub(crate) fn is_keyword(&self, s: &str) -> bool {
-        /// Keywords in JSON-LD 1.1.
-        ///
-        /// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#syntax-tokens-and-keywords>.
-        const KEYWORDS_1_1: &[&str] = &[
-            "@base",
-            "@container",
-            "@context",
-            "@direction",
-            "@graph",
-            "@id",
-            "@import",
-            "@included",
-            "@index",
-            "@json",
-            "@language",
-            "@list",
-            "@nest",
-            "@none",
-            "@prefix",
-            "@propagate",
-            "@protected",
-            "@reverse",
-            "@set",
-            "@type",
-            "@value",
-            "@version",
-            "@vocab",
-        ];
-        KEYWORDS_1_1.contains(&s)
-    }
-
-    /// Returns the base IRI.
-    ///
-    /// Note that the base can be empty (null) when `{ "@context": { "@base": null } }` is
-    /// specified.
-    pub(crate) fn base<'a>(&'a self, context: &'a Context) -> Option<Cow<'a, IriStr>> {
-        match context.base() {
-            Nullable::Value(context_base) => match context_base.to_iri() {
-                Ok(iri) => Some(Cow::Borrowed(iri)),
-                Err(_) => Some(Cow::Owned(
-                    context_base.resolve_against(self.document_iri().to_absolute()),
-                )),
-            },
-            Nullable::Null => None,
-        }
-    }
-
-    /// Returns the limit of number of remote contexts.
-    ///
-    /// If `Some(n)` is returned, `n` remote contexts is allowed, and one more remote context will
-    /// be rejected.
-    /// `None` means there are no limits.
-    pub(crate) fn allowed_max_remote_context(&self) -> Option<usize> {
-        unimplemented!()
-    }
-
-    /// Creates a processor from the option and the given loader.
-    pub fn build<L: LoadRemoteDocument>(self, loader: L) -> Processor<L> {
-        Processor {
-            options: self,
-            loader,
-        }
-    }
-}
+mod options;
 
 /// JSON-LD processor.
 ///
@@ -126,6 +41,81 @@ impl<L: LoadRemoteDocument> Processor<L> {
     pub fn loader(&self) -> &L {
         &self.loader
     }
+
+    /// Expands the given JSON-LD document.
+    ///
+    /// The result is always an array in expanded document form.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-expand>.
+    pub async fn expand(&self, input: &Value) -> Result<Value> {
+        expand::expand(self, input).await
+    }
+
+    /// Compacts the given JSON-LD document using the given context.
+    ///
+    /// `context` can be a context (a map, an IRI, or an array of them), a map with `@context`
+    /// entry, or `null`.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-compact>.
+    pub async fn compact(&self, input: &Value, context: &Value) -> Result<Value> {
+        compact::compact(self, input, context).await
+    }
+
+    /// Flattens the given JSON-LD document, and compacts it if the context is given.
+    ///
+    /// If `context` is `None`, the result is an array in flattened document form.
+    /// Otherwise, the result is a map with `@graph` entry (or its alias), even if it has only one
+    /// node.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-flatten>.
+    pub async fn flatten(&self, input: &Value, context: Option<&Value>) -> Result<Value> {
+        flatten::flatten(self, input, context).await
+    }
+
+    /// Merges the given JSON-LD documents into a single graph.
+    ///
+    /// All graphs (including named graphs) in all documents are merged into a single graph.
+    /// Blank node identifiers are relabelled so that blank nodes in different documents are not
+    /// merged.
+    /// The result is an array of node objects in the merged graph, ordered by their identifiers.
+    ///
+    /// To merge node maps instead of documents, use [`NodeMap`].
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#merge-node-maps>.
+    ///
+    /// [`NodeMap`]: flatten/struct.NodeMap.html
+    pub async fn merge(&self, inputs: &[Value]) -> Result<Value> {
+        flatten::merge(self, inputs).await
+    }
+
+    /// Frames the given JSON-LD document using the given frame.
+    ///
+    /// `frame` is a JSON-LD document used as a pattern, and its `@context` entry (if exists) is
+    /// used to compact the result.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldprocessor-frame>.
+    pub async fn frame(&self, input: &Value, frame: &Value) -> Result<Value> {
+        frame::frame(self, input, frame).await
+    }
+
+    /// Deserializes the given JSON-LD document to an RDF dataset.
+    ///
+    /// Triples with relative IRIs (or other ill-formed terms) are dropped with a warning logged
+    /// through `log` crate.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-tordf>.
+    pub async fn to_rdf(&self, input: &Value) -> Result<Dataset> {
+        rdf::to_rdf(self, input).await
+    }
+
+    /// Serializes the given RDF dataset as a JSON-LD document.
+    ///
+    /// The result is an array in expanded document form.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-fromrdf>.
+    pub async fn from_rdf(&self, dataset: &Dataset) -> Result<Value> {
+        rdf::from_rdf(self.options(), dataset)
+    }
 }
 
 impl<L: LoadRemoteDocument> Processor<L> {
@@ -0,0 +1,700 @@
+//! JSON-LD processor options.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#the-jsonldoptions-type>.
+
+use std::{borrow::Cow, fmt};
+
+use iri_string::types::{IriStr, IriString};
+use serde_json::Value;
+
+use crate::{
+    context::Context,
+    frame::Embed,
+    json::Nullable,
+    processor::Processor,
+    rdf::RdfDirection,
+    remote::{LoadDocumentOptions, LoadRemoteDocument},
+};
+
+/// Default limit of number of remote contexts.
+const DEFAULT_MAX_REMOTE_CONTEXTS: usize = 10;
+
+/// Processing mode.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-processingmode>.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessingMode {
+    /// `json-ld-1.0`.
+    ///
+    /// Features introduced in JSON-LD 1.1 are rejected or ignored.
+    JsonLd10,
+    /// `json-ld-1.1`.
+    ///
+    /// This is the default.
+    #[default]
+    JsonLd11,
+}
+
+impl ProcessingMode {
+    /// Returns the processing mode string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessingMode::JsonLd10 => "json-ld-1.0",
+            ProcessingMode::JsonLd11 => "json-ld-1.1",
+        }
+    }
+}
+
+impl fmt::Display for ProcessingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// JSON-LD processor options.
+///
+/// Options can be set by setters, or by [`ProcessorOptionsBuilder`].
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#the-jsonldoptions-type>.
+///
+/// [`ProcessorOptionsBuilder`]: struct.ProcessorOptionsBuilder.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessorOptions {
+    /// Document IRI.
+    document_iri: IriString,
+    /// `base` option, which overrides the document IRI.
+    base: Option<IriString>,
+    /// `compactArrays` flag.
+    compact_arrays: bool,
+    /// `compactToRelative` flag.
+    compact_to_relative: bool,
+    /// `expandContext` option.
+    expand_context: Option<Value>,
+    /// `extractAllScripts` flag.
+    extract_all_scripts: bool,
+    /// `frameExpansion` flag.
+    frame_expansion: bool,
+    /// `ordered` flag.
+    ordered: bool,
+    /// Processing mode.
+    processing_mode: ProcessingMode,
+    /// Limit of number of remote contexts.
+    max_remote_contexts: Option<usize>,
+    /// `produceGeneralizedRdf` flag.
+    produce_generalized_rdf: bool,
+    /// `useNativeTypes` flag.
+    use_native_types: bool,
+    /// `useRdfType` flag.
+    use_rdf_type: bool,
+    /// `rdfDirection` option.
+    rdf_direction: Option<RdfDirection>,
+    /// `embed` option.
+    embed: Embed,
+    /// `explicit` flag.
+    explicit: bool,
+    /// `omitDefault` flag.
+    omit_default: bool,
+    /// `omitGraph` flag.
+    omit_graph: Option<bool>,
+    /// `requireAll` flag.
+    require_all: bool,
+}
+
+impl ProcessorOptions {
+    /// Creates a new `ProcessorOptions` from the given base IRI (or document IRI).
+    pub fn with_base(document_iri: impl Into<IriString>) -> Self {
+        Self {
+            document_iri: document_iri.into(),
+            base: None,
+            compact_arrays: true,
+            compact_to_relative: true,
+            expand_context: None,
+            extract_all_scripts: false,
+            frame_expansion: false,
+            ordered: false,
+            processing_mode: ProcessingMode::JsonLd11,
+            max_remote_contexts: Some(DEFAULT_MAX_REMOTE_CONTEXTS),
+            produce_generalized_rdf: false,
+            use_native_types: false,
+            use_rdf_type: false,
+            rdf_direction: None,
+            embed: Embed::Once,
+            explicit: false,
+            omit_default: false,
+            omit_graph: None,
+            require_all: false,
+        }
+    }
+
+    /// Creates a new builder with the given base IRI (or document IRI).
+    pub fn builder(document_iri: impl Into<IriString>) -> ProcessorOptionsBuilder {
+        ProcessorOptionsBuilder {
+            options: Self::with_base(document_iri),
+        }
+    }
+
+    /// Sets the `base` option.
+    ///
+    /// If set to `Some(_)`, the given IRI is used as the base IRI instead of the document IRI.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-base>.
+    pub fn set_base(&mut self, base: Option<IriString>) {
+        self.base = base;
+    }
+
+    /// Sets the `compactArrays` flag.
+    ///
+    /// If set to `false`, arrays with a single element are not replaced with the element in the
+    /// compaction algorithm.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-compactarrays>.
+    pub fn set_compact_arrays(&mut self, compact_arrays: bool) {
+        self.compact_arrays = compact_arrays;
+    }
+
+    /// Sets the `compactToRelative` flag.
+    ///
+    /// If set to `false`, IRIs are not compacted to relative IRIs against the base IRI.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-compacttorelative>.
+    pub fn set_compact_to_relative(&mut self, compact_to_relative: bool) {
+        self.compact_to_relative = compact_to_relative;
+    }
+
+    /// Sets the `expandContext` option.
+    ///
+    /// If set to `Some(_)`, the context is applied to the initial active context before expanding
+    /// documents.
+    /// The value can be a context (a map, an IRI, or an array of them), or a map with `@context`
+    /// entry.
+    /// IRIs are loaded by the loader of the processor.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-expandcontext>.
+    pub fn set_expand_context(&mut self, expand_context: Option<Value>) {
+        self.expand_context = expand_context;
+    }
+
+    /// Sets the `extractAllScripts` flag.
+    ///
+    /// If set to `true`, all JSON-LD script elements are extracted from an HTML document, unless a
+    /// specific fragment identifier is targeted.
+    ///
+    /// Note that processors take already loaded documents as input and remote contexts are always
+    /// loaded without this flag, so this flag has no effect on the processor itself.
+    /// Pass [`ProcessorOptions::load_document_options`] to the loader when loading the input
+    /// document to honor it.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-extractallscripts>.
+    ///
+    /// [`ProcessorOptions::load_document_options`]: #method.load_document_options
+    pub fn set_extract_all_scripts(&mut self, extract_all_scripts: bool) {
+        self.extract_all_scripts = extract_all_scripts;
+    }
+
+    /// Sets the `frameExpansion` flag.
+    ///
+    /// If set to `true`, [`Processor::expand`] expands the input as a frame.
+    /// Note that [`Processor::frame`] always expands frames in this mode regardless of this flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-frameexpansion>.
+    ///
+    /// [`Processor::expand`]: struct.Processor.html#method.expand
+    /// [`Processor::frame`]: struct.Processor.html#method.frame
+    pub fn set_frame_expansion(&mut self, frame_expansion: bool) {
+        self.frame_expansion = frame_expansion;
+    }
+
+    /// Sets the `ordered` flag.
+    ///
+    /// Note that this crate always processes map entries in lexicographical order of their keys,
+    /// so the output is ordered regardless of this flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-ordered>.
+    pub fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
+
+    /// Sets the processing mode.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-processingmode>.
+    pub fn set_processing_mode(&mut self, processing_mode: ProcessingMode) {
+        self.processing_mode = processing_mode;
+    }
+
+    /// Sets the limit of number of remote contexts.
+    ///
+    /// If `Some(n)` is set, `n` remote contexts are allowed to be loaded recursively, and one more
+    /// remote context will be rejected with [`ErrorCode::ContextOverflow`].
+    /// `None` means there are no limits, and it is not recommended for untrusted documents.
+    /// The default is `Some(10)`.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonlderrorcode-context-overflow>.
+    ///
+    /// [`ErrorCode::ContextOverflow`]: enum.ErrorCode.html#variant.ContextOverflow
+    pub fn set_max_remote_contexts(&mut self, max_remote_contexts: Option<usize>) {
+        self.max_remote_contexts = max_remote_contexts;
+    }
+
+    /// Sets the `produceGeneralizedRdf` flag.
+    ///
+    /// If set to `true`, blank nodes can be used as predicates in RDF output.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-producegeneralizedrdf>.
+    pub fn set_produce_generalized_rdf(&mut self, produce_generalized_rdf: bool) {
+        self.produce_generalized_rdf = produce_generalized_rdf;
+    }
+
+    /// Sets the `useNativeTypes` flag.
+    ///
+    /// If set to `true`, `xsd:boolean`, `xsd:integer`, and `xsd:double` literals in RDF are
+    /// converted to JSON booleans and numbers.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-usenativetypes>.
+    pub fn set_use_native_types(&mut self, use_native_types: bool) {
+        self.use_native_types = use_native_types;
+    }
+
+    /// Sets the `useRdfType` flag.
+    ///
+    /// If set to `true`, `rdf:type` predicates in RDF are kept as is instead of being converted
+    /// to `@type`.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-userdftype>.
+    pub fn set_use_rdf_type(&mut self, use_rdf_type: bool) {
+        self.use_rdf_type = use_rdf_type;
+    }
+
+    /// Sets the `rdfDirection` option.
+    ///
+    /// If set to `None`, base direction of strings is not encoded in RDF.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-rdfdirection>.
+    pub fn set_rdf_direction(&mut self, rdf_direction: Option<RdfDirection>) {
+        self.rdf_direction = rdf_direction;
+    }
+
+    /// Sets the default of `@embed` flag used by framing.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-embed>.
+    pub fn set_embed(&mut self, embed: Embed) {
+        self.embed = embed;
+    }
+
+    /// Sets the default of `@explicit` flag used by framing.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-explicit>.
+    pub fn set_explicit(&mut self, explicit: bool) {
+        self.explicit = explicit;
+    }
+
+    /// Sets the default of `@omitDefault` flag used by framing.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-omitdefault>.
+    pub fn set_omit_default(&mut self, omit_default: bool) {
+        self.omit_default = omit_default;
+    }
+
+    /// Sets the `omitGraph` flag used by framing.
+    ///
+    /// If set to `None`, `@graph` is omitted when the framed result has only one node, unless the
+    /// processing mode is `json-ld-1.0`.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-omitgraph>.
+    pub fn set_omit_graph(&mut self, omit_graph: Option<bool>) {
+        self.omit_graph = omit_graph;
+    }
+
+    /// Sets the default of `@requireAll` flag used by framing.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-requireall>.
+    pub fn set_require_all(&mut self, require_all: bool) {
+        self.require_all = require_all;
+    }
+
+    /// Returns the base IRI set by the processor.
+    ///
+    /// This is the `base` option if set, or the document IRI.
+    pub(crate) fn document_iri(&self) -> &IriStr {
+        self.base.as_ref().unwrap_or(&self.document_iri).as_ref()
+    }
+
+    /// Checks if the processing mode is `json-ld-1.0`.
+    pub(crate) fn is_processing_mode_1_0(&self) -> bool {
+        self.processing_mode == ProcessingMode::JsonLd10
+    }
+
+    /// Returns the `compactArrays` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-compactarrays>.
+    pub(crate) fn compact_arrays(&self) -> bool {
+        self.compact_arrays
+    }
+
+    /// Returns the `compactToRelative` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-compacttorelative>.
+    pub(crate) fn compact_to_relative(&self) -> bool {
+        self.compact_to_relative
+    }
+
+    /// Returns the `expandContext` option.
+    ///
+    /// If the option is a map with `@context` entry, the value of the entry is returned.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-expandcontext>.
+    pub(crate) fn expand_context(&self) -> Option<&Value> {
+        match self.expand_context.as_ref()? {
+            Value::Object(map) if map.contains_key("@context") => map.get("@context"),
+            v => Some(v),
+        }
+    }
+
+    /// Returns the options to load the input document with.
+    ///
+    /// The `extractAllScripts` flag is taken from the options.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-extractallscripts>.
+    pub fn load_document_options(&self) -> LoadDocumentOptions {
+        let mut options = LoadDocumentOptions::new();
+        options.set_extract_all_scripts(self.extract_all_scripts);
+        options
+    }
+
+    /// Returns the `frameExpansion` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-frameexpansion>.
+    pub(crate) fn frame_expansion(&self) -> bool {
+        self.frame_expansion
+    }
+
+    /// Returns the `produceGeneralizedRdf` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-producegeneralizedrdf>.
+    pub(crate) fn produce_generalized_rdf(&self) -> bool {
+        self.produce_generalized_rdf
+    }
+
+    /// Returns the `useNativeTypes` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-usenativetypes>.
+    pub(crate) fn use_native_types(&self) -> bool {
+        self.use_native_types
+    }
+
+    /// Returns the `useRdfType` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-userdftype>.
+    pub(crate) fn use_rdf_type(&self) -> bool {
+        self.use_rdf_type
+    }
+
+    /// Returns the `rdfDirection` option.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-rdfdirection>.
+    pub(crate) fn rdf_direction(&self) -> Option<RdfDirection> {
+        self.rdf_direction
+    }
+
+    /// Returns the `embed` option.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-embed>.
+    pub(crate) fn embed(&self) -> Embed {
+        self.embed
+    }
+
+    /// Returns the `explicit` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-explicit>.
+    pub(crate) fn explicit(&self) -> bool {
+        self.explicit
+    }
+
+    /// Returns the `omitDefault` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-omitdefault>.
+    pub(crate) fn omit_default(&self) -> bool {
+        self.omit_default
+    }
+
+    /// Returns the `omitGraph` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-omitgraph>.
+    pub(crate) fn omit_graph(&self) -> bool {
+        self.omit_graph
+            .unwrap_or_else(|| !self.is_processing_mode_1_0())
+    }
+
+    /// Returns the `requireAll` flag.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#dom-jsonldoptions-requireall>.
+    pub(crate) fn require_all(&self) -> bool {
+        self.require_all
+    }
+
+    /// Checks if the given string is a keyword.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#syntax-tokens-and-keywords>.
+    pub(crate) fn is_keyword(&self, s: &str) -> bool {
+        /// Keywords in JSON-LD 1.0.
+        ///
+        /// See <https://www.w3.org/TR/2014/REC-json-ld-20140116/#syntax-tokens-and-keywords>.
+        const KEYWORDS_1_0: &[&str] = &[
+            "@base",
+            "@container",
+            "@context",
+            "@graph",
+            "@id",
+            "@index",
+            "@language",
+            "@list",
+            "@reverse",
+            "@set",
+            "@type",
+            "@value",
+            "@vocab",
+        ];
+        /// Keywords in JSON-LD 1.1.
+        ///
+        /// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#syntax-tokens-and-keywords>.
+        const KEYWORDS_1_1: &[&str] = &[
+            "@base",
+            "@container",
+            "@context",
+            "@direction",
+            "@graph",
+            "@id",
+            "@import",
+            "@included",
+            "@index",
+            "@json",
+            "@language",
+            "@list",
+            "@nest",
+            "@none",
+            "@prefix",
+            "@propagate",
+            "@protected",
+            "@reverse",
+            "@set",
+            "@type",
+            "@value",
+            "@version",
+            "@vocab",
+        ];
+        /// Keywords introduced by JSON-LD 1.1 Framing.
+        ///
+        /// See <https://www.w3.org/TR/2019/WD-json-ld11-framing-20191112/#syntax-tokens-and-keywords>.
+        const FRAMING_KEYWORDS: &[&str] = &[
+            "@default",
+            "@embed",
+            "@explicit",
+            "@omitDefault",
+            "@requireAll",
+        ];
+        let keywords = if self.is_processing_mode_1_0() {
+            KEYWORDS_1_0
+        } else {
+            KEYWORDS_1_1
+        };
+        keywords.contains(&s) || FRAMING_KEYWORDS.contains(&s)
+    }
+
+    /// Returns the base IRI.
+    ///
+    /// Note that the base can be empty (null) when `{ "@context": { "@base": null } }` is
+    /// specified.
+    pub(crate) fn base<'a>(&'a self, context: &'a Context) -> Option<Cow<'a, IriStr>> {
+        match context.base() {
+            Nullable::Value(context_base) => match context_base.to_iri() {
+                Ok(iri) => Some(Cow::Borrowed(iri)),
+                Err(_) => Some(Cow::Owned(
+                    context_base.resolve_against(self.document_iri().to_absolute()),
+                )),
+            },
+            Nullable::Null => None,
+        }
+    }
+
+    /// Returns the limit of number of remote contexts.
+    ///
+    /// If `Some(n)` is returned, `n` remote contexts is allowed, and one more remote context will
+    /// be rejected.
+    /// `None` means there are no limits.
+    pub(crate) fn allowed_max_remote_context(&self) -> Option<usize> {
+        self.max_remote_contexts
+    }
+
+    /// Creates a processor from the option and the given loader.
+    pub fn build<L: LoadRemoteDocument>(self, loader: L) -> Processor<L> {
+        Processor {
+            options: self,
+            loader,
+        }
+    }
+}
+
+/// Builder of [`ProcessorOptions`].
+///
+/// [`ProcessorOptions`]: struct.ProcessorOptions.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessorOptionsBuilder {
+    /// Options.
+    options: ProcessorOptions,
+}
+
+impl ProcessorOptionsBuilder {
+    /// Sets the `base` option.
+    ///
+    /// See [`ProcessorOptions::set_base`](struct.ProcessorOptions.html#method.set_base).
+    pub fn base(mut self, base: impl Into<IriString>) -> Self {
+        self.options.set_base(Some(base.into()));
+        self
+    }
+
+    /// Sets the `compactArrays` flag.
+    ///
+    /// See [`ProcessorOptions::set_compact_arrays`](struct.ProcessorOptions.html#method.set_compact_arrays).
+    pub fn compact_arrays(mut self, compact_arrays: bool) -> Self {
+        self.options.set_compact_arrays(compact_arrays);
+        self
+    }
+
+    /// Sets the `compactToRelative` flag.
+    ///
+    /// See [`ProcessorOptions::set_compact_to_relative`](struct.ProcessorOptions.html#method.set_compact_to_relative).
+    pub fn compact_to_relative(mut self, compact_to_relative: bool) -> Self {
+        self.options.set_compact_to_relative(compact_to_relative);
+        self
+    }
+
+    /// Sets the `expandContext` option.
+    ///
+    /// See [`ProcessorOptions::set_expand_context`](struct.ProcessorOptions.html#method.set_expand_context).
+    pub fn expand_context(mut self, expand_context: Value) -> Self {
+        self.options.set_expand_context(Some(expand_context));
+        self
+    }
+
+    /// Sets the `extractAllScripts` flag.
+    ///
+    /// See [`ProcessorOptions::set_extract_all_scripts`](struct.ProcessorOptions.html#method.set_extract_all_scripts).
+    pub fn extract_all_scripts(mut self, extract_all_scripts: bool) -> Self {
+        self.options.set_extract_all_scripts(extract_all_scripts);
+        self
+    }
+
+    /// Sets the `frameExpansion` flag.
+    ///
+    /// See [`ProcessorOptions::set_frame_expansion`](struct.ProcessorOptions.html#method.set_frame_expansion).
+    pub fn frame_expansion(mut self, frame_expansion: bool) -> Self {
+        self.options.set_frame_expansion(frame_expansion);
+        self
+    }
+
+    /// Sets the `ordered` flag.
+    ///
+    /// See [`ProcessorOptions::set_ordered`](struct.ProcessorOptions.html#method.set_ordered).
+    pub fn ordered(mut self, ordered: bool) -> Self {
+        self.options.set_ordered(ordered);
+        self
+    }
+
+    /// Sets the processing mode.
+    ///
+    /// See [`ProcessorOptions::set_processing_mode`](struct.ProcessorOptions.html#method.set_processing_mode).
+    pub fn processing_mode(mut self, processing_mode: ProcessingMode) -> Self {
+        self.options.set_processing_mode(processing_mode);
+        self
+    }
+
+    /// Sets the limit of number of remote contexts.
+    ///
+    /// See [`ProcessorOptions::set_max_remote_contexts`](struct.ProcessorOptions.html#method.set_max_remote_contexts).
+    pub fn max_remote_contexts(mut self, max_remote_contexts: Option<usize>) -> Self {
+        self.options.set_max_remote_contexts(max_remote_contexts);
+        self
+    }
+
+    /// Sets the `produceGeneralizedRdf` flag.
+    ///
+    /// See [`ProcessorOptions::set_produce_generalized_rdf`](struct.ProcessorOptions.html#method.set_produce_generalized_rdf).
+    pub fn produce_generalized_rdf(mut self, produce_generalized_rdf: bool) -> Self {
+        self.options
+            .set_produce_generalized_rdf(produce_generalized_rdf);
+        self
+    }
+
+    /// Sets the `useNativeTypes` flag.
+    ///
+    /// See [`ProcessorOptions::set_use_native_types`](struct.ProcessorOptions.html#method.set_use_native_types).
+    pub fn use_native_types(mut self, use_native_types: bool) -> Self {
+        self.options.set_use_native_types(use_native_types);
+        self
+    }
+
+    /// Sets the `useRdfType` flag.
+    ///
+    /// See [`ProcessorOptions::set_use_rdf_type`](struct.ProcessorOptions.html#method.set_use_rdf_type).
+    pub fn use_rdf_type(mut self, use_rdf_type: bool) -> Self {
+        self.options.set_use_rdf_type(use_rdf_type);
+        self
+    }
+
+    /// Sets the `rdfDirection` option.
+    ///
+    /// See [`ProcessorOptions::set_rdf_direction`](struct.ProcessorOptions.html#method.set_rdf_direction).
+    pub fn rdf_direction(mut self, rdf_direction: Option<RdfDirection>) -> Self {
+        self.options.set_rdf_direction(rdf_direction);
+        self
+    }
+
+    /// Sets the default of `@embed` flag used by framing.
+    ///
+    /// See [`ProcessorOptions::set_embed`](struct.ProcessorOptions.html#method.set_embed).
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.options.set_embed(embed);
+        self
+    }
+
+    /// Sets the default of `@explicit` flag used by framing.
+    ///
+    /// See [`ProcessorOptions::set_explicit`](struct.ProcessorOptions.html#method.set_explicit).
+    pub fn explicit(mut self, explicit: bool) -> Self {
+        self.options.set_explicit(explicit);
+        self
+    }
+
+    /// Sets the default of `@omitDefault` flag used by framing.
+    ///
+    /// See [`ProcessorOptions::set_omit_default`](struct.ProcessorOptions.html#method.set_omit_default).
+    pub fn omit_default(mut self, omit_default: bool) -> Self {
+        self.options.set_omit_default(omit_default);
+        self
+    }
+
+    /// Sets the `omitGraph` flag used by framing.
+    ///
+    /// See [`ProcessorOptions::set_omit_graph`](struct.ProcessorOptions.html#method.set_omit_graph).
+    pub fn omit_graph(mut self, omit_graph: Option<bool>) -> Self {
+        self.options.set_omit_graph(omit_graph);
+        self
+    }
+
+    /// Sets the default of `@requireAll` flag used by framing.
+    ///
+    /// See [`ProcessorOptions::set_require_all`](struct.ProcessorOptions.html#method.set_require_all).
+    pub fn require_all(mut self, require_all: bool) -> Self {
+        self.options.set_require_all(require_all);
+        self
+    }
+
+    /// Creates the options.
+    pub fn build(self) -> ProcessorOptions {
+        self.options
+    }
+
+    /// Creates a processor from the options and the given loader.
+    pub fn processor<L: LoadRemoteDocument>(self, loader: L) -> Processor<L> {
+        self.options.build(loader)
+    }
+}
@@ -0,0 +1,18 @@
+//! RDF dataset and conversion between JSON-LD and RDF.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#rdf-serialization-deserialization-algorithms>.
+
+pub use self::{
+    dataset::{Dataset, Literal, Quad, Term},
+    direction::{RdfDirection, RdfDirectionParseError},
+};
+
+pub(crate) use self::{from_rdf::from_rdf, to_rdf::to_rdf};
+
+pub mod canon;
+mod dataset;
+mod direction;
+mod from_rdf;
+pub mod nquads;
+mod to_rdf;
+pub(crate) mod vocab;
@@ -0,0 +1,629 @@
+//! RDF dataset canonicalization.
+//!
+//! This module implements RDFC-1.0 (formerly known as URDNA2015), which issues canonical blank
+//! node identifiers deterministically, so that isomorphic datasets are serialized to the same
+//! canonical N-Quads document.
+//!
+//! Some datasets (so-called "poison graphs") make the algorithm take exponential time.
+//! To defend against them, [`Canonicalizer`] limits the amount of work by default.
+//!
+//! See <https://www.w3.org/TR/rdf-canon/>.
+//!
+//! [`Canonicalizer`]: struct.Canonicalizer.html
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error as ThisError;
+
+use crate::rdf::{nquads, Dataset, Quad, Term};
+
+/// Prefix of canonical blank node identifiers.
+const CANONICAL_PREFIX: &str = "c14n";
+
+/// Prefix of temporary blank node identifiers.
+const TEMPORARY_PREFIX: &str = "b";
+
+/// Default work limit.
+const DEFAULT_WORK_LIMIT: usize = 1_000_000;
+
+/// Error returned when canonicalization exceeds the work limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ThisError)]
+#[error("canonicalization work limit ({limit}) exceeded")]
+pub struct WorkLimitExceeded {
+    /// Work limit.
+    limit: usize,
+}
+
+impl WorkLimitExceeded {
+    /// Returns the work limit which was exceeded.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// Canonicalizes the given dataset with the default options.
+///
+/// See <https://www.w3.org/TR/rdf-canon/#canon-algorithm>.
+pub fn canonicalize(dataset: &Dataset) -> Result<CanonicalDataset, WorkLimitExceeded> {
+    Canonicalizer::new().canonicalize(dataset)
+}
+
+/// RDF dataset canonicalizer.
+///
+/// See <https://www.w3.org/TR/rdf-canon/#canon-algorithm>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Canonicalizer {
+    /// Work limit.
+    work_limit: Option<usize>,
+}
+
+impl Canonicalizer {
+    /// Creates a new canonicalizer with the default work limit.
+    pub fn new() -> Self {
+        Self {
+            work_limit: Some(DEFAULT_WORK_LIMIT),
+        }
+    }
+
+    /// Sets the work limit.
+    ///
+    /// Each invocation of the hash N-degree quads algorithm and each permutation examined by it
+    /// counts as one unit of work.
+    /// If the limit is exceeded, canonicalization fails with [`WorkLimitExceeded`].
+    /// `None` means there are no limits, and it is not recommended for untrusted input.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#dataset-poisoning>.
+    ///
+    /// [`WorkLimitExceeded`]: struct.WorkLimitExceeded.html
+    pub fn set_work_limit(&mut self, work_limit: Option<usize>) {
+        self.work_limit = work_limit;
+    }
+
+    /// Canonicalizes the given dataset.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#canon-algorithm>.
+    pub fn canonicalize(&self, dataset: &Dataset) -> Result<CanonicalDataset, WorkLimitExceeded> {
+        State::new(self.work_limit, dataset).run()
+    }
+}
+
+impl Default for Canonicalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Canonicalized dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalDataset {
+    /// Dataset with canonical blank node labels, sorted in canonical N-Quads order.
+    dataset: Dataset,
+    /// Map from blank node labels in the input to canonical labels.
+    issued: BTreeMap<String, String>,
+}
+
+impl CanonicalDataset {
+    /// Returns the dataset with canonical blank node labels.
+    ///
+    /// Quads are sorted in code point order of their canonical N-Quads forms.
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    /// Returns the dataset with canonical blank node labels.
+    pub fn into_dataset(self) -> Dataset {
+        self.dataset
+    }
+
+    /// Returns the map from blank node labels in the input to canonical labels.
+    ///
+    /// Labels do not have the leading `_:`.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#dfn-issued-identifiers-map>.
+    pub fn issued_identifiers(&self) -> &BTreeMap<String, String> {
+        &self.issued
+    }
+
+    /// Serializes the dataset into a canonical N-Quads document.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#canonical-quads>.
+    pub fn to_nquads(&self) -> String {
+        nquads::serialize(&self.dataset)
+    }
+}
+
+/// Identifier issuer.
+///
+/// See <https://www.w3.org/TR/rdf-canon/#issue-identifier-algorithm>.
+#[derive(Debug, Clone)]
+struct IdIssuer {
+    /// Prefix of identifiers to be issued.
+    prefix: &'static str,
+    /// Identifier map (from old identifiers to issued identifiers).
+    issued: HashMap<String, String>,
+    /// Old identifiers in the order of issuance.
+    order: Vec<String>,
+}
+
+impl IdIssuer {
+    /// Creates a new issuer with the given prefix.
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            issued: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Returns the issued identifier for the given old identifier, if exists.
+    fn get(&self, identifier: &str) -> Option<&str> {
+        self.issued.get(identifier).map(String::as_str)
+    }
+
+    /// Runs issue identifier algorithm.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#issue-identifier-algorithm>.
+    fn issue(&mut self, identifier: &str) -> &str {
+        // Step 1
+        if !self.issued.contains_key(identifier) {
+            // Step 2-4
+            let issued = format!("{}{}", self.prefix, self.order.len());
+            self.issued.insert(identifier.to_owned(), issued);
+            self.order.push(identifier.to_owned());
+        }
+        // Step 5
+        &self.issued[identifier]
+    }
+}
+
+/// Canonicalization state.
+///
+/// See <https://www.w3.org/TR/rdf-canon/#canon-state>.
+struct State<'a> {
+    /// Input dataset.
+    dataset: &'a Dataset,
+    /// Blank node to quads map.
+    blank_node_to_quads: HashMap<&'a str, Vec<&'a Quad>>,
+    /// Canonical issuer.
+    canonical_issuer: IdIssuer,
+    /// Work limit.
+    work_limit: Option<usize>,
+    /// Amount of work done.
+    work: usize,
+}
+
+impl<'a> State<'a> {
+    /// Creates a new state.
+    fn new(work_limit: Option<usize>, dataset: &'a Dataset) -> Self {
+        Self {
+            dataset,
+            blank_node_to_quads: HashMap::new(),
+            canonical_issuer: IdIssuer::new(CANONICAL_PREFIX),
+            work_limit,
+            work: 0,
+        }
+    }
+
+    /// Runs the canonicalization algorithm.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#canon-algo-algo>.
+    fn run(mut self) -> Result<CanonicalDataset, WorkLimitExceeded> {
+        // Step 2
+        for quad in self.dataset {
+            for label in blank_node_labels(quad) {
+                let quads = self.blank_node_to_quads.entry(label).or_default();
+                if quads.last() != Some(&quad) {
+                    quads.push(quad);
+                }
+            }
+        }
+        // Step 3
+        let mut hash_to_blank_nodes = BTreeMap::<String, Vec<&str>>::new();
+        let mut labels = self.blank_node_to_quads.keys().copied().collect::<Vec<_>>();
+        labels.sort_unstable();
+        for label in labels {
+            hash_to_blank_nodes
+                .entry(self.hash_first_degree_quads(label))
+                .or_default()
+                .push(label);
+        }
+        // Step 4
+        let mut non_unique = Vec::new();
+        for (_, labels) in hash_to_blank_nodes {
+            if labels.len() > 1 {
+                non_unique.push(labels);
+                continue;
+            }
+            self.canonical_issuer.issue(labels[0]);
+        }
+        // Step 5
+        for labels in non_unique {
+            // Step 5.1
+            let mut hash_path_list = Vec::new();
+            // Step 5.2
+            for label in labels {
+                // Step 5.2.1
+                if self.canonical_issuer.get(label).is_some() {
+                    continue;
+                }
+                // Step 5.2.2-5.2.3
+                let mut temporary_issuer = IdIssuer::new(TEMPORARY_PREFIX);
+                temporary_issuer.issue(label);
+                // Step 5.2.4
+                hash_path_list.push(self.hash_n_degree_quads(label, temporary_issuer)?);
+            }
+            // Step 5.3
+            hash_path_list.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+            for (_, issuer) in hash_path_list {
+                for existing in &issuer.order {
+                    self.canonical_issuer.issue(existing);
+                }
+            }
+        }
+        // Step 6
+        let mut quads = self
+            .dataset
+            .iter()
+            .map(|quad| {
+                let quad = relabel(quad, |label| {
+                    self.canonical_issuer
+                        .get(label)
+                        .expect("Should never fail: all blank nodes have canonical identifiers")
+                        .to_owned()
+                });
+                (quad.to_string(), quad)
+            })
+            .collect::<Vec<_>>();
+        quads.sort_unstable_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        let issued = self.canonical_issuer.issued.into_iter().collect();
+
+        Ok(CanonicalDataset {
+            dataset: quads.into_iter().map(|(_, quad)| quad).collect(),
+            issued,
+        })
+    }
+
+    /// Consumes one unit of work.
+    fn consume_work(&mut self) -> Result<(), WorkLimitExceeded> {
+        self.work += 1;
+        match self.work_limit {
+            Some(limit) if self.work > limit => Err(WorkLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs hash first degree quads algorithm.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#hash-1d-quads>.
+    fn hash_first_degree_quads(&self, reference: &str) -> String {
+        // Step 1-3
+        let mut nquads = self.blank_node_to_quads[reference]
+            .iter()
+            .map(|quad| {
+                // Step 3.1
+                let quad = relabel(quad, |label| {
+                    if label == reference { "a" } else { "z" }.to_owned()
+                });
+                format!("{}\n", quad)
+            })
+            .collect::<Vec<_>>();
+        // Step 4
+        nquads.sort_unstable();
+        // Step 5
+        hash(&nquads.concat())
+    }
+
+    /// Runs hash related blank node algorithm.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#hash-related-blank-node>.
+    fn hash_related_blank_node(
+        &self,
+        related: &str,
+        quad: &Quad,
+        issuer: &IdIssuer,
+        position: char,
+    ) -> String {
+        // Step 1
+        let identifier = match self
+            .canonical_issuer
+            .get(related)
+            .or_else(|| issuer.get(related))
+        {
+            Some(issued) => format!("_:{}", issued),
+            None => self.hash_first_degree_quads(related),
+        };
+        // Step 2
+        let mut input = position.to_string();
+        // Step 3
+        if position != 'g' {
+            write!(input, "{}", quad.predicate()).expect("Should never fail: writing to string");
+        }
+        // Step 4
+        input.push_str(&identifier);
+        // Step 5
+        hash(&input)
+    }
+
+    /// Runs hash N-degree quads algorithm.
+    ///
+    /// Returns the hash and the updated issuer.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#hash-nd-quads>.
+    fn hash_n_degree_quads(
+        &mut self,
+        identifier: &str,
+        mut issuer: IdIssuer,
+    ) -> Result<(String, IdIssuer), WorkLimitExceeded> {
+        self.consume_work()?;
+        // Step 1
+        let mut hash_to_related = BTreeMap::<String, Vec<&'a str>>::new();
+        // Step 2
+        let quads = self.blank_node_to_quads[identifier].clone();
+        // Step 3
+        for quad in quads {
+            let components = IntoIterator::into_iter([(quad.subject(), 's'), (quad.object(), 'o')])
+                .chain(quad.graph_name().map(|name| (name, 'g')));
+            for (component, position) in components {
+                let related = match component.as_blank_node() {
+                    Some(related) if related != identifier => related,
+                    _ => continue,
+                };
+                // Step 3.1.1-3.1.2
+                let hash = self.hash_related_blank_node(related, quad, &issuer, position);
+                let related_list = hash_to_related.entry(hash).or_default();
+                if !related_list.contains(&related) {
+                    related_list.push(related);
+                }
+            }
+        }
+        // Step 4
+        let mut data_to_hash = String::new();
+        // Step 5
+        for (related_hash, mut blank_node_list) in hash_to_related {
+            // Step 5.1
+            data_to_hash.push_str(&related_hash);
+            // Step 5.2-5.3
+            let mut chosen: Option<(String, IdIssuer)> = None;
+            // Step 5.4
+            blank_node_list.sort_unstable();
+            loop {
+                self.consume_work()?;
+                if let Some(candidate) =
+                    self.hash_permutation(&blank_node_list, &issuer, chosen.as_ref())?
+                {
+                    // Step 5.4.5
+                    chosen = Some(candidate);
+                }
+                if !next_permutation(&mut blank_node_list) {
+                    break;
+                }
+            }
+            // Step 5.5
+            let (chosen_path, chosen_issuer) =
+                chosen.expect("Should never fail: the first permutation is always chosen");
+            data_to_hash.push_str(&chosen_path);
+            // Step 5.6
+            issuer = chosen_issuer;
+        }
+        // Step 6
+        Ok((hash(&data_to_hash), issuer))
+    }
+
+    /// Computes the path for the permutation.
+    ///
+    /// Returns `None` if the path is not smaller than the chosen path.
+    ///
+    /// See <https://www.w3.org/TR/rdf-canon/#hash-nd-quads>.
+    // Step 5.4.1-5.4.5
+    fn hash_permutation(
+        &mut self,
+        permutation: &[&str],
+        issuer: &IdIssuer,
+        chosen: Option<&(String, IdIssuer)>,
+    ) -> Result<Option<(String, IdIssuer)>, WorkLimitExceeded> {
+        let is_worse = |path: &str| {
+            chosen.is_some_and(|(chosen_path, _)| {
+                path.len() >= chosen_path.len() && path > chosen_path.as_str()
+            })
+        };
+        // Step 5.4.1
+        let mut issuer_copy = issuer.clone();
+        // Step 5.4.2
+        let mut path = String::new();
+        // Step 5.4.3
+        let mut recursion_list = Vec::new();
+        // Step 5.4.4
+        for &related in permutation {
+            match self.canonical_issuer.get(related) {
+                // Step 5.4.4.1
+                Some(canonical) => {
+                    path.push_str("_:");
+                    path.push_str(canonical);
+                }
+                // Step 5.4.4.2
+                None => {
+                    if issuer_copy.get(related).is_none() {
+                        recursion_list.push(related);
+                    }
+                    path.push_str("_:");
+                    path.push_str(issuer_copy.issue(related));
+                }
+            }
+            // Step 5.4.4.3
+            if is_worse(&path) {
+                return Ok(None);
+            }
+        }
+        // Step 5.4.5
+        for related in recursion_list {
+            // Step 5.4.5.1
+            let (result_hash, result_issuer) =
+                self.hash_n_degree_quads(related, issuer_copy.clone())?;
+            // Step 5.4.5.2
+            path.push_str("_:");
+            path.push_str(issuer_copy.issue(related));
+            // Step 5.4.5.3
+            path.push('<');
+            path.push_str(&result_hash);
+            path.push('>');
+            // Step 5.4.5.4
+            issuer_copy = result_issuer;
+            // Step 5.4.5.5
+            if is_worse(&path) {
+                return Ok(None);
+            }
+        }
+        // Step 5.4.6
+        match chosen {
+            Some((chosen_path, _)) if path >= *chosen_path => Ok(None),
+            _ => Ok(Some((path, issuer_copy))),
+        }
+    }
+}
+
+/// Returns blank node labels in the quad.
+fn blank_node_labels(quad: &Quad) -> impl Iterator<Item = &str> {
+    IntoIterator::into_iter([quad.subject(), quad.predicate(), quad.object()])
+        .chain(quad.graph_name())
+        .filter_map(Term::as_blank_node)
+}
+
+/// Returns the quad with blank nodes relabelled.
+fn relabel(quad: &Quad, mut f: impl FnMut(&str) -> String) -> Quad {
+    let mut relabel_term = |term: &Term| match term.as_blank_node() {
+        Some(label) => Term::blank_node(f(label)),
+        None => term.clone(),
+    };
+    Quad::new(
+        relabel_term(quad.subject()),
+        relabel_term(quad.predicate()),
+        relabel_term(quad.object()),
+        quad.graph_name().map(relabel_term),
+    )
+}
+
+/// Returns the SHA-256 hash of the input as a lowercase hexadecimal string.
+fn hash(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut s, b| {
+            write!(s, "{:02x}", b).expect("Should never fail: writing to string");
+            s
+        })
+}
+
+/// Rearranges the slice into the next permutation in lexicographic order.
+///
+/// Returns `false` if the slice is already the last permutation.
+fn next_permutation<T: Ord>(v: &mut [T]) -> bool {
+    let pivot = match v.windows(2).rposition(|w| w[0] < w[1]) {
+        Some(pivot) => pivot,
+        None => return false,
+    };
+    let successor = v
+        .iter()
+        .rposition(|x| *x > v[pivot])
+        .expect("Should never fail: `v[pivot + 1]` is greater than the pivot");
+    v.swap(pivot, successor);
+    v[(pivot + 1)..].reverse();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Canonicalizes the given N-Quads document.
+    fn canonicalize_nquads(input: &str) -> String {
+        let dataset = nquads::parse(input).expect("Should never fail: valid N-Quads");
+        canonicalize(&dataset)
+            .expect("Should never fail: small dataset")
+            .to_nquads()
+    }
+
+    #[test]
+    fn unique_hashes() {
+        let input = r#"
+            <http://example.com/#p> <http://example.com/#q> _:e0 .
+            <http://example.com/#p> <http://example.com/#r> _:e1 .
+            _:e0 <http://example.com/#s> <http://example.com/#u> .
+            _:e1 <http://example.com/#t> <http://example.com/#u> .
+        "#;
+        assert_eq!(
+            canonicalize_nquads(input),
+            "<http://example.com/#p> <http://example.com/#q> _:c14n0 .\n\
+             <http://example.com/#p> <http://example.com/#r> _:c14n1 .\n\
+             _:c14n0 <http://example.com/#s> <http://example.com/#u> .\n\
+             _:c14n1 <http://example.com/#t> <http://example.com/#u> .\n"
+        );
+    }
+
+    #[test]
+    fn shared_hashes() {
+        let input = r#"
+            <http://example.com/#p> <http://example.com/#q> _:e0 .
+            <http://example.com/#p> <http://example.com/#q> _:e1 .
+            _:e0 <http://example.com/#p> _:e2 .
+            _:e1 <http://example.com/#p> _:e3 .
+            _:e2 <http://example.com/#r> _:e3 .
+        "#;
+        let expected = "<http://example.com/#p> <http://example.com/#q> _:c14n2 .\n\
+                        <http://example.com/#p> <http://example.com/#q> _:c14n3 .\n\
+                        _:c14n0 <http://example.com/#r> _:c14n1 .\n\
+                        _:c14n2 <http://example.com/#p> _:c14n1 .\n\
+                        _:c14n3 <http://example.com/#p> _:c14n0 .\n";
+        assert_eq!(canonicalize_nquads(input), expected);
+
+        // Isomorphic datasets have the same canonical form.
+        let relabelled = r#"
+            _:y <http://example.com/#r> _:x .
+            _:w <http://example.com/#p> _:x .
+            <http://example.com/#p> <http://example.com/#q> _:v .
+            _:v <http://example.com/#p> _:y .
+            <http://example.com/#p> <http://example.com/#q> _:w .
+        "#;
+        assert_eq!(canonicalize_nquads(relabelled), expected);
+    }
+
+    #[test]
+    fn named_graphs() {
+        let lhs = r#"
+            _:a <http://example.com/#p> _:b _:g .
+            _:b <http://example.com/#p> _:a _:g .
+            _:g <http://example.com/#name> "graph" .
+        "#;
+        let rhs = r#"
+            _:g2 <http://example.com/#name> "graph" .
+            _:n1 <http://example.com/#p> _:n0 _:g2 .
+            _:n0 <http://example.com/#p> _:n1 _:g2 .
+        "#;
+        assert_eq!(canonicalize_nquads(lhs), canonicalize_nquads(rhs));
+        assert_ne!(
+            canonicalize_nquads(lhs),
+            canonicalize_nquads(&lhs.replacen("_:a _:g", "_:a", 1))
+        );
+    }
+
+    #[test]
+    fn work_limit() {
+        // A clique of blank nodes which cannot be distinguished from each other.
+        let labels = ["a", "b", "c", "d", "e", "f"];
+        let input = labels
+            .iter()
+            .flat_map(|s| labels.iter().map(move |o| (s, o)))
+            .filter(|(s, o)| s != o)
+            .map(|(s, o)| format!("_:{} <http://example.com/#p> _:{} .\n", s, o))
+            .collect::<String>();
+        let dataset = nquads::parse(&input).expect("Should never fail: valid N-Quads");
+        let mut canonicalizer = Canonicalizer::new();
+        canonicalizer.set_work_limit(Some(1000));
+        assert_eq!(
+            canonicalizer.canonicalize(&dataset),
+            Err(WorkLimitExceeded { limit: 1000 })
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! RDF dataset.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#data-round-tripping> and
+//! <https://www.w3.org/TR/rdf11-concepts/#section-dataset>.
+
+use std::{collections::HashSet, iter::FromIterator, slice, vec};
+
+use crate::rdf::vocab::{RDF_LANG_STRING, XSD_STRING};
+
+/// RDF term.
+///
+/// In generalized RDF, any kind of terms can be a subject, a predicate, an object, and a graph
+/// name.
+///
+/// See <https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-term>.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Term {
+    /// IRI.
+    Iri(String),
+    /// Blank node.
+    ///
+    /// The value is a blank node label without the leading `_:`.
+    BlankNode(String),
+    /// Literal.
+    Literal(Literal),
+}
+
+impl Term {
+    /// Creates an IRI term.
+    pub fn iri(iri: impl Into<String>) -> Self {
+        Term::Iri(iri.into())
+    }
+
+    /// Creates a blank node term from the label.
+    ///
+    /// The label should not have the leading `_:`.
+    pub fn blank_node(label: impl Into<String>) -> Self {
+        Term::BlankNode(label.into())
+    }
+
+    /// Creates a term from the IRI or the blank node identifier (which starts with `_:`).
+    pub(crate) fn from_iri_or_blank_node(s: &str) -> Self {
+        match s.strip_prefix("_:") {
+            Some(label) => Term::blank_node(label),
+            None => Term::iri(s),
+        }
+    }
+
+    /// Returns the IRI if the term is an IRI.
+    pub fn as_iri(&self) -> Option<&str> {
+        match self {
+            Term::Iri(iri) => Some(iri),
+            _ => None,
+        }
+    }
+
+    /// Returns the blank node label (without the leading `_:`) if the term is a blank node.
+    pub fn as_blank_node(&self) -> Option<&str> {
+        match self {
+            Term::BlankNode(label) => Some(label),
+            _ => None,
+        }
+    }
+
+    /// Returns the literal if the term is a literal.
+    pub fn as_literal(&self) -> Option<&Literal> {
+        match self {
+            Term::Literal(literal) => Some(literal),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the term is an IRI.
+    pub fn is_iri(&self) -> bool {
+        self.as_iri().is_some()
+    }
+
+    /// Returns true if the term is a blank node.
+    pub fn is_blank_node(&self) -> bool {
+        self.as_blank_node().is_some()
+    }
+
+    /// Returns true if the term is a literal.
+    pub fn is_literal(&self) -> bool {
+        self.as_literal().is_some()
+    }
+}
+
+impl From<Literal> for Term {
+    fn from(literal: Literal) -> Self {
+        Term::Literal(literal)
+    }
+}
+
+/// RDF literal.
+///
+/// See <https://www.w3.org/TR/rdf11-concepts/#section-Graph-Literal>.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    /// Lexical form.
+    lexical_form: String,
+    /// Datatype IRI.
+    datatype: String,
+    /// Language tag.
+    language: Option<String>,
+}
+
+impl Literal {
+    /// Creates a new typed literal.
+    pub fn new(lexical_form: impl Into<String>, datatype: impl Into<String>) -> Self {
+        Self {
+            lexical_form: lexical_form.into(),
+            datatype: datatype.into(),
+            language: None,
+        }
+    }
+
+    /// Creates a new simple literal, i.e. a literal with `xsd:string` datatype.
+    pub fn simple(lexical_form: impl Into<String>) -> Self {
+        Self::new(lexical_form, XSD_STRING)
+    }
+
+    /// Creates a new language-tagged string.
+    ///
+    /// The datatype of the literal is `rdf:langString`.
+    pub fn with_language(lexical_form: impl Into<String>, language: impl Into<String>) -> Self {
+        Self {
+            lexical_form: lexical_form.into(),
+            datatype: RDF_LANG_STRING.to_owned(),
+            language: Some(language.into()),
+        }
+    }
+
+    /// Returns the lexical form.
+    pub fn lexical_form(&self) -> &str {
+        &self.lexical_form
+    }
+
+    /// Returns the datatype IRI.
+    pub fn datatype(&self) -> &str {
+        &self.datatype
+    }
+
+    /// Returns the language tag.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+}
+
+/// RDF quad, i.e. a triple with an optional graph name.
+///
+/// See <https://www.w3.org/TR/rdf11-concepts/#section-dataset>.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Quad {
+    /// Subject.
+    subject: Term,
+    /// Predicate.
+    predicate: Term,
+    /// Object.
+    object: Term,
+    /// Graph name (`None` for the default graph).
+    graph_name: Option<Term>,
+}
+
+impl Quad {
+    /// Creates a new quad.
+    ///
+    /// `None` as a graph name means the default graph.
+    pub fn new(subject: Term, predicate: Term, object: Term, graph_name: Option<Term>) -> Self {
+        Self {
+            subject,
+            predicate,
+            object,
+            graph_name,
+        }
+    }
+
+    /// Returns the subject.
+    pub fn subject(&self) -> &Term {
+        &self.subject
+    }
+
+    /// Returns the predicate.
+    pub fn predicate(&self) -> &Term {
+        &self.predicate
+    }
+
+    /// Returns the object.
+    pub fn object(&self) -> &Term {
+        &self.object
+    }
+
+    /// Returns the graph name.
+    ///
+    /// `None` means the default graph.
+    pub fn graph_name(&self) -> Option<&Term> {
+        self.graph_name.as_ref()
+    }
+}
+
+/// RDF dataset.
+///
+/// Quads are kept in the insertion order, and duplicate quads are ignored.
+///
+/// See <https://www.w3.org/TR/rdf11-concepts/#section-dataset>.
+#[derive(Default, Debug, Clone)]
+pub struct Dataset {
+    /// Quads.
+    quads: Vec<Quad>,
+    /// Set of quads, to prevent duplicates.
+    quads_set: HashSet<Quad>,
+}
+
+impl Dataset {
+    /// Creates a new empty dataset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the quad to the dataset.
+    ///
+    /// Returns `false` if the dataset already has the quad.
+    pub fn insert(&mut self, quad: Quad) -> bool {
+        if self.quads_set.contains(&quad) {
+            return false;
+        }
+        self.quads_set.insert(quad.clone());
+        self.quads.push(quad);
+        true
+    }
+
+    /// Returns true if the dataset has the quad.
+    pub fn contains(&self, quad: &Quad) -> bool {
+        self.quads_set.contains(quad)
+    }
+
+    /// Returns the quads.
+    pub fn quads(&self) -> &[Quad] {
+        &self.quads
+    }
+
+    /// Returns an iterator of the quads.
+    pub fn iter(&self) -> slice::Iter<'_, Quad> {
+        self.quads.iter()
+    }
+
+    /// Returns the number of the quads.
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    /// Returns true if the dataset has no quads.
+    pub fn is_empty(&self) -> bool {
+        self.quads.is_empty()
+    }
+
+    /// Returns the quads with ownership.
+    pub fn into_quads(self) -> Vec<Quad> {
+        self.quads
+    }
+}
+
+impl PartialEq for Dataset {
+    /// Compares two datasets as sets of quads.
+    ///
+    /// Note that blank nodes are compared by their labels.
+    fn eq(&self, other: &Self) -> bool {
+        self.quads_set == other.quads_set
+    }
+}
+
+impl Eq for Dataset {}
+
+impl Extend<Quad> for Dataset {
+    fn extend<I: IntoIterator<Item = Quad>>(&mut self, iter: I) {
+        for quad in iter {
+            self.insert(quad);
+        }
+    }
+}
+
+impl FromIterator<Quad> for Dataset {
+    fn from_iter<I: IntoIterator<Item = Quad>>(iter: I) -> Self {
+        let mut dataset = Self::new();
+        dataset.extend(iter);
+        dataset
+    }
+}
+
+impl IntoIterator for Dataset {
+    type Item = Quad;
+    type IntoIter = vec::IntoIter<Quad>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.quads.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Dataset {
+    type Item = &'a Quad;
+    type IntoIter = slice::Iter<'a, Quad>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.quads.iter()
+    }
+}
@@ -0,0 +1,55 @@
+//! Encoding of base direction in RDF.
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error as ThisError;
+
+/// Encoding of base direction of strings in RDF.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldoptions-rdfdirection>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RdfDirection {
+    /// `i18n-datatype`.
+    ///
+    /// Strings with base direction are encoded as literals with datatype IRIs such as
+    /// `https://www.w3.org/ns/i18n#en_rtl`.
+    I18nDatatype,
+    /// `compound-literal`.
+    ///
+    /// Strings with base direction are encoded as blank nodes with `rdf:value`,
+    /// `rdf:language`, and `rdf:direction` properties.
+    CompoundLiteral,
+}
+
+impl RdfDirection {
+    /// Returns the string representation.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RdfDirection::I18nDatatype => "i18n-datatype",
+            RdfDirection::CompoundLiteral => "compound-literal",
+        }
+    }
+}
+
+impl fmt::Display for RdfDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RdfDirection {
+    type Err = RdfDirectionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i18n-datatype" => Ok(RdfDirection::I18nDatatype),
+            "compound-literal" => Ok(RdfDirection::CompoundLiteral),
+            s => Err(RdfDirectionParseError(s.to_owned())),
+        }
+    }
+}
+
+/// Error for invalid `rdfDirection` value.
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+#[error("Invalid `rdfDirection` value {0:?}")]
+pub struct RdfDirectionParseError(String);
@@ -0,0 +1,713 @@
+//! Serialization of RDF as JSON-LD.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#serialize-rdf-as-json-ld-algorithm>.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Number, Value};
+
+use crate::{
+    context::Direction,
+    error::{ErrorCode, Result},
+    flatten::Graph,
+    iri::is_blank_node_ident,
+    json::single_entry_map,
+    processor::ProcessorOptions,
+    rdf::{
+        vocab::{
+            I18N_PREFIX, RDF_DIRECTION, RDF_FIRST, RDF_JSON, RDF_LANGUAGE, RDF_LIST, RDF_NIL,
+            RDF_REST, RDF_TYPE, RDF_VALUE, XSD_BOOLEAN, XSD_DOUBLE, XSD_INTEGER, XSD_STRING,
+        },
+        Dataset, Literal, RdfDirection, Term,
+    },
+    syntax::is_well_formed_language_tag,
+};
+
+/// Usage of a node.
+#[derive(Debug, Clone)]
+struct Usage {
+    /// Graph name.
+    graph: String,
+    /// Node identifier of the subject.
+    subject: String,
+    /// Property.
+    property: String,
+    /// Value.
+    value: Value,
+}
+
+/// Runs serialize RDF as JSON-LD algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#serialize-rdf-as-json-ld-algorithm>.
+pub(crate) fn from_rdf(options: &ProcessorOptions, dataset: &Dataset) -> Result<Value> {
+    // Step 1, 2
+    let mut graph_map = BTreeMap::<String, Graph>::new();
+    graph_map.insert("@default".into(), Graph::new());
+    // Step 3
+    // `None` means the node is referenced more than once.
+    let mut referenced_once = HashMap::<String, Option<Usage>>::new();
+    // Usages of `rdf:nil` for each graph.
+    let mut nil_usages = BTreeMap::<String, Vec<Usage>>::new();
+    // Subjects of compound literals for each graph.
+    let mut compound_literal_subjects = BTreeMap::<String, Vec<String>>::new();
+    // Step 5
+    for quad in dataset {
+        // Step 5.1
+        let name = quad
+            .graph_name()
+            .map_or_else(|| "@default".to_owned(), term_to_id);
+        // Step 5.4
+        if name != "@default" {
+            graph_map
+                .entry("@default".into())
+                .or_default()
+                .entry(name.clone())
+                .or_insert_with(|| single_entry_map("@id", name.as_str()));
+        }
+        // Step 5.2, 5.5
+        let node_map = graph_map.entry(name.clone()).or_default();
+        let subject = term_to_id(quad.subject());
+        let predicate = term_to_id(quad.predicate());
+        let object = quad.object();
+        // Step 5.6.3
+        if options.rdf_direction() == Some(RdfDirection::CompoundLiteral)
+            && predicate == RDF_DIRECTION
+        {
+            compound_literal_subjects
+                .entry(name.clone())
+                .or_default()
+                .push(subject.clone());
+        }
+        // Step 5.6.4
+        if !object.is_literal() {
+            let object = term_to_id(object);
+            node_map
+                .entry(object.clone())
+                .or_insert_with(|| single_entry_map("@id", object));
+        }
+        // Step 5.6.1, 5.6.2
+        let node = node_map
+            .entry(subject.clone())
+            .or_insert_with(|| single_entry_map("@id", subject.as_str()));
+        // Step 5.6.5
+        if predicate == RDF_TYPE && !options.use_rdf_type() && !object.is_literal() {
+            let ty = Value::String(term_to_id(object));
+            let types = node
+                .entry("@type")
+                .or_insert_with(|| Value::Array(Vec::new()))
+                .as_array_mut()
+                .expect("Should never fail: `@type` is an array");
+            if !types.contains(&ty) {
+                types.push(ty);
+            }
+            continue;
+        }
+        // Step 5.6.6
+        let value = rdf_to_object(options, object)?;
+        // Step 5.6.7, 5.6.8
+        let values = node
+            .entry(predicate.as_str())
+            .or_insert_with(|| Value::Array(Vec::new()))
+            .as_array_mut()
+            .expect("Should never fail: property values are arrays");
+        if !values.contains(&value) {
+            values.push(value.clone());
+        }
+        let usage = Usage {
+            graph: name.clone(),
+            subject,
+            property: predicate,
+            value,
+        };
+        match object {
+            // Step 5.6.9
+            Term::Iri(iri) if iri == RDF_NIL => nil_usages.entry(name).or_default().push(usage),
+            // Step 5.6.11
+            Term::BlankNode(_) => {
+                referenced_once
+                    .entry(term_to_id(object))
+                    // Step 5.6.10
+                    .and_modify(|usage| *usage = None)
+                    .or_insert(Some(usage));
+            }
+            _ => {}
+        }
+    }
+    // Step 6
+    for (name, graph_object) in &mut graph_map {
+        // Step 6.1
+        for cl in compound_literal_subjects.remove(name).into_iter().flatten() {
+            convert_compound_literal(graph_object, &referenced_once, name, &cl)?;
+        }
+        // Step 6.4
+        for usage in nil_usages.remove(name).into_iter().flatten() {
+            convert_list(graph_object, &referenced_once, usage);
+        }
+    }
+    // Step 7
+    let mut result = Vec::new();
+    // Step 8
+    let default_graph = graph_map.remove("@default").unwrap_or_default();
+    for (subject, mut node) in default_graph {
+        // Step 8.1
+        if let Some(graph) = graph_map.get(&subject) {
+            // Step 8.1.1, 8.1.2
+            node.insert("@graph".into(), Value::Array(to_node_array(graph)));
+        }
+        // Step 8.2
+        if !is_only_id(&node) {
+            result.push(Value::Object(node));
+        }
+    }
+
+    // Step 9
+    Ok(Value::Array(result))
+}
+
+/// Replaces references to the given compound literal with a value object.
+// Step 6.1
+fn convert_compound_literal(
+    graph_object: &mut Graph,
+    referenced_once: &HashMap<String, Option<Usage>>,
+    graph_name: &str,
+    cl: &str,
+) -> Result<()> {
+    // Step 6.1.1
+    let usage = match referenced_once.get(cl) {
+        Some(Some(usage)) if usage.graph == graph_name => usage,
+        _ => return Ok(()),
+    };
+    // Step 6.1.5
+    let cl_node = match graph_object.remove(cl) {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let first_value = |property: &str| {
+        cl_node
+            .get(property)
+            .and_then(|values| values.get(0))
+            .and_then(|v| v.get("@value"))
+    };
+    let value = first_value(RDF_VALUE).cloned().unwrap_or(Value::Null);
+    let language = first_value(RDF_LANGUAGE);
+    let direction = first_value(RDF_DIRECTION);
+    let mut replacement = JsonMap::new();
+    // Step 6.1.6.2
+    replacement.insert("@value".into(), value);
+    // Step 6.1.6.3
+    if let Some(language) = language {
+        match language.as_str() {
+            Some(s) if is_well_formed_language_tag(s) => {
+                replacement.insert("@language".into(), language.clone());
+            }
+            _ => {
+                return Err(ErrorCode::InvalidLanguageTaggedString.and_source(anyhow!(
+                    "Invalid language tag {} in the compound literal {:?}",
+                    language,
+                    cl
+                )))
+            }
+        }
+    }
+    // Step 6.1.6.4
+    if let Some(direction) = direction {
+        match direction.as_str().map(Direction::from_str) {
+            Some(Ok(_)) => {
+                replacement.insert("@direction".into(), direction.clone());
+            }
+            _ => {
+                return Err(ErrorCode::InvalidBaseDirection.and_source(anyhow!(
+                    "Invalid base direction {} in the compound literal {:?}",
+                    direction,
+                    cl
+                )))
+            }
+        }
+    }
+    // Step 6.1.6
+    let references = graph_object
+        .get_mut(&usage.subject)
+        .and_then(|node| node.get_mut(&usage.property))
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter(|v| v.get("@id").and_then(Value::as_str) == Some(cl));
+    for reference in references {
+        // Step 6.1.6.1
+        *reference = Value::Object(replacement.clone());
+    }
+
+    Ok(())
+}
+
+/// Converts the list which ends with the given usage of `rdf:nil`.
+// Step 6.4
+fn convert_list(
+    graph_object: &mut Graph,
+    referenced_once: &HashMap<String, Option<Usage>>,
+    usage: Usage,
+) {
+    // Step 6.4.1
+    let mut usage = usage;
+    // Step 6.4.2
+    let mut list = Vec::new();
+    let mut list_nodes = Vec::new();
+    // Step 6.4.3
+    while usage.property == RDF_REST && is_blank_node_ident(&usage.subject) {
+        let node_usage = match referenced_once.get(&usage.subject) {
+            Some(Some(node_usage)) if node_usage.graph == usage.graph => node_usage,
+            _ => break,
+        };
+        let first = match graph_object
+            .get(&usage.subject)
+            .and_then(get_well_formed_list_node_first)
+        {
+            Some(first) => first,
+            None => break,
+        };
+        // Step 6.4.3.1
+        list.push(first.clone());
+        // Step 6.4.3.2
+        list_nodes.push(usage.subject.clone());
+        // Step 6.4.3.3, 6.4.3.4
+        usage = node_usage.clone();
+        // Step 6.4.3.5
+        if !is_blank_node_ident(&usage.subject) {
+            break;
+        }
+    }
+    // Step 6.4.4, 6.4.5, 6.4.6
+    list.reverse();
+    if let Some(head) = graph_object
+        .get_mut(&usage.subject)
+        .and_then(|node| node.get_mut(&usage.property))
+        .and_then(Value::as_array_mut)
+        .and_then(|values| values.iter_mut().find(|v| **v == usage.value))
+    {
+        *head = Value::Object(single_entry_map("@list", list));
+    }
+    // Step 6.4.7
+    for node_id in list_nodes {
+        graph_object.remove(&node_id);
+    }
+}
+
+/// Returns the `rdf:first` value of the node if the node is a well-formed list node.
+// Step 6.4.3
+fn get_well_formed_list_node_first(node: &JsonMap<String, Value>) -> Option<&Value> {
+    let single = |key: &str| match node.get(key)?.as_array()?.as_slice() {
+        [v] => Some(v),
+        _ => None,
+    };
+    let first = single(RDF_FIRST)?;
+    single(RDF_REST)?;
+    let has_valid_type = match node.get("@type") {
+        Some(_) => single("@type")? == RDF_LIST,
+        None => true,
+    };
+    let has_no_other_entries = node
+        .keys()
+        .all(|key| matches!(key.as_str(), "@id" | "@type" | RDF_FIRST | RDF_REST));
+    if has_valid_type && has_no_other_entries {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// Runs RDF to object conversion algorithm.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#rdf-to-object-conversion>.
+fn rdf_to_object(options: &ProcessorOptions, value: &Term) -> Result<Value> {
+    let literal = match value {
+        Term::Literal(literal) => literal,
+        // Step 1
+        v => return Ok(single_entry_map("@id", term_to_id(v)).into()),
+    };
+    // Step 2
+    // Step 2.1
+    let mut result = JsonMap::new();
+    // Step 2.2
+    let mut converted_value = Value::String(literal.lexical_form().to_owned());
+    // Step 2.3
+    let mut ty = None;
+    let datatype = literal.datatype();
+    if options.use_native_types()
+        && [XSD_STRING, XSD_BOOLEAN, XSD_INTEGER, XSD_DOUBLE].contains(&datatype)
+    {
+        // Step 2.4
+        if let Some(native) = to_native_value(literal) {
+            converted_value = native;
+        } else {
+            ty = Some(datatype);
+        }
+    } else if !options.is_processing_mode_1_0() && datatype == RDF_JSON {
+        // Step 2.5
+        converted_value = serde_json::from_str(literal.lexical_form()).map_err(|e| {
+            ErrorCode::InvalidJsonLiteral.and_source(anyhow!(
+                "Failed to parse JSON literal {:?}: {}",
+                literal.lexical_form(),
+                e
+            ))
+        })?;
+        ty = Some("@json");
+    } else if let Some((language, direction)) = options
+        .rdf_direction()
+        .filter(|&v| v == RdfDirection::I18nDatatype)
+        .and_then(|_| datatype.strip_prefix(I18N_PREFIX))
+        .and_then(|fragment| fragment.split_once('_'))
+    {
+        // Step 2.6
+        if !language.is_empty() {
+            result.insert("@language".into(), language.into());
+        }
+        result.insert("@direction".into(), direction.into());
+    } else if let Some(language) = literal.language() {
+        // Step 2.7
+        result.insert("@language".into(), language.into());
+    } else if datatype != XSD_STRING {
+        // Step 2.8
+        ty = Some(datatype);
+    }
+    // Step 2.9
+    result.insert("@value".into(), converted_value);
+    // Step 2.10
+    if let Some(ty) = ty {
+        result.insert("@type".into(), ty.into());
+    }
+
+    // Step 2.11
+    Ok(Value::Object(result))
+}
+
+/// Converts the literal into a native JSON value.
+///
+/// Returns `None` if the lexical form is not valid for the datatype.
+// Step 2.4
+fn to_native_value(literal: &Literal) -> Option<Value> {
+    let lexical_form = literal.lexical_form();
+    match literal.datatype() {
+        // Step 2.4.1
+        XSD_STRING => Some(lexical_form.into()),
+        // Step 2.4.2
+        XSD_BOOLEAN => match lexical_form {
+            "true" => Some(true.into()),
+            "false" => Some(false.into()),
+            _ => None,
+        },
+        // Step 2.4.3
+        XSD_INTEGER if is_valid_xsd_integer(lexical_form) => {
+            let digits = lexical_form.strip_prefix('+').unwrap_or(lexical_form);
+            if let Ok(v) = digits.parse::<i64>() {
+                Some(v.into())
+            } else if let Ok(v) = digits.parse::<u64>() {
+                Some(v.into())
+            } else {
+                digits
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Into::into)
+            }
+        }
+        XSD_DOUBLE if is_valid_xsd_double(lexical_form) => lexical_form
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Into::into),
+        _ => None,
+    }
+}
+
+/// Checks if the given string is a valid lexical form of `xsd:integer`.
+///
+/// See <https://www.w3.org/TR/xmlschema11-2/#integer-lexical-space>.
+fn is_valid_xsd_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Checks if the given string is a valid lexical form of `xsd:double`, except for special values
+/// (such as `INF` and `NaN`) which cannot be JSON numbers.
+///
+/// See <https://www.w3.org/TR/xmlschema11-2/#double-lexical-space>.
+fn is_valid_xsd_double(s: &str) -> bool {
+    let s = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(pos) => (&s[..pos], Some(&s[(pos + 1)..])),
+        None => (s, None),
+    };
+    let (int_part, frac_part) = match mantissa.find('.') {
+        Some(pos) => (&mantissa[..pos], &mantissa[(pos + 1)..]),
+        None => (mantissa, ""),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let is_valid_mantissa = is_digits(int_part)
+        && is_digits(frac_part)
+        && !(int_part.is_empty() && frac_part.is_empty());
+    is_valid_mantissa && exponent.map_or(true, is_valid_xsd_integer)
+}
+
+/// Converts the IRI or blank node into a node identifier.
+fn term_to_id(term: &Term) -> String {
+    match term {
+        Term::Iri(iri) => iri.clone(),
+        Term::BlankNode(label) => format!("_:{}", label),
+        Term::Literal(literal) => unreachable!(
+            "Should never fail: a literal cannot be a node identifier, but got {:?}",
+            literal
+        ),
+    }
+}
+
+/// Checks if the node only has `@id` entry.
+fn is_only_id(node: &JsonMap<String, Value>) -> bool {
+    node.len() == 1 && node.contains_key("@id")
+}
+
+/// Returns an array of nodes ordered by the identifiers, excluding nodes which only have `@id`.
+// Step 8.1.2
+fn to_node_array(graph: &Graph) -> Vec<Value> {
+    graph
+        .values()
+        .filter(|node| !is_only_id(node))
+        .cloned()
+        .map(Value::Object)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    use crate::{
+        rdf::Quad,
+        test_utils::{block_on, processor},
+    };
+
+    /// Creates a dataset from the quads.
+    fn dataset(quads: &[(Term, &str, Term, Option<&str>)]) -> Dataset {
+        quads
+            .iter()
+            .map(|(s, p, o, g)| {
+                Quad::new(
+                    s.clone(),
+                    Term::iri(*p),
+                    o.clone(),
+                    g.map(Term::from_iri_or_blank_node),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn literals_and_types() {
+        let s = Term::iri("http://example.org/s");
+        let p = "http://example.org/p";
+        let dataset = dataset(&[
+            (s.clone(), RDF_TYPE, Term::iri("http://example.org/T"), None),
+            (s.clone(), p, Literal::simple("str").into(), None),
+            (
+                s.clone(),
+                p,
+                Literal::with_language("hi", "en").into(),
+                None,
+            ),
+            (s.clone(), p, Literal::new("1", XSD_INTEGER).into(), None),
+            (s.clone(), p, Literal::new("1", XSD_INTEGER).into(), None),
+            (s.clone(), p, Literal::new("[1]", RDF_JSON).into(), None),
+            (
+                s,
+                p,
+                Literal::new("x", "http://example.org/dt").into(),
+                None,
+            ),
+        ]);
+        assert_eq!(
+            from_rdf(processor().options(), &dataset).expect("Should never fail: valid dataset"),
+            json!([{
+                "@id": "http://example.org/s",
+                "@type": ["http://example.org/T"],
+                "http://example.org/p": [
+                    { "@value": "str" },
+                    { "@value": "hi", "@language": "en" },
+                    { "@value": "1", "@type": XSD_INTEGER },
+                    { "@value": [1], "@type": "@json" },
+                    { "@value": "x", "@type": "http://example.org/dt" }
+                ]
+            }])
+        );
+    }
+
+    #[test]
+    fn native_types_and_rdf_type() {
+        let s = Term::iri("http://example.org/s");
+        let p = "http://example.org/p";
+        let dataset = dataset(&[
+            (s.clone(), RDF_TYPE, Term::iri("http://example.org/T"), None),
+            (s.clone(), p, Literal::new("true", XSD_BOOLEAN).into(), None),
+            (s.clone(), p, Literal::new("yes", XSD_BOOLEAN).into(), None),
+            (s.clone(), p, Literal::new("+42", XSD_INTEGER).into(), None),
+            (s.clone(), p, Literal::new("1.5E0", XSD_DOUBLE).into(), None),
+            (s, p, Literal::new("INF", XSD_DOUBLE).into(), None),
+        ]);
+        let mut options = processor().options().clone();
+        options.set_use_native_types(true);
+        options.set_use_rdf_type(true);
+        assert_eq!(
+            from_rdf(&options, &dataset).expect("Should never fail: valid dataset"),
+            json!([{
+                "@id": "http://example.org/s",
+                RDF_TYPE: [{ "@id": "http://example.org/T" }],
+                "http://example.org/p": [
+                    { "@value": true },
+                    { "@value": "yes", "@type": XSD_BOOLEAN },
+                    { "@value": 42 },
+                    { "@value": 1.5 },
+                    { "@value": "INF", "@type": XSD_DOUBLE }
+                ]
+            }])
+        );
+    }
+
+    #[test]
+    fn lists_and_graphs() {
+        let s = Term::iri("http://example.org/s");
+        let p = "http://example.org/p";
+        let bnode = Term::blank_node;
+        let nil = Term::iri(RDF_NIL);
+        let dataset = dataset(&[
+            (s.clone(), p, bnode("l1"), Some("http://example.org/g")),
+            (
+                bnode("l1"),
+                RDF_FIRST,
+                Literal::simple("a").into(),
+                Some("http://example.org/g"),
+            ),
+            (
+                bnode("l1"),
+                RDF_REST,
+                bnode("l2"),
+                Some("http://example.org/g"),
+            ),
+            (
+                bnode("l2"),
+                RDF_FIRST,
+                Literal::simple("b").into(),
+                Some("http://example.org/g"),
+            ),
+            (
+                bnode("l2"),
+                RDF_REST,
+                nil.clone(),
+                Some("http://example.org/g"),
+            ),
+            (s.clone(), "http://example.org/empty", nil, None),
+            // Referenced twice, so this is not converted to a list.
+            (s.clone(), "http://example.org/q", bnode("m"), None),
+            (s, "http://example.org/r", bnode("m"), None),
+            (bnode("m"), RDF_FIRST, Literal::simple("c").into(), None),
+            (bnode("m"), RDF_REST, Term::iri(RDF_NIL), None),
+        ]);
+        let result = block_on(processor().from_rdf(&dataset)).expect("Should never fail: valid");
+        assert_eq!(
+            result,
+            json!([
+                {
+                    "@id": "_:m",
+                    RDF_FIRST: [{ "@value": "c" }],
+                    RDF_REST: [{ "@list": [] }]
+                },
+                {
+                    "@id": "http://example.org/g",
+                    "@graph": [{
+                        "@id": "http://example.org/s",
+                        "http://example.org/p": [{ "@list": [{ "@value": "a" }, { "@value": "b" }] }]
+                    }]
+                },
+                {
+                    "@id": "http://example.org/s",
+                    "http://example.org/empty": [{ "@list": [] }],
+                    "http://example.org/q": [{ "@id": "_:m" }],
+                    "http://example.org/r": [{ "@id": "_:m" }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_json_literal() {
+        let dataset = dataset(&[(
+            Term::iri("http://example.org/s"),
+            "http://example.org/p",
+            Literal::new("{", RDF_JSON).into(),
+            None,
+        )]);
+        let err = block_on(processor().from_rdf(&dataset))
+            .expect_err("Should never fail: invalid JSON literal");
+        assert_eq!(err.code(), ErrorCode::InvalidJsonLiteral);
+    }
+
+    #[test]
+    fn rdf_direction() {
+        let s = Term::iri("http://example.org/s");
+        let p = "http://example.org/p";
+        let i18n = |suffix: &str| format!("{}{}", I18N_PREFIX, suffix);
+        let dataset = dataset(&[
+            (s.clone(), p, Literal::new("a", i18n("en_rtl")).into(), None),
+            (s.clone(), p, Literal::new("b", i18n("_ltr")).into(), None),
+            (s, p, Term::blank_node("cl"), None),
+            (
+                Term::blank_node("cl"),
+                RDF_VALUE,
+                Literal::simple("c").into(),
+                None,
+            ),
+            (
+                Term::blank_node("cl"),
+                RDF_LANGUAGE,
+                Literal::simple("ja").into(),
+                None,
+            ),
+            (
+                Term::blank_node("cl"),
+                RDF_DIRECTION,
+                Literal::simple("rtl").into(),
+                None,
+            ),
+        ]);
+
+        let mut options = processor().options().clone();
+        options.set_rdf_direction(Some(RdfDirection::I18nDatatype));
+        let values = from_rdf(&options, &dataset).expect("Should never fail: valid dataset")[1]
+            ["http://example.org/p"]
+            .clone();
+        assert_eq!(
+            values,
+            json!([
+                { "@value": "a", "@language": "en", "@direction": "rtl" },
+                { "@value": "b", "@direction": "ltr" },
+                { "@id": "_:cl" }
+            ])
+        );
+
+        options.set_rdf_direction(Some(RdfDirection::CompoundLiteral));
+        assert_eq!(
+            from_rdf(&options, &dataset).expect("Should never fail: valid dataset"),
+            json!([{
+                "@id": "http://example.org/s",
+                "http://example.org/p": [
+                    { "@value": "a", "@type": i18n("en_rtl") },
+                    { "@value": "b", "@type": i18n("_ltr") },
+                    { "@value": "c", "@language": "ja", "@direction": "rtl" }
+                ]
+            }])
+        );
+    }
+}
@@ -0,0 +1,629 @@
+//! N-Quads parser and serializer.
+//!
+//! N-Triples documents are also accepted by the parser, since N-Triples is a subset of N-Quads.
+//!
+//! The parser also accepts blank nodes as predicates, in order to read generalized RDF datasets.
+//!
+//! See <https://www.w3.org/TR/n-quads/>.
+
+use std::fmt::{self, Write as _};
+
+use thiserror::Error as ThisError;
+
+use crate::rdf::{vocab::XSD_STRING, Dataset, Literal, Quad, Term};
+
+/// N-Quads parse error.
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+#[error("{message} (at line {line}, column {column})")]
+pub struct ParseError {
+    /// Line number (1-based).
+    line: usize,
+    /// Column number (1-based, in characters).
+    column: usize,
+    /// Error message.
+    message: String,
+}
+
+impl ParseError {
+    /// Returns the line number (1-based) where the error occurred.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column number (1-based, in characters) where the error occurred.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// Parses the given N-Quads document into a dataset.
+///
+/// Duplicate quads are ignored.
+pub fn parse(input: &str) -> Result<Dataset, ParseError> {
+    let mut parser = Parser::new(input);
+    let mut dataset = Dataset::new();
+    while let Some(quad) = parser.next_statement()? {
+        dataset.insert(quad);
+    }
+    Ok(dataset)
+}
+
+/// Serializes the given dataset into an N-Quads document.
+///
+/// Each quad is serialized in canonical form in a line, in the same order as the dataset.
+pub fn serialize(dataset: &Dataset) -> String {
+    let mut buf = String::new();
+    for quad in dataset {
+        writeln!(buf, "{}", quad).expect("Should never fail: writing to string");
+    }
+    buf
+}
+
+impl fmt::Display for Term {
+    /// Writes the term in N-Quads form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Iri(iri) => write_iri(f, iri),
+            Term::BlankNode(label) => write!(f, "_:{}", label),
+            Term::Literal(literal) => literal.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    /// Writes the literal in N-Quads form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_string(f, self.lexical_form())?;
+        match self.language() {
+            Some(language) => write!(f, "@{}", language),
+            None if self.datatype() == XSD_STRING => Ok(()),
+            None => {
+                f.write_str("^^")?;
+                write_iri(f, self.datatype())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Quad {
+    /// Writes the quad as an N-Quads statement, without a trailing newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            self.subject(),
+            self.predicate(),
+            self.object()
+        )?;
+        if let Some(graph_name) = self.graph_name() {
+            write!(f, "{} ", graph_name)?;
+        }
+        f.write_char('.')
+    }
+}
+
+/// Writes the IRI in `IRIREF` form.
+fn write_iri(f: &mut fmt::Formatter<'_>, iri: &str) -> fmt::Result {
+    f.write_char('<')?;
+    for c in iri.chars() {
+        match c {
+            '\u{00}'..='\u{20}' | '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' => {
+                write!(f, "\\u{:04X}", c as u32)?
+            }
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('>')
+}
+
+/// Writes the string in canonical `STRING_LITERAL_QUOTE` form.
+///
+/// See <https://www.w3.org/TR/rdf12-n-quads/#canonical-quads>.
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '\u{08}' => f.write_str("\\b")?,
+            '\t' => f.write_str("\\t")?,
+            '\n' => f.write_str("\\n")?,
+            '\u{0C}' => f.write_str("\\f")?,
+            '\r' => f.write_str("\\r")?,
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\u{00}'..='\u{1F}' | '\u{7F}' => write!(f, "\\u{:04X}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Checks if the character matches `PN_CHARS_BASE`.
+fn is_pn_chars_base(c: char) -> bool {
+    matches!(c,
+        'A'..='Z'
+        | 'a'..='z'
+        | '\u{00C0}'..='\u{00D6}'
+        | '\u{00D8}'..='\u{00F6}'
+        | '\u{00F8}'..='\u{02FF}'
+        | '\u{0370}'..='\u{037D}'
+        | '\u{037F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+/// Checks if the character matches `PN_CHARS_U`.
+fn is_pn_chars_u(c: char) -> bool {
+    is_pn_chars_base(c) || c == '_' || c == ':'
+}
+
+/// Checks if the character matches `PN_CHARS`.
+fn is_pn_chars(c: char) -> bool {
+    is_pn_chars_u(c)
+        || matches!(c,
+            '-' | '0'..='9' | '\u{00B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}')
+}
+
+/// Checks if the IRI has a scheme, i.e. is not a relative IRI.
+fn has_scheme(iri: &str) -> bool {
+    match iri.find(':') {
+        Some(pos) => {
+            let scheme = &iri[..pos];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// N-Quads parser.
+struct Parser<'a> {
+    /// Rest of the input.
+    rest: &'a str,
+    /// Current line number (1-based).
+    line: usize,
+    /// Current column number (1-based).
+    column: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a new parser.
+    fn new(input: &'a str) -> Self {
+        Self {
+            rest: input,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns an error at the current position.
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    /// Consumes the next character.
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        match c {
+            // CR LF is a single line break.
+            '\r' if self.rest.starts_with('\n') => self.column += 1,
+            '\r' | '\n' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => self.column += 1,
+        }
+        Some(c)
+    }
+
+    /// Consumes the next character if it is the expected one.
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the expected character, or returns an error.
+    fn expect(&mut self, expected: char, context: &str) -> Result<(), ParseError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected {:?} {}", expected, context)))
+        }
+    }
+
+    /// Skips spaces and tabs.
+    fn skip_whitespaces(&mut self) {
+        while self.eat(' ') || self.eat('\t') {}
+    }
+
+    /// Skips a comment if available.
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\r') | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Parses the next statement.
+    ///
+    /// Returns `Ok(None)` if the input is exhausted.
+    fn next_statement(&mut self) -> Result<Option<Quad>, ParseError> {
+        // Skip empty lines and comments.
+        loop {
+            self.skip_whitespaces();
+            self.skip_comment();
+            match self.peek() {
+                None => return Ok(None),
+                Some('\r') | Some('\n') => {
+                    self.bump();
+                }
+                Some(_) => break,
+            }
+        }
+
+        let subject = match self.peek() {
+            Some('<') => self.parse_iri()?,
+            Some('_') => self.parse_blank_node()?,
+            _ => return Err(self.error("Expected an IRI or a blank node as a subject")),
+        };
+        self.skip_whitespaces();
+        let predicate = match self.peek() {
+            Some('<') => self.parse_iri()?,
+            Some('_') => self.parse_blank_node()?,
+            _ => return Err(self.error("Expected an IRI as a predicate")),
+        };
+        self.skip_whitespaces();
+        let object = match self.peek() {
+            Some('<') => self.parse_iri()?,
+            Some('_') => self.parse_blank_node()?,
+            Some('"') => self.parse_literal()?,
+            _ => return Err(self.error("Expected an IRI, a blank node, or a literal as an object")),
+        };
+        self.skip_whitespaces();
+        let graph_name = match self.peek() {
+            Some('<') => Some(self.parse_iri()?),
+            Some('_') => Some(self.parse_blank_node()?),
+            _ => None,
+        };
+        self.skip_whitespaces();
+        self.expect('.', "at the end of the statement")?;
+        self.skip_whitespaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\r') | Some('\n') => {}
+            Some(_) => return Err(self.error("Expected the end of line after the statement")),
+        }
+
+        Ok(Some(Quad::new(subject, predicate, object, graph_name)))
+    }
+
+    /// Parses `IRIREF`.
+    fn parse_iri(&mut self) -> Result<Term, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.expect('<', "at the start of the IRI")?;
+        let mut iri = String::new();
+        loop {
+            match self.peek() {
+                Some('>') => {
+                    self.bump();
+                    break;
+                }
+                Some('\\') => {
+                    self.bump();
+                    iri.push(self.parse_uchar()?);
+                }
+                Some(c @ '\u{00}'..='\u{20}')
+                | Some(c @ '<')
+                | Some(c @ '"')
+                | Some(c @ '{')
+                | Some(c @ '}')
+                | Some(c @ '|')
+                | Some(c @ '^')
+                | Some(c @ '`') => {
+                    return Err(self.error(format!("Unexpected character {:?} in the IRI", c)))
+                }
+                Some(c) => {
+                    self.bump();
+                    iri.push(c);
+                }
+                None => return Err(self.error("Unexpected end of input in the IRI")),
+            }
+        }
+        if !has_scheme(&iri) {
+            return Err(ParseError {
+                line,
+                column,
+                message: format!("Expected an absolute IRI, but got {:?}", iri),
+            });
+        }
+        Ok(Term::Iri(iri))
+    }
+
+    /// Parses `BLANK_NODE_LABEL`.
+    fn parse_blank_node(&mut self) -> Result<Term, ParseError> {
+        self.expect('_', "at the start of the blank node")?;
+        self.expect(':', "after `_` of the blank node")?;
+        let mut label = String::new();
+        match self.peek() {
+            Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => {
+                self.bump();
+                label.push(c);
+            }
+            _ => return Err(self.error("Expected a blank node label")),
+        }
+        loop {
+            match self.peek() {
+                Some(c) if is_pn_chars(c) => {
+                    self.bump();
+                    label.push(c);
+                }
+                Some('.') => {
+                    // The label cannot end with `.`.
+                    let after_dots = self.rest.trim_start_matches('.');
+                    if !after_dots.starts_with(is_pn_chars) {
+                        break;
+                    }
+                    self.bump();
+                    label.push('.');
+                }
+                _ => break,
+            }
+        }
+        Ok(Term::BlankNode(label))
+    }
+
+    /// Parses a literal.
+    fn parse_literal(&mut self) -> Result<Term, ParseError> {
+        let lexical_form = self.parse_string()?;
+        let literal = match self.peek() {
+            Some('@') => {
+                self.bump();
+                Literal::with_language(lexical_form, self.parse_language_tag()?)
+            }
+            Some('^') => {
+                self.bump();
+                self.expect('^', "for the datatype IRI")?;
+                match self.parse_iri()? {
+                    Term::Iri(datatype) => Literal::new(lexical_form, datatype),
+                    _ => unreachable!("Should never fail: `parse_iri()` returns an IRI"),
+                }
+            }
+            _ => Literal::simple(lexical_form),
+        };
+        Ok(literal.into())
+    }
+
+    /// Parses `STRING_LITERAL_QUOTE`.
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"', "at the start of the string")?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.bump();
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.bump();
+                    let c = match self.peek() {
+                        Some('t') => '\t',
+                        Some('b') => '\u{08}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('f') => '\u{0C}',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('\\') => '\\',
+                        Some('u') | Some('U') => {
+                            s.push(self.parse_uchar()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("Invalid escape sequence in the string")),
+                    };
+                    self.bump();
+                    s.push(c);
+                }
+                Some('\r') | Some('\n') => {
+                    return Err(self.error("Unexpected line break in the string"))
+                }
+                Some(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+                None => return Err(self.error("Unexpected end of input in the string")),
+            }
+        }
+    }
+
+    /// Parses `UCHAR` after the backslash.
+    fn parse_uchar(&mut self) -> Result<char, ParseError> {
+        let (line, column) = (self.line, self.column);
+        let len = match self.peek() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.error("Expected `\\u` or `\\U` escape sequence")),
+        };
+        self.bump();
+        let mut code = 0;
+        for _ in 0..len {
+            let digit = self
+                .peek()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("Expected a hexadecimal digit"))?;
+            self.bump();
+            code = code * 16 + digit;
+        }
+        std::char::from_u32(code).ok_or(ParseError {
+            line,
+            column,
+            message: format!("Invalid code point U+{:X}", code),
+        })
+    }
+
+    /// Parses `LANGTAG` after `@`.
+    fn parse_language_tag(&mut self) -> Result<String, ParseError> {
+        let mut tag = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            self.bump();
+            tag.push(c);
+        }
+        if tag.is_empty() {
+            return Err(self.error("Expected a language tag"));
+        }
+        while self.peek() == Some('-') {
+            self.bump();
+            tag.push('-');
+            let start_len = tag.len();
+            while let Some(c) = self.peek().filter(char::is_ascii_alphanumeric) {
+                self.bump();
+                tag.push(c);
+            }
+            if tag.len() == start_len {
+                return Err(self.error("Expected a subtag of the language tag"));
+            }
+        }
+        Ok(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rdf::vocab::XSD_INTEGER;
+
+    #[test]
+    fn parse_statements() {
+        let input = "# comment\n\
+            <http://example.org/s> <http://example.org/p> <http://example.org/o> .\n\
+            \n\
+            _:b0 <http://example.org/p> \"a\\tb\\u00E9\\U0001F600\" <http://example.org/g> . # c\r\n\
+            _:b.0 <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> _:g.\n\
+            <http://example.org/s>\t<http://example.org/p> \"hi\"@en-US .";
+        let dataset = parse(input).expect("Should never fail: valid N-Quads");
+        let s = Term::iri("http://example.org/s");
+        let p = Term::iri("http://example.org/p");
+        assert_eq!(
+            dataset.quads(),
+            &[
+                Quad::new(
+                    s.clone(),
+                    p.clone(),
+                    Term::iri("http://example.org/o"),
+                    None
+                ),
+                Quad::new(
+                    Term::blank_node("b0"),
+                    p.clone(),
+                    Literal::simple("a\tb\u{e9}\u{1F600}").into(),
+                    Some(Term::iri("http://example.org/g")),
+                ),
+                Quad::new(
+                    Term::blank_node("b.0"),
+                    p.clone(),
+                    Literal::new("1", XSD_INTEGER).into(),
+                    Some(Term::blank_node("g")),
+                ),
+                Quad::new(s, p, Literal::with_language("hi", "en-US").into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = &[
+            ("<http://example.org/s> <http://example.org/p> .", 1, 47),
+            ("<s> <http://example.org/p> <http://example.org/o> .", 1, 1),
+            (
+                "\n<http://example.org/s> <http://example.org/p> \"a\n\" .",
+                2,
+                49,
+            ),
+            (
+                "<http://example.org/s> <http://example.org/p> <http://example.org/o>",
+                1,
+                69,
+            ),
+            (
+                "<http://example.org/s> <http://example.org/p> \"x\"@ .",
+                1,
+                51,
+            ),
+            (
+                "<http://example.org/s> <http://example.org/p> <http://example.org/o> . x",
+                1,
+                72,
+            ),
+        ];
+        for (input, line, column) in cases {
+            let err = parse(input).expect_err("Should never fail: invalid N-Quads");
+            assert_eq!(
+                (err.line(), err.column()),
+                (*line, *column),
+                "input={:?}, err={}",
+                input,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn serialize_and_parse() {
+        let dataset = vec![
+            Quad::new(
+                Term::iri("http://example.org/s"),
+                Term::iri("http://example.org/p"),
+                Literal::simple("\"q\"\\\n\r\t\u{08}\u{0C}\u{01}\u{7F}\u{e9}").into(),
+                None,
+            ),
+            Quad::new(
+                Term::blank_node("b0"),
+                Term::iri("http://example.org/p"),
+                Literal::new("1", XSD_INTEGER).into(),
+                Some(Term::iri("http://example.org/g")),
+            ),
+            Quad::new(
+                Term::blank_node("b0"),
+                Term::iri("http://example.org/p"),
+                Literal::with_language("x", "en").into(),
+                Some(Term::blank_node("g")),
+            ),
+        ]
+        .into_iter()
+        .collect::<Dataset>();
+        let serialized = serialize(&dataset);
+        assert_eq!(
+            serialized,
+            "<http://example.org/s> <http://example.org/p> \
+             \"\\\"q\\\"\\\\\\n\\r\\t\\b\\f\\u0001\\u007F\u{e9}\" .\n\
+             _:b0 <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> \
+             <http://example.org/g> .\n\
+             _:b0 <http://example.org/p> \"x\"@en _:g .\n"
+        );
+        assert_eq!(
+            parse(&serialized).expect("Should never fail: valid N-Quads"),
+            dataset
+        );
+    }
+}
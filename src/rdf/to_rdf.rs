@@ -0,0 +1,529 @@
+//! Deserialization of JSON-LD to RDF.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#deserialize-json-ld-to-rdf-algorithm>.
+
+use serde_json::{Number, Value};
+
+use crate::{
+    error::Result,
+    expand,
+    flatten::{BlankNodeIdIssuer, NodeMap},
+    iri::{is_absolute_iri_ref, is_blank_node_ident},
+    json::to_canonical_string,
+    processor::{Processor, ProcessorOptions},
+    rdf::{
+        vocab::{
+            I18N_PREFIX, RDF_DIRECTION, RDF_FIRST, RDF_JSON, RDF_LANGUAGE, RDF_LANG_STRING,
+            RDF_NIL, RDF_REST, RDF_TYPE, RDF_VALUE, XSD_BOOLEAN, XSD_DOUBLE, XSD_INTEGER,
+            XSD_STRING,
+        },
+        Dataset, Literal, Quad, RdfDirection, Term,
+    },
+    remote::LoadRemoteDocument,
+    syntax::{has_form_of_keyword, is_well_formed_language_tag},
+};
+
+/// RDF triple.
+type Triple = (Term, Term, Term);
+
+/// Deserializes the given JSON-LD document to an RDF dataset.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-jsonldprocessor-tordf>.
+pub(crate) async fn to_rdf<L: LoadRemoteDocument>(
+    processor: &Processor<L>,
+    input: &Value,
+) -> Result<Dataset> {
+    // Step 2-4
+    let expanded_input = expand::expand(processor, input).await?;
+    // Step 5, 6
+    let mut issuer = BlankNodeIdIssuer::default();
+    let mut node_map = NodeMap::new();
+    node_map.add_element(&mut issuer, &expanded_input)?;
+    // Step 7
+    Ok(node_map_to_rdf(processor.options(), &mut issuer, &node_map))
+}
+
+/// Runs deserialize JSON-LD to RDF algorithm.
+///
+/// `issuer` should be the one used to generate the node map.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#deserialize-json-ld-to-rdf-algorithm>.
+pub(crate) fn node_map_to_rdf(
+    options: &ProcessorOptions,
+    issuer: &mut BlankNodeIdIssuer,
+    node_map: &NodeMap,
+) -> Dataset {
+    let mut converter = Converter { options, issuer };
+    let mut dataset = Dataset::new();
+    // Step 1
+    for (graph_name, graph) in node_map.graphs() {
+        // Step 1.1
+        let graph_name = if graph_name == "@default" {
+            None
+        } else {
+            match to_node_term(graph_name) {
+                Some(term) => Some(term),
+                None => continue,
+            }
+        };
+        // Step 1.2
+        let mut triples = Vec::new();
+        // Step 1.3
+        for (subject, node) in graph {
+            // Step 1.3.1
+            let subject = match to_node_term(subject) {
+                Some(term) => term,
+                None => continue,
+            };
+            // Step 1.3.2
+            for (property, values) in node {
+                if property == "@type" {
+                    // Step 1.3.2.1
+                    let types = values
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .filter_map(to_node_term);
+                    for ty in types {
+                        triples.push((subject.clone(), Term::iri(RDF_TYPE), ty));
+                    }
+                } else if has_form_of_keyword(property) {
+                    // Step 1.3.2.2
+                } else if is_blank_node_ident(property) && !options.produce_generalized_rdf() {
+                    // Step 1.3.2.3
+                } else if let Some(predicate) = to_node_term(property) {
+                    // Step 1.3.2.5
+                    for item in values.as_array().into_iter().flatten() {
+                        // Step 1.3.2.5.1
+                        let mut list_triples = Vec::new();
+                        // Step 1.3.2.5.2
+                        if let Some(object) = converter.object_to_rdf(item, &mut list_triples) {
+                            triples.push((subject.clone(), predicate.clone(), object));
+                        }
+                        // Step 1.3.2.5.3
+                        triples.append(&mut list_triples);
+                    }
+                }
+                // Step 1.3.2.4: Properties which are not well-formed are ignored.
+            }
+        }
+        // Step 1.4
+        dataset.extend(
+            triples
+                .into_iter()
+                .map(|(s, p, o)| Quad::new(s, p, o, graph_name.clone())),
+        );
+    }
+
+    dataset
+}
+
+/// Converter of JSON-LD values into RDF terms.
+struct Converter<'a> {
+    /// Processor options.
+    options: &'a ProcessorOptions,
+    /// Blank node identifier issuer.
+    issuer: &'a mut BlankNodeIdIssuer,
+}
+
+impl Converter<'_> {
+    /// Runs object to RDF conversion algorithm.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#object-to-rdf-conversion>.
+    fn object_to_rdf(&mut self, item: &Value, list_triples: &mut Vec<Triple>) -> Option<Term> {
+        let item = item.as_object()?;
+        // Step 1, 2
+        if let Some(id) = item.get("@id") {
+            return to_node_term(id.as_str()?);
+        }
+        // Step 3
+        if let Some(list) = item.get("@list") {
+            let list = list.as_array().map_or(&[][..], Vec::as_slice);
+            return Some(self.list_to_rdf(list, list_triples));
+        }
+        // Step 4
+        let value = item.get("@value")?;
+        // Step 5
+        let datatype = item.get("@type").and_then(Value::as_str);
+        // Step 6
+        if let Some(datatype) = datatype {
+            if datatype != "@json" && !is_absolute_iri_ref(datatype) {
+                log::warn!("Dropping a literal with the datatype {:?}", datatype);
+                return None;
+            }
+        }
+        // Step 7
+        let language = item.get("@language").and_then(Value::as_str);
+        if let Some(language) = language {
+            if !is_well_formed_language_tag(language) {
+                log::warn!("Dropping a literal with the language tag {:?}", language);
+                return None;
+            }
+        }
+        let (value, datatype) = match value {
+            // Step 8
+            v if datatype == Some("@json") => (to_canonical_string(v), RDF_JSON),
+            // Step 9
+            Value::Bool(v) => (v.to_string(), datatype.unwrap_or(XSD_BOOLEAN)),
+            // Step 10
+            Value::Number(v) if datatype == Some(XSD_DOUBLE) || !is_integer_like(v) => {
+                (to_canonical_double(v), datatype.unwrap_or(XSD_DOUBLE))
+            }
+            // Step 11
+            Value::Number(v) => (to_canonical_integer(v), datatype.unwrap_or(XSD_INTEGER)),
+            // Step 12
+            Value::String(v) => {
+                let default = if language.is_some() {
+                    RDF_LANG_STRING
+                } else {
+                    XSD_STRING
+                };
+                (v.clone(), datatype.unwrap_or(default))
+            }
+            _ => return None,
+        };
+        // Step 13
+        let direction = item.get("@direction").and_then(Value::as_str);
+        if let (Some(direction), Some(rdf_direction)) = (direction, self.options.rdf_direction()) {
+            // Step 13.1
+            let language = language.unwrap_or_default().to_lowercase();
+            match rdf_direction {
+                // Step 13.2
+                RdfDirection::I18nDatatype => {
+                    let datatype = format!("{}{}_{}", I18N_PREFIX, language, direction);
+                    return Some(Literal::new(value, datatype).into());
+                }
+                // Step 13.3
+                RdfDirection::CompoundLiteral => {
+                    // Step 13.3.1
+                    let literal = Term::from_iri_or_blank_node(&self.issuer.issue(None));
+                    // Step 13.3.2
+                    list_triples.push((
+                        literal.clone(),
+                        Term::iri(RDF_VALUE),
+                        Literal::simple(value).into(),
+                    ));
+                    // Step 13.3.3
+                    if item.contains_key("@language") {
+                        list_triples.push((
+                            literal.clone(),
+                            Term::iri(RDF_LANGUAGE),
+                            Literal::simple(language).into(),
+                        ));
+                    }
+                    // Step 13.3.4
+                    list_triples.push((
+                        literal.clone(),
+                        Term::iri(RDF_DIRECTION),
+                        Literal::simple(direction).into(),
+                    ));
+                    return Some(literal);
+                }
+            }
+        }
+        // Step 14
+        let literal = match language {
+            Some(language) if datatype == RDF_LANG_STRING => {
+                Literal::with_language(value, language)
+            }
+            _ => Literal::new(value, datatype),
+        };
+
+        // Step 15
+        Some(literal.into())
+    }
+
+    /// Runs list conversion algorithm.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#list-to-rdf-conversion>.
+    fn list_to_rdf(&mut self, list: &[Value], list_triples: &mut Vec<Triple>) -> Term {
+        // Step 1
+        if list.is_empty() {
+            return Term::iri(RDF_NIL);
+        }
+        // Step 2
+        let bnodes = list
+            .iter()
+            .map(|_| Term::from_iri_or_blank_node(&self.issuer.issue(None)))
+            .collect::<Vec<_>>();
+        // Step 3
+        for (i, (subject, item)) in bnodes.iter().zip(list).enumerate() {
+            // Step 3.1
+            let mut embedded_triples = Vec::new();
+            // Step 3.2
+            let object = self.object_to_rdf(item, &mut embedded_triples);
+            // Step 3.3
+            if let Some(object) = object {
+                list_triples.push((subject.clone(), Term::iri(RDF_FIRST), object));
+            }
+            // Step 3.4
+            let rest = bnodes
+                .get(i + 1)
+                .cloned()
+                .unwrap_or_else(|| Term::iri(RDF_NIL));
+            list_triples.push((subject.clone(), Term::iri(RDF_REST), rest));
+            // Step 3.5
+            list_triples.append(&mut embedded_triples);
+        }
+
+        // Step 4
+        bnodes
+            .into_iter()
+            .next()
+            .expect("Should never fail: the list is not empty")
+    }
+}
+
+/// Converts the given IRI or blank node identifier to an RDF term.
+///
+/// Returns `None` if the string is not well-formed, such as a relative IRI.
+fn to_node_term(s: &str) -> Option<Term> {
+    if is_blank_node_ident(s) || is_absolute_iri_ref(s) {
+        Some(Term::from_iri_or_blank_node(s))
+    } else {
+        log::warn!("Dropping a relative IRI {:?}", s);
+        None
+    }
+}
+
+/// Checks if the given number has no non-zero fractional part and is less than 10^21.
+fn is_integer_like(v: &Number) -> bool {
+    if v.is_i64() || v.is_u64() {
+        return true;
+    }
+    let v = v
+        .as_f64()
+        .expect("Should never fail: JSON number can be converted to `f64`");
+    v.fract() == 0.0 && v.abs() < 1e21
+}
+
+/// Converts the number to the canonical lexical form of `xsd:integer`.
+fn to_canonical_integer(v: &Number) -> String {
+    if let Some(v) = v.as_i64() {
+        return v.to_string();
+    }
+    if let Some(v) = v.as_u64() {
+        return v.to_string();
+    }
+    let v = v
+        .as_f64()
+        .expect("Should never fail: JSON number can be converted to `f64`");
+    format!("{:.0}", v)
+}
+
+/// Converts the number to the canonical lexical form of `xsd:double`.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#data-round-tripping>.
+fn to_canonical_double(v: &Number) -> String {
+    let v = v
+        .as_f64()
+        .expect("Should never fail: JSON number can be converted to `f64`");
+    let repr = format!("{:.15E}", v);
+    let (mantissa, exponent) = repr
+        .split_once('E')
+        .expect("Should never fail: `{:E}` format has an exponent");
+    let mantissa = mantissa.trim_end_matches('0');
+    let zero = if mantissa.ends_with('.') { "0" } else { "" };
+    format!("{}{}E{}", mantissa, zero, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    use crate::test_utils::{block_on, processor, NoLoader};
+
+    /// Converts the document to a dataset, and returns the quads as tuples of strings.
+    fn to_rdf(input: Value) -> Vec<(String, String, String, Option<String>)> {
+        to_rdf_with(processor().options().clone(), input)
+    }
+
+    /// Converts the document to a dataset using the given options, and returns the quads as
+    /// tuples of strings.
+    fn to_rdf_with(
+        options: ProcessorOptions,
+        input: Value,
+    ) -> Vec<(String, String, String, Option<String>)> {
+        let to_string = |term: &Term| match term {
+            Term::Iri(iri) => format!("<{}>", iri),
+            Term::BlankNode(label) => format!("_:{}", label),
+            Term::Literal(lit) => match lit.language() {
+                Some(lang) => format!("{:?}@{}", lit.lexical_form(), lang),
+                None => format!("{:?}^^<{}>", lit.lexical_form(), lit.datatype()),
+            },
+        };
+        block_on(options.build(NoLoader).to_rdf(&input))
+            .expect("Should never fail: valid input")
+            .iter()
+            .map(|quad| {
+                (
+                    to_string(quad.subject()),
+                    to_string(quad.predicate()),
+                    to_string(quad.object()),
+                    quad.graph_name().map(to_string),
+                )
+            })
+            .collect()
+    }
+
+    /// Creates an expected quad.
+    fn quad(
+        s: &str,
+        p: &str,
+        o: &str,
+        g: Option<&str>,
+    ) -> (String, String, String, Option<String>) {
+        (s.into(), p.into(), o.into(), g.map(Into::into))
+    }
+
+    #[test]
+    fn canonical_double() {
+        let double = |v: f64| Number::from_f64(v).expect("Should never fail: finite number");
+        let cases = &[
+            (double(1.1), "1.1E0"),
+            (Number::from(1), "1.0E0"),
+            (double(0.0), "0.0E0"),
+            (double(-0.00123), "-1.23E-3"),
+            (double(1e21), "1.0E21"),
+            (double(123_456.789), "1.23456789E5"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(to_canonical_double(value), *expected, "value={:?}", value);
+        }
+    }
+
+    #[test]
+    fn literals() {
+        let input = json!({
+            "@id": "http://example.org/a",
+            "@type": "http://example.org/T",
+            "http://example.org/p": [
+                "str",
+                { "@value": "hello", "@language": "en" },
+                true,
+                1,
+                1.5,
+                1e21,
+                { "@value": 2, "@type": "http://www.w3.org/2001/XMLSchema#double" },
+                { "@value": "x", "@type": "http://example.org/dt" },
+                { "@value": { "b": 1, "a": [null] }, "@type": "@json" }
+            ]
+        });
+        let s = "<http://example.org/a>";
+        let p = "<http://example.org/p>";
+        let xsd = |local: &str| format!("^^<http://www.w3.org/2001/XMLSchema#{}>", local);
+        assert_eq!(
+            to_rdf(input),
+            vec![
+                quad(
+                    s,
+                    "<http://www.w3.org/1999/02/22-rdf-syntax-ns#type>",
+                    "<http://example.org/T>",
+                    None
+                ),
+                quad(s, p, &format!("\"str\"{}", xsd("string")), None),
+                quad(s, p, "\"hello\"@en", None),
+                quad(s, p, &format!("\"true\"{}", xsd("boolean")), None),
+                quad(s, p, &format!("\"1\"{}", xsd("integer")), None),
+                quad(s, p, &format!("\"1.5E0\"{}", xsd("double")), None),
+                quad(s, p, &format!("\"1.0E21\"{}", xsd("double")), None),
+                quad(s, p, &format!("\"2.0E0\"{}", xsd("double")), None),
+                quad(s, p, "\"x\"^^<http://example.org/dt>", None),
+                quad(
+                    s,
+                    p,
+                    "\"{\\\"a\\\":[null],\\\"b\\\":1}\"\
+                     ^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON>",
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_and_graphs() {
+        let input = json!({
+            "@context": { "@base": null },
+            "@id": "http://example.org/g",
+            "@graph": {
+                "@id": "_:x",
+                "http://example.org/list": { "@list": [1, { "@id": "http://example.org/b" }] },
+                "http://example.org/empty": { "@list": [] },
+                "http://example.org/relative": { "@id": "relative" }
+            }
+        });
+        let g = Some("<http://example.org/g>");
+        let rdf = |local: &str| format!("<http://www.w3.org/1999/02/22-rdf-syntax-ns#{}>", local);
+        assert_eq!(
+            to_rdf(input),
+            vec![
+                quad("_:b0", "<http://example.org/empty>", &rdf("nil"), g),
+                quad("_:b0", "<http://example.org/list>", "_:b1", g),
+                quad(
+                    "_:b1",
+                    &rdf("first"),
+                    "\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                    g
+                ),
+                quad("_:b1", &rdf("rest"), "_:b2", g),
+                quad("_:b2", &rdf("first"), "<http://example.org/b>", g),
+                quad("_:b2", &rdf("rest"), &rdf("nil"), g),
+            ]
+        );
+    }
+
+    #[test]
+    fn rdf_direction() {
+        let input = json!({
+            "@id": "http://example.org/a",
+            "http://example.org/p": [
+                { "@value": "hello", "@language": "en-US", "@direction": "rtl" },
+                { "@value": "world", "@direction": "ltr" }
+            ]
+        });
+        let s = "<http://example.org/a>";
+        let p = "<http://example.org/p>";
+        let rdf = |local: &str| format!("<http://www.w3.org/1999/02/22-rdf-syntax-ns#{}>", local);
+        let string = |v: &str| format!("{:?}^^<http://www.w3.org/2001/XMLSchema#string>", v);
+
+        assert_eq!(
+            to_rdf(input.clone()),
+            vec![
+                quad(s, p, "\"hello\"@en-US", None),
+                quad(s, p, &string("world"), None),
+            ]
+        );
+
+        let mut options = processor().options().clone();
+        options.set_rdf_direction(Some(RdfDirection::I18nDatatype));
+        assert_eq!(
+            to_rdf_with(options.clone(), input.clone()),
+            vec![
+                quad(
+                    s,
+                    p,
+                    "\"hello\"^^<https://www.w3.org/ns/i18n#en-us_rtl>",
+                    None
+                ),
+                quad(s, p, "\"world\"^^<https://www.w3.org/ns/i18n#_ltr>", None),
+            ]
+        );
+
+        options.set_rdf_direction(Some(RdfDirection::CompoundLiteral));
+        assert_eq!(
+            to_rdf_with(options, input),
+            vec![
+                quad(s, p, "_:b0", None),
+                quad("_:b0", &rdf("value"), &string("hello"), None),
+                quad("_:b0", &rdf("language"), &string("en-us"), None),
+                quad("_:b0", &rdf("direction"), &string("rtl"), None),
+                quad(s, p, "_:b1", None),
+                quad("_:b1", &rdf("value"), &string("world"), None),
+                quad("_:b1", &rdf("direction"), &string("ltr"), None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! IRIs of RDF-related vocabularies.
+
+/// Defines IRI constants with the common prefix.
+macro_rules! define_iris {
+    ($prefix:expr; $($(#[$meta:meta])* $name:ident = $local:expr;)*) => {
+        $(
+            $(#[$meta])*
+            pub(crate) const $name: &str = concat!($prefix, $local);
+        )*
+    };
+}
+
+define_iris! {
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+    /// `rdf:direction`.
+    RDF_DIRECTION = "direction";
+    /// `rdf:first`.
+    RDF_FIRST = "first";
+    /// `rdf:JSON`.
+    RDF_JSON = "JSON";
+    /// `rdf:langString`.
+    RDF_LANG_STRING = "langString";
+    /// `rdf:language`.
+    RDF_LANGUAGE = "language";
+    /// `rdf:List`.
+    RDF_LIST = "List";
+    /// `rdf:nil`.
+    RDF_NIL = "nil";
+    /// `rdf:rest`.
+    RDF_REST = "rest";
+    /// `rdf:type`.
+    RDF_TYPE = "type";
+    /// `rdf:value`.
+    RDF_VALUE = "value";
+}
+
+define_iris! {
+    "http://www.w3.org/2001/XMLSchema#";
+    /// `xsd:boolean`.
+    XSD_BOOLEAN = "boolean";
+    /// `xsd:double`.
+    XSD_DOUBLE = "double";
+    /// `xsd:integer`.
+    XSD_INTEGER = "integer";
+    /// `xsd:string`.
+    XSD_STRING = "string";
+}
+
+/// Prefix of datatype IRIs for strings with base direction.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#the-i18n-namespace>.
+pub(crate) const I18N_PREFIX: &str = "https://www.w3.org/ns/i18n#";
@@ -2,13 +2,43 @@
 
 use std::sync::Arc;
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use iri_string::types::IriStr;
 use serde_json::Value;
 
-pub use self::profile::{Profile, RequestProfile};
+use crate::error::{Error, ErrorCode};
 
+pub use self::{
+    cache::CachingLoader,
+    fs::FsLoader,
+    html::{extract_json_ld, HtmlDocument},
+    policy::{PolicyError, PolicyLoader},
+    profile::{Profile, RequestProfile},
+    response::{
+        is_json_media_type, parse_link_header, process_response, JsonResponse, Link, ResponseAction,
+    },
+    static_loader::StaticLoader,
+};
+
+#[cfg(feature = "http-loader")]
+pub use self::http::HttpLoader;
+
+mod cache;
+mod fs;
+mod html;
+#[cfg(feature = "http-loader")]
+mod http;
+mod policy;
 mod profile;
+mod response;
+mod static_loader;
+
+/// Media type of JSON-LD documents.
+pub(crate) const MEDIA_TYPE_JSON_LD: &str = "application/ld+json";
+
+/// Media type of JSON documents.
+pub(crate) const MEDIA_TYPE_JSON: &str = "application/json";
 
 /// A trait for types which can be used as remote document loader.
 ///
@@ -36,6 +66,8 @@ pub trait LoadRemoteDocument: Send + Sync {
     /// * Do network operations every time this method is called.
     /// * Cache result for every document loaders, but don't share the caches among other loaders.
     /// * Cache the data globally, and share caches among all loaders.
+    ///
+    /// [`CachingLoader`] can be used to add a cache shared among processors to any loader.
     async fn load(
         &self,
         iri: &IriStr,
@@ -62,7 +94,7 @@ pub struct LoadDocumentOptions {
     /// One or more profiles to use in the request as a `profile` parameter.
     ///
     /// > One or more IRIs to use in the request as a `profile` parameter. (See IANA Considerations
-    /// in \[JSON-LD11\]).
+    /// > in \[JSON-LD11\]).
     request_profile: RequestProfile,
 }
 
@@ -72,6 +104,11 @@ impl LoadDocumentOptions {
         Self::default()
     }
 
+    /// Sets whether the loader should extract all JSON-LD script elements in HTML.
+    pub(crate) fn set_extract_all_scripts(&mut self, extract_all_scripts: bool) {
+        self.extract_all_scripts = extract_all_scripts;
+    }
+
     /// Sets the given profile.
     pub(crate) fn set_profile(&mut self, profile: impl Into<Option<Profile>>) {
         self.profile = profile.into();
@@ -102,24 +139,74 @@ impl LoadDocumentOptions {
     /// Returns profiles to use in the request as a `profile` parameter.
     ///
     /// > One or more IRIs to use in the request as a `profile` parameter. (See IANA Considerations
-    /// in \[JSON-LD11\]).
+    /// > in \[JSON-LD11\]).
     pub fn request_profile(&self) -> RequestProfile {
         self.request_profile
     }
 }
 
 /// Remote document.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#remotedocument>.
 #[derive(Debug, Clone, PartialEq)]
 pub struct RemoteDocument {
+    /// Content type.
+    content_type: Option<String>,
     /// Context URL.
     context_url: Option<String>,
     /// Document IRI.
     document_url: String,
+    /// Profile.
+    profile: Option<String>,
+    /// Base IRI given by the document, if it differs from the document IRI.
+    base_iri: Option<String>,
     /// Document.
     document: Value,
 }
 
 impl RemoteDocument {
+    /// Creates a builder of a remote document.
+    ///
+    /// `document_url` should be the final URL of the document after redirects.
+    pub fn builder(document_url: impl Into<String>) -> RemoteDocumentBuilder {
+        RemoteDocumentBuilder::new(document_url)
+    }
+
+    /// Returns the content type.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-contenttype>.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Returns the context URL given by the HTTP `Link` header.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-contexturl>.
+    pub fn context_url(&self) -> Option<&str> {
+        self.context_url.as_deref()
+    }
+
+    /// Returns the document URL, which is the final URL after redirects.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-documenturl>.
+    pub fn document_url(&self) -> &str {
+        &self.document_url
+    }
+
+    /// Returns the profile.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-profile>.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Returns the base IRI of the document.
+    ///
+    /// This is the document URL, or the IRI given by the `<base>` element for HTML documents.
+    pub fn base_iri(&self) -> &str {
+        self.base_iri.as_deref().unwrap_or(&self.document_url)
+    }
+
     /// Returns a reference to the document.
     pub fn document(&self) -> &Value {
         &self.document
@@ -130,3 +217,80 @@ impl RemoteDocument {
         self.document
     }
 }
+
+/// Builder of `RemoteDocument`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteDocumentBuilder {
+    /// Content type.
+    content_type: Option<String>,
+    /// Context URL.
+    context_url: Option<String>,
+    /// Document IRI.
+    document_url: String,
+    /// Profile.
+    profile: Option<String>,
+}
+
+impl RemoteDocumentBuilder {
+    /// Creates a new builder.
+    fn new(document_url: impl Into<String>) -> Self {
+        Self {
+            content_type: None,
+            context_url: None,
+            document_url: document_url.into(),
+            profile: None,
+        }
+    }
+
+    /// Sets the content type.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the context URL given by the HTTP `Link` header.
+    pub fn context_url(mut self, context_url: impl Into<String>) -> Self {
+        self.context_url = Some(context_url.into());
+        self
+    }
+
+    /// Sets the profile.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Creates a remote document with the JSON document.
+    pub fn json(self, document: Value) -> RemoteDocument {
+        RemoteDocument {
+            content_type: self.content_type,
+            context_url: self.context_url,
+            document_url: self.document_url,
+            profile: self.profile,
+            base_iri: None,
+            document,
+        }
+    }
+
+    /// Creates a remote document with JSON-LD extracted from the HTML document.
+    ///
+    /// The document URL is used to select the script element by the fragment, and the
+    /// `<base>` element in HTML is used as the base IRI.
+    /// See [`extract_json_ld`](fn.extract_json_ld.html) for details.
+    pub fn html(self, html: &str, options: &LoadDocumentOptions) -> Result<RemoteDocument, Error> {
+        let document_url = IriStr::new(&self.document_url).map_err(|e| {
+            ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "Invalid document URL {:?}: {}",
+                self.document_url,
+                e
+            ))
+        })?;
+        let extracted = extract_json_ld(html, document_url, options)?;
+        let base_iri = extracted.base_iri().as_str().to_owned();
+        let mut document = self.json(extracted.into_document());
+        if base_iri != document.document_url {
+            document.base_iri = Some(base_iri);
+        }
+        Ok(document)
+    }
+}
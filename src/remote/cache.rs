@@ -0,0 +1,501 @@
+//! Caching document loader.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use iri_string::types::IriStr;
+
+use crate::remote::{LoadDocumentOptions, LoadRemoteDocument, RemoteDocument};
+
+/// Default maximum number of cached results.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Result of loading, shareable among callers.
+type LoadResult<E> = Result<Arc<RemoteDocument>, Arc<E>>;
+
+/// Cache key.
+type Key = (String, LoadDocumentOptions);
+
+/// Document loader wrapper which caches results of the inner loader.
+///
+/// The cache is shared by all processors using the same loader (for example via `Arc` or
+/// reference), and has the features below:
+///
+/// * Bounded size: the least recently used result is evicted when the cache is full
+///   (see [`set_capacity`]).
+/// * Optional expiry of successful results (see [`set_ttl`]).
+/// * Optional caching of failures (see [`set_failure_ttl`]).
+/// * Concurrent requests for the same document are coalesced into one request to the inner
+///   loader.
+///
+/// Documents are cached per pair of the IRI and the load options.
+/// Errors of the inner loader are wrapped by `Arc`, since a cached failure can be returned to
+/// multiple callers.
+///
+/// [`set_capacity`]: #method.set_capacity
+/// [`set_ttl`]: #method.set_ttl
+/// [`set_failure_ttl`]: #method.set_failure_ttl
+#[derive(Debug)]
+pub struct CachingLoader<L: LoadRemoteDocument> {
+    /// Inner loader.
+    loader: L,
+    /// Maximum number of cached results.
+    capacity: usize,
+    /// Lifetime of successful results.
+    ttl: Option<Duration>,
+    /// Lifetime of failures.
+    failure_ttl: Option<Duration>,
+    /// Cache state.
+    state: Mutex<State<L::Error>>,
+}
+
+impl<L: LoadRemoteDocument> CachingLoader<L> {
+    /// Creates a new caching loader.
+    ///
+    /// By default, the cache holds at most 256 results, successful results never expire, and
+    /// failures are not cached.
+    pub fn new(loader: L) -> Self {
+        Self {
+            loader,
+            capacity: DEFAULT_CAPACITY,
+            ttl: None,
+            failure_ttl: None,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Sets the maximum number of cached results.
+    ///
+    /// If `0` is given, results are not cached but concurrent requests are still coalesced.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let now = Instant::now();
+        let mut state = self.lock();
+        while state.entries.len() > capacity {
+            state.evict(now);
+        }
+    }
+
+    /// Sets the lifetime of successful results.
+    ///
+    /// If `None` is given, successful results are kept until evicted.
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+
+    /// Sets the lifetime of failures.
+    ///
+    /// If `None` is given, failures are not cached and every request after a failure is passed
+    /// to the inner loader.
+    pub fn set_failure_ttl(&mut self, ttl: Option<Duration>) {
+        self.failure_ttl = ttl;
+    }
+
+    /// Returns a reference to the inner loader.
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Locks the cache state.
+    fn lock(&self) -> MutexGuard<'_, State<L::Error>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stores the result and notifies the waiting requests.
+    fn finish(&self, key: Key, flight: &InFlight<L::Error>, result: LoadResult<L::Error>) {
+        let now = Instant::now();
+        let ttl = if result.is_ok() {
+            Some(self.ttl)
+        } else {
+            self.failure_ttl.map(Some)
+        };
+        let mut state = self.lock();
+        state.in_flight.remove(&key);
+        if let Some(ttl) = ttl {
+            if self.capacity > 0 {
+                if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+                    state.evict(now);
+                }
+                state.clock += 1;
+                let entry = Entry {
+                    result: result.clone(),
+                    expires_at: ttl.map(|ttl| now + ttl),
+                    last_used: state.clock,
+                };
+                state.entries.insert(key, entry);
+            }
+        }
+        drop(state);
+        flight.complete(SlotState::Done(result));
+    }
+}
+
+#[async_trait]
+impl<L: LoadRemoteDocument> LoadRemoteDocument for CachingLoader<L> {
+    type Error = Arc<L::Error>;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        let key = (iri.as_str().to_owned(), options.clone());
+        loop {
+            let role = {
+                let mut state = self.lock();
+                if let Some(result) = state.get(&key, Instant::now()) {
+                    return result;
+                }
+                match state.in_flight.get(&key) {
+                    Some(flight) => Role::Wait(flight.clone()),
+                    None => {
+                        let flight = Arc::new(InFlight::new());
+                        state.in_flight.insert(key.clone(), flight.clone());
+                        Role::Lead(flight)
+                    }
+                }
+            };
+            match role {
+                Role::Wait(flight) => {
+                    // If the leading request is cancelled, retry by ourselves.
+                    if let Some(result) = Wait(flight).await {
+                        return result;
+                    }
+                }
+                Role::Lead(flight) => {
+                    let mut guard = LeadGuard {
+                        loader: self,
+                        key: Some(key),
+                        flight,
+                    };
+                    let result = self.loader.load(iri, options).await.map_err(Arc::new);
+                    let key = guard
+                        .key
+                        .take()
+                        .expect("Should never fail: not finished yet");
+                    self.finish(key, &guard.flight, result.clone());
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Cache state.
+#[derive(Debug)]
+struct State<E> {
+    /// Cached results.
+    entries: HashMap<Key, Entry<E>>,
+    /// Requests being processed by the inner loader.
+    in_flight: HashMap<Key, Arc<InFlight<E>>>,
+    /// Counter to track recent uses.
+    clock: u64,
+}
+
+impl<E> Default for State<E> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            in_flight: HashMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<E> State<E> {
+    /// Returns the cached result if available.
+    fn get(&mut self, key: &Key, now: Instant) -> Option<LoadResult<E>> {
+        let is_expired = self.entries.get(key)?.is_expired(now);
+        if is_expired {
+            self.entries.remove(key);
+            return None;
+        }
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.result.clone())
+    }
+
+    /// Removes expired entries, or the least recently used entry if none are expired.
+    fn evict(&mut self, now: Instant) {
+        let len = self.entries.len();
+        self.entries.retain(|_, entry| !entry.is_expired(now));
+        if self.entries.len() < len {
+            return;
+        }
+        let lru = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = lru {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Cached result.
+#[derive(Debug)]
+struct Entry<E> {
+    /// Result.
+    result: LoadResult<E>,
+    /// Expiry time.
+    expires_at: Option<Instant>,
+    /// Last use, compared to `State::clock`.
+    last_used: u64,
+}
+
+impl<E> Entry<E> {
+    /// Returns true if the entry is expired.
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// Role of a request.
+enum Role<E> {
+    /// Loads the document by the inner loader.
+    Lead(Arc<InFlight<E>>),
+    /// Waits for the leading request.
+    Wait(Arc<InFlight<E>>),
+}
+
+/// Request being processed by the inner loader.
+#[derive(Debug)]
+struct InFlight<E> {
+    /// Slot for the result.
+    slot: Mutex<Slot<E>>,
+}
+
+impl<E> InFlight<E> {
+    /// Creates a new pending request.
+    fn new() -> Self {
+        Self {
+            slot: Mutex::new(Slot {
+                state: SlotState::Pending,
+                wakers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Sets the final state and wakes the waiting requests.
+    fn complete(&self, state: SlotState<E>) {
+        let wakers = {
+            let mut slot = self.slot.lock().unwrap_or_else(PoisonError::into_inner);
+            slot.state = state;
+            std::mem::take(&mut slot.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Result slot of a request.
+#[derive(Debug)]
+struct Slot<E> {
+    /// State.
+    state: SlotState<E>,
+    /// Wakers of the waiting requests.
+    wakers: Vec<Waker>,
+}
+
+/// State of a request.
+#[derive(Debug)]
+enum SlotState<E> {
+    /// Being processed.
+    Pending,
+    /// Finished.
+    Done(LoadResult<E>),
+    /// Cancelled before finished.
+    Abandoned,
+}
+
+/// Future waiting for the leading request.
+///
+/// This resolves to `None` if the leading request is cancelled.
+struct Wait<E>(Arc<InFlight<E>>);
+
+impl<E> Future for Wait<E> {
+    type Output = Option<LoadResult<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.0.slot.lock().unwrap_or_else(PoisonError::into_inner);
+        match &slot.state {
+            SlotState::Pending => {
+                slot.wakers.retain(|waker| !waker.will_wake(cx.waker()));
+                slot.wakers.push(cx.waker().clone());
+                Poll::Pending
+            }
+            SlotState::Done(result) => Poll::Ready(Some(result.clone())),
+            SlotState::Abandoned => Poll::Ready(None),
+        }
+    }
+}
+
+/// Guard to release waiting requests when the leading request is cancelled.
+struct LeadGuard<'a, L: LoadRemoteDocument> {
+    /// Loader.
+    loader: &'a CachingLoader<L>,
+    /// Key of the request, or `None` if finished.
+    key: Option<Key>,
+    /// Request.
+    flight: Arc<InFlight<L::Error>>,
+}
+
+impl<L: LoadRemoteDocument> Drop for LeadGuard<'_, L> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.loader.lock().in_flight.remove(&key);
+            self.flight.complete(SlotState::Abandoned);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+        task::Wake,
+    };
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        error::{Error, ErrorCode},
+        test_utils::load,
+    };
+
+    /// Loader counting requests, which fails for IRIs containing `missing`.
+    #[derive(Default)]
+    struct CountingLoader {
+        /// Number of requests.
+        count: AtomicUsize,
+        /// Whether requests are blocked.
+        blocked: AtomicBool,
+        /// Wakers of the blocked requests.
+        wakers: Mutex<Vec<Waker>>,
+    }
+
+    impl CountingLoader {
+        /// Unblocks the requests.
+        fn unblock(&self) {
+            self.blocked.store(false, Ordering::SeqCst);
+            self.wakers.lock().unwrap().drain(..).for_each(Waker::wake);
+        }
+    }
+
+    /// Waker which does nothing.
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[async_trait]
+    impl LoadRemoteDocument for CountingLoader {
+        type Error = Error;
+
+        async fn load(
+            &self,
+            iri: &IriStr,
+            _options: LoadDocumentOptions,
+        ) -> Result<Arc<RemoteDocument>, Self::Error> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            std::future::poll_fn(|cx| {
+                if self.blocked.load(Ordering::SeqCst) {
+                    self.wakers.lock().unwrap().push(cx.waker().clone());
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            })
+            .await;
+            if iri.as_str().contains("missing") {
+                return Err(ErrorCode::LoadingDocumentFailed.into());
+            }
+            Ok(Arc::new(
+                RemoteDocument::builder(iri.as_str()).json(json!({})),
+            ))
+        }
+    }
+
+    #[test]
+    fn cache_results() {
+        let mut loader = CachingLoader::new(CountingLoader::default());
+        loader.set_capacity(2);
+        let count =
+            |loader: &CachingLoader<CountingLoader>| loader.loader().count.load(Ordering::SeqCst);
+
+        load(&loader, "http://example.com/a").unwrap();
+        load(&loader, "http://example.com/a").unwrap();
+        assert_eq!(count(&loader), 1);
+
+        // `b` is evicted as the least recently used one.
+        load(&loader, "http://example.com/b").unwrap();
+        load(&loader, "http://example.com/a").unwrap();
+        load(&loader, "http://example.com/c").unwrap();
+        load(&loader, "http://example.com/a").unwrap();
+        assert_eq!(count(&loader), 3);
+        load(&loader, "http://example.com/b").unwrap();
+        assert_eq!(count(&loader), 4);
+
+        // Failures are not cached by default.
+        load(&loader, "http://example.com/missing").unwrap_err();
+        load(&loader, "http://example.com/missing").unwrap_err();
+        assert_eq!(count(&loader), 6);
+        loader.set_failure_ttl(Some(Duration::from_secs(60)));
+        load(&loader, "http://example.com/missing").unwrap_err();
+        let err = load(&loader, "http://example.com/missing").unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+        assert_eq!(count(&loader), 7);
+
+        // Expired results are loaded again.
+        loader.clear();
+        loader.set_ttl(Some(Duration::from_secs(0)));
+        load(&loader, "http://example.com/a").unwrap();
+        load(&loader, "http://example.com/a").unwrap();
+        assert_eq!(count(&loader), 9);
+    }
+
+    #[test]
+    fn coalesce_concurrent_requests() {
+        let loader = CachingLoader::new(CountingLoader::default());
+        loader.loader().blocked.store(true, Ordering::SeqCst);
+        let iri = IriStr::new("http://example.com/a").expect("Should never fail: valid IRI");
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut first = loader.load(iri, LoadDocumentOptions::new());
+        let mut second = loader.load(iri, LoadDocumentOptions::new());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        loader.loader().unblock();
+        assert!(first.as_mut().poll(&mut cx).is_ready());
+        assert!(second.as_mut().poll(&mut cx).is_ready());
+        assert_eq!(loader.loader().count.load(Ordering::SeqCst), 1);
+
+        // A cancelled leading request lets the waiting one load the document by itself.
+        loader.clear();
+        loader.loader().blocked.store(true, Ordering::SeqCst);
+        let mut first = loader.load(iri, LoadDocumentOptions::new());
+        let mut second = loader.load(iri, LoadDocumentOptions::new());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        drop(first);
+        loader.loader().unblock();
+        assert!(second.as_mut().poll(&mut cx).is_ready());
+        assert_eq!(loader.loader().count.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,248 @@
+//! Filesystem-backed document loader.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use iri_string::types::IriStr;
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorCode},
+    remote::{
+        LoadDocumentOptions, LoadRemoteDocument, RemoteDocument, MEDIA_TYPE_JSON,
+        MEDIA_TYPE_JSON_LD,
+    },
+};
+
+/// Document loader which loads documents from local files.
+///
+/// IRIs are mapped to files in two ways:
+///
+/// * by the longest matching prefix registered by [`add_mapping`], and
+/// * for `file:` IRIs, if enabled by [`set_file_iri_enabled`].
+///
+/// Files with `.json` extension are loaded as plain JSON (`application/json`) documents, and
+/// other files are loaded as JSON-LD (`application/ld+json`) documents.
+///
+/// Note that files are read synchronously, so loading blocks the current thread.
+///
+/// [`add_mapping`]: #method.add_mapping
+/// [`set_file_iri_enabled`]: #method.set_file_iri_enabled
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct FsLoader {
+    /// Pairs of IRI prefixes and directories.
+    mappings: Vec<(String, PathBuf)>,
+    /// Whether `file:` IRIs are loaded.
+    file_iri_enabled: bool,
+    /// Context URL for plain JSON documents.
+    default_context_url: Option<String>,
+}
+
+impl FsLoader {
+    /// Creates a new loader without any mappings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps IRIs starting with the given prefix to files under the given directory.
+    ///
+    /// For example, if `http://example.com/contexts/` is mapped to `/srv/contexts`,
+    /// `http://example.com/contexts/foo/bar.jsonld` is loaded from
+    /// `/srv/contexts/foo/bar.jsonld`.
+    /// Query and fragment of IRIs are ignored, and IRIs containing `..` segments are rejected.
+    pub fn add_mapping(&mut self, prefix: impl Into<String>, dir: impl Into<PathBuf>) {
+        self.mappings.push((prefix.into(), dir.into()));
+    }
+
+    /// Sets whether `file:` IRIs are loaded.
+    ///
+    /// This is disabled by default, since it allows documents to read arbitrary local files.
+    pub fn set_file_iri_enabled(&mut self, enabled: bool) {
+        self.file_iri_enabled = enabled;
+    }
+
+    /// Sets the context URL for plain JSON documents.
+    ///
+    /// The context URL is set to documents loaded as `application/json`, as if it were given by
+    /// an HTTP `Link` header.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-contexturl>.
+    pub fn set_default_context_url(&mut self, context_url: Option<String>) {
+        self.default_context_url = context_url;
+    }
+
+    /// Returns the path of the file for the IRI.
+    fn resolve(&self, iri: &str) -> Result<PathBuf, Error> {
+        let iri = iri.split(&['?', '#'][..]).next().unwrap_or(iri);
+        let mapped = self
+            .mappings
+            .iter()
+            .filter(|(prefix, _)| iri.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, dir)| (&iri[prefix.len()..], dir.as_path()));
+        let (relative, dir) = match mapped {
+            Some(v) => v,
+            None => match iri.strip_prefix("file://") {
+                Some(path) if self.file_iri_enabled => {
+                    let path = path.strip_prefix("localhost").unwrap_or(path);
+                    if !path.starts_with('/') {
+                        return Err(ErrorCode::LoadingDocumentFailed
+                            .and_source(anyhow!("Unsupported `file:` IRI {:?}", iri)));
+                    }
+                    (path, Path::new("/"))
+                }
+                _ => {
+                    return Err(ErrorCode::LoadingDocumentFailed
+                        .and_source(anyhow!("No local files are mapped to the IRI {:?}", iri)))
+                }
+            },
+        };
+        let relative = PathBuf::from(percent_decode(relative)?);
+        let is_safe = relative.components().all(|c| match c {
+            Component::Normal(_) | Component::CurDir | Component::RootDir => true,
+            Component::ParentDir | Component::Prefix(_) => false,
+        });
+        if !is_safe {
+            return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "Unsafe path {:?} for the IRI {:?}",
+                relative,
+                iri
+            )));
+        }
+        let relative = relative.strip_prefix("/").unwrap_or(&relative);
+
+        Ok(dir.join(relative))
+    }
+}
+
+#[async_trait]
+impl LoadRemoteDocument for FsLoader {
+    type Error = Error;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        _options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        let path = self.resolve(iri.as_str())?;
+        let content = fs::read(&path).map_err(|e| {
+            ErrorCode::LoadingDocumentFailed
+                .and_source(e)
+                .context(format!("Failed to read {:?}", path))
+        })?;
+        let document: Value = serde_json::from_slice(&content).map_err(|e| {
+            ErrorCode::LoadingDocumentFailed
+                .and_source(e)
+                .context(format!("Failed to parse {:?} as JSON", path))
+        })?;
+        let is_plain_json = path.extension().is_some_and(|ext| ext == "json");
+        let (content_type, context_url) = if is_plain_json {
+            (MEDIA_TYPE_JSON, self.default_context_url.clone())
+        } else {
+            (MEDIA_TYPE_JSON_LD, None)
+        };
+
+        let builder = RemoteDocument::builder(iri.as_str()).content_type(content_type);
+        let builder = match context_url {
+            Some(context_url) => builder.context_url(context_url),
+            None => builder,
+        };
+
+        Ok(Arc::new(builder.json(document)))
+    }
+}
+
+/// Decodes percent-encoded octets in the path.
+fn percent_decode(s: &str) -> Result<String, Error> {
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            decoded.push(b);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let octet = match hex {
+            [Some(hi), Some(lo)] => std::str::from_utf8(&[hi, lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match octet {
+            Some(octet) => decoded.push(octet),
+            None => {
+                return Err(ErrorCode::LoadingDocumentFailed
+                    .and_source(anyhow!("Invalid percent encoding in {:?}", s)))
+            }
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|e| ErrorCode::LoadingDocumentFailed.and_source(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::load;
+
+    #[test]
+    fn load_files() {
+        let dir = env::temp_dir().join(format!("json-ld-fs-loader-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub dir")).unwrap();
+        fs::write(dir.join("sub dir/ctx.jsonld"), r#"{"@context": {}}"#).unwrap();
+        fs::write(dir.join("data.json"), r#"{"name": "x"}"#).unwrap();
+        fs::write(dir.join("broken.jsonld"), "{").unwrap();
+
+        let mut loader = FsLoader::new();
+        loader.add_mapping("http://example.com/", "/nonexistent");
+        loader.add_mapping("http://example.com/contexts/", &dir);
+        loader.set_default_context_url(Some("http://example.com/context.jsonld".to_owned()));
+
+        let doc = load(
+            &loader,
+            "http://example.com/contexts/sub%20dir/ctx.jsonld#frag",
+        )
+        .unwrap();
+        assert_eq!(doc.document(), &json!({ "@context": {} }));
+        assert_eq!(doc.content_type(), Some(MEDIA_TYPE_JSON_LD));
+        assert_eq!(doc.context_url(), None);
+
+        let doc = load(&loader, "http://example.com/contexts/data.json").unwrap();
+        assert_eq!(doc.content_type(), Some(MEDIA_TYPE_JSON));
+        assert_eq!(doc.context_url(), Some("http://example.com/context.jsonld"));
+
+        for iri in &[
+            "http://example.com/contexts/broken.jsonld",
+            "http://example.com/contexts/missing.jsonld",
+            "http://example.com/contexts/../secret.jsonld",
+            "http://example.org/ctx.jsonld",
+            "file:///etc/passwd",
+        ] {
+            let err = load(&loader, iri).expect_err("Should fail: unavailable document");
+            assert_eq!(
+                err.code(),
+                ErrorCode::LoadingDocumentFailed,
+                "iri={:?}",
+                iri
+            );
+        }
+
+        loader.set_file_iri_enabled(true);
+        let file_iri = format!("file://{}/data.json", dir.display()).replace(' ', "%20");
+        assert_eq!(
+            load(&loader, &file_iri).unwrap().document(),
+            &json!({ "name": "x" })
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+//! JSON-LD script extraction from HTML documents.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#process-html>.
+
+use anyhow::anyhow;
+use iri_string::types::{IriReferenceStr, IriStr, IriString};
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorCode},
+    remote::{response::media_type_param, LoadDocumentOptions, Profile, MEDIA_TYPE_JSON_LD},
+};
+
+/// JSON-LD content extracted from an HTML document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlDocument {
+    /// JSON-LD document.
+    document: Value,
+    /// Base IRI of the HTML document.
+    base_iri: IriString,
+}
+
+impl HtmlDocument {
+    /// Returns a reference to the extracted JSON-LD document.
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// Returns the extracted JSON-LD document with ownership.
+    pub fn into_document(self) -> Value {
+        self.document
+    }
+
+    /// Returns the base IRI of the HTML document.
+    ///
+    /// This is the `href` of the first `<base>` element resolved against the document URL, or
+    /// the document URL without the fragment if the HTML has no `<base>` element.
+    pub fn base_iri(&self) -> &IriStr {
+        &self.base_iri
+    }
+}
+
+/// Extracts JSON-LD content from the HTML document.
+///
+/// `document_url` is the URL of the HTML document. If it has a fragment, the script element
+/// with the matching `id` is extracted.
+/// Otherwise, the script element is selected by the `profile` and `extractAllScripts` options.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#process-html>.
+pub fn extract_json_ld(
+    html: &str,
+    document_url: &IriStr,
+    options: &LoadDocumentOptions,
+) -> Result<HtmlDocument, Error> {
+    let (url, fragment) = match document_url.as_str().find('#') {
+        Some(pos) => (
+            &document_url.as_str()[..pos],
+            Some(&document_url.as_str()[(pos + 1)..]),
+        ),
+        None => (document_url.as_str(), None),
+    };
+    let elements = Scanner::new(html).collect::<Vec<_>>();
+    let base_iri = base_iri(&elements, url)?;
+    let scripts = elements
+        .iter()
+        .filter_map(|element| match element {
+            Element::Script(script) => Some(script),
+            Element::Base { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Step 1: If the URL has a fragment, use the script element identified by it.
+    let source = if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+        let script = scripts
+            .iter()
+            .find(|script| script.id.as_deref() == Some(fragment))
+            .ok_or_else(|| {
+                ErrorCode::LoadingDocumentFailed
+                    .and_source(anyhow!("No script elements with id {:?}", fragment))
+            })?;
+        if !script.is_json_ld(None) {
+            return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "Script element with id {:?} is not JSON-LD: type={:?}",
+                fragment,
+                script.type_
+            )));
+        }
+        Some(*script)
+    } else {
+        // Step 2: If the profile is specified, use the first script element with the profile.
+        options.profile().and_then(|profile| {
+            scripts
+                .iter()
+                .find(|script| script.is_json_ld(Some(profile)))
+                .copied()
+        })
+    };
+
+    let document = match source {
+        Some(script) => script.parse()?,
+        // Step 3: Use the first JSON-LD script element.
+        None if !options.should_extract_all_scripts() => scripts
+            .iter()
+            .find(|script| script.is_json_ld(None))
+            .ok_or_else(|| {
+                ErrorCode::LoadingDocumentFailed
+                    .and_source(anyhow!("No JSON-LD script elements found"))
+            })?
+            .parse()?,
+        // Step 4: Merge all JSON-LD script elements into an array.
+        None => {
+            let mut merged = Vec::new();
+            for script in scripts.iter().filter(|script| script.is_json_ld(None)) {
+                match script.parse()? {
+                    Value::Array(items) => merged.extend(items),
+                    v => merged.push(v),
+                }
+            }
+            Value::Array(merged)
+        }
+    };
+
+    Ok(HtmlDocument { document, base_iri })
+}
+
+/// Returns the base IRI of the HTML document.
+fn base_iri(elements: &[Element<'_>], url: &str) -> Result<IriString, Error> {
+    let url = IriStr::new(url).map_err(|e| {
+        ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+            "Invalid document URL {:?}: {}",
+            url,
+            e
+        ))
+    })?;
+    let href = elements.iter().find_map(|element| match element {
+        Element::Base { href } => Some(href),
+        Element::Script(_) => None,
+    });
+    let href = match href {
+        Some(href) => href,
+        None => return Ok(url.to_owned()),
+    };
+    let href = IriReferenceStr::new(href.trim()).map_err(|e| {
+        ErrorCode::LoadingDocumentFailed.and_source(anyhow!("Invalid base {:?}: {}", href, e))
+    })?;
+
+    Ok(href.resolve_against(url.to_absolute()))
+}
+
+/// Element of interest in HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Element<'a> {
+    /// `<base>` element with `href` attribute.
+    Base {
+        /// `href` attribute.
+        href: String,
+    },
+    /// `<script>` element.
+    Script(Script<'a>),
+}
+
+/// Script element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Script<'a> {
+    /// `id` attribute.
+    id: Option<String>,
+    /// `type` attribute.
+    type_: Option<String>,
+    /// Content.
+    content: &'a str,
+}
+
+impl Script<'_> {
+    /// Returns true if the script element is JSON-LD.
+    ///
+    /// If the profile is given, the `profile` parameter of the type should also contain it.
+    fn is_json_ld(&self, profile: Option<Profile>) -> bool {
+        let type_ = match &self.type_ {
+            Some(v) => v,
+            None => return false,
+        };
+        let essence = type_.split(';').next().unwrap_or_default().trim();
+        if !essence.eq_ignore_ascii_case(MEDIA_TYPE_JSON_LD) {
+            return false;
+        }
+        let profile = match profile {
+            Some(v) => v,
+            None => return true,
+        };
+        media_type_param(type_, "profile")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .any(|v| v == profile.uri())
+    }
+
+    /// Parses the content as JSON.
+    fn parse(&self) -> Result<Value, Error> {
+        serde_json::from_str(self.content).map_err(|e| {
+            ErrorCode::InvalidScriptElement
+                .and_source(e)
+                .context(format!("Invalid JSON in script element: id={:?}", self.id))
+        })
+    }
+}
+
+/// Scanner of `<base>` and `<script>` elements in HTML.
+///
+/// This is not a full HTML parser, but handles comments, attributes and raw text content of
+/// script elements, which are enough to find JSON-LD script elements.
+struct Scanner<'a> {
+    /// Rest of the HTML.
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    /// Creates a new scanner.
+    fn new(html: &'a str) -> Self {
+        Self { rest: html }
+    }
+
+    /// Skips the rest until the end of the pattern, and returns the skipped part.
+    ///
+    /// The pattern should be ASCII, and is matched case-insensitively.
+    fn skip_past(&mut self, pattern: &str) -> &'a str {
+        let found = self
+            .rest
+            .as_bytes()
+            .windows(pattern.len())
+            .position(|window| window.eq_ignore_ascii_case(pattern.as_bytes()));
+        match found {
+            Some(pos) => {
+                let skipped = &self.rest[..pos];
+                self.rest = &self.rest[(pos + pattern.len())..];
+                skipped
+            }
+            None => std::mem::take(&mut self.rest),
+        }
+    }
+
+    /// Parses attributes of a start tag, and consumes the tag up to `>`.
+    fn attributes(&mut self) -> Vec<(String, String)> {
+        let mut attrs = Vec::new();
+        loop {
+            self.rest = self
+                .rest
+                .trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+            if self.rest.is_empty() {
+                return attrs;
+            }
+            if let Some(rest) = self.rest.strip_prefix('>') {
+                self.rest = rest;
+                return attrs;
+            }
+            let name_len = self
+                .rest
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, '/' | '>' | '='))
+                .unwrap_or(self.rest.len());
+            // Skip a stray character which cannot start an attribute name.
+            let name_len = match name_len {
+                0 => self.rest.chars().next().map_or(0, char::len_utf8),
+                len => len,
+            };
+            let name = self.rest[..name_len].to_ascii_lowercase();
+            self.rest = self.rest[name_len..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+            let value = match self.rest.strip_prefix('=') {
+                Some(rest) => {
+                    let rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+                    let (value, rest) = match rest.chars().next() {
+                        Some(quote @ '"') | Some(quote @ '\'') => {
+                            let rest = &rest[1..];
+                            let end = rest.find(quote).unwrap_or(rest.len());
+                            (&rest[..end], rest.get((end + 1)..).unwrap_or_default())
+                        }
+                        _ => {
+                            let end = rest
+                                .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                                .unwrap_or(rest.len());
+                            rest.split_at(end)
+                        }
+                    };
+                    self.rest = rest;
+                    decode_character_references(value)
+                }
+                None => String::new(),
+            };
+            attrs.push((name, value));
+        }
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Element<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.skip_past("<");
+            if self.rest.is_empty() {
+                return None;
+            }
+            if let Some(rest) = self.rest.strip_prefix("!--") {
+                self.rest = rest;
+                self.skip_past("-->");
+                continue;
+            }
+            let name_len = self
+                .rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(self.rest.len());
+            let name = &self.rest[..name_len];
+            self.rest = &self.rest[name_len..];
+            if name.eq_ignore_ascii_case("script") {
+                let attrs = self.attributes();
+                let content = self.skip_past("</script");
+                self.skip_past(">");
+                let attr = |name: &str| {
+                    attrs
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, v)| v.clone())
+                };
+                return Some(Element::Script(Script {
+                    id: attr("id"),
+                    type_: attr("type"),
+                    content,
+                }));
+            }
+            if name.eq_ignore_ascii_case("base") {
+                let href = self
+                    .attributes()
+                    .into_iter()
+                    .find(|(n, _)| n == "href")
+                    .map(|(_, v)| v);
+                if let Some(href) = href {
+                    return Some(Element::Base { href });
+                }
+                continue;
+            }
+            if !name.is_empty() {
+                self.attributes();
+            }
+        }
+    }
+}
+
+/// Decodes character references in an attribute value.
+///
+/// Only numeric references and a few common named references are supported.
+fn decode_character_references(s: &str) -> String {
+    /// Supported named character references.
+    const NAMED: &[(&str, char)] = &[
+        ("amp", '&'),
+        ("apos", '\''),
+        ("gt", '>'),
+        ("lt", '<'),
+        ("quot", '"'),
+    ];
+
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('&') {
+        decoded.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let reference = rest[1..].find(';').map(|end| &rest[1..=end]);
+        let c = reference.and_then(|reference| match reference.strip_prefix('#') {
+            Some(num) => match num.strip_prefix(|c| c == 'x' || c == 'X') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => num.parse().ok(),
+            }
+            .and_then(std::char::from_u32),
+            None => NAMED
+                .iter()
+                .find(|(name, _)| *name == reference)
+                .map(|(_, c)| *c),
+        });
+        match (c, reference) {
+            (Some(c), Some(reference)) => {
+                decoded.push(c);
+                rest = &rest[(reference.len() + 2)..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// HTML document with several script elements.
+    const HTML: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <!-- <base href="http://example.org/ignored/"> -->
+  <BASE target="_self">
+  <base href="/base/?a=1&amp;b=2">
+  <script type="application/javascript">if (a < b) { x = "</div>"; }</script>
+  <script id="first" type="application/ld+json">{"@id": "#first"}</script>
+  <script type='application/ld+json;profile="http://www.w3.org/ns/json-ld#context http://www.w3.org/ns/json-ld#frame"'>
+    [{"@id": "#second"}, {"@id": "#third"}]
+  </script>
+  <script id=broken type=application/ld+json>{</script>
+  <script id="js" type="text/javascript">{}</script>
+</head>
+</html>"##;
+
+    /// Extracts JSON-LD from `HTML`.
+    fn extract(url: &str, options: &LoadDocumentOptions) -> Result<HtmlDocument, Error> {
+        let url = IriStr::new(url).expect("Should never fail: valid IRI");
+        extract_json_ld(HTML, url, options)
+    }
+
+    #[test]
+    fn extract_scripts() {
+        let mut options = LoadDocumentOptions::new();
+        let doc = extract("http://example.com/doc", &options).unwrap();
+        assert_eq!(doc.document(), &json!({ "@id": "#first" }));
+        assert_eq!(doc.base_iri().as_str(), "http://example.com/base/?a=1&b=2");
+
+        options.set_profile(Profile::Frame);
+        let doc = extract("http://example.com/doc", &options).unwrap();
+        assert_eq!(
+            doc.document(),
+            &json!([{ "@id": "#second" }, { "@id": "#third" }])
+        );
+
+        // Fragments take precedence over profiles.
+        let doc = extract("http://example.com/doc#first", &options).unwrap();
+        assert_eq!(doc.document(), &json!({ "@id": "#first" }));
+
+        // Falls back to the first JSON-LD script element.
+        options.set_profile(Profile::Expanded);
+        let doc = extract("http://example.com/doc", &options).unwrap();
+        assert_eq!(doc.document(), &json!({ "@id": "#first" }));
+    }
+
+    #[test]
+    fn extract_all_scripts() {
+        let html = r##"<script type="application/ld+json">{"@id": "#a"}</script>
+<script type="application/ld+json">[{"@id": "#b"}, {"@id": "#c"}]</script>"##;
+        let url = IriStr::new("http://example.com/doc").expect("Should never fail: valid IRI");
+        let mut options = LoadDocumentOptions::new();
+        options.set_extract_all_scripts(true);
+        let doc = extract_json_ld(html, url, &options).unwrap();
+        assert_eq!(
+            doc.document(),
+            &json!([{ "@id": "#a" }, { "@id": "#b" }, { "@id": "#c" }])
+        );
+        assert_eq!(doc.base_iri().as_str(), "http://example.com/doc");
+
+        let doc = extract_json_ld("<p>no scripts</p>", url, &options).unwrap();
+        assert_eq!(doc.document(), &json!([]));
+
+        let err = extract("http://example.com/doc", &options).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidScriptElement);
+    }
+
+    #[test]
+    fn extract_errors() {
+        let options = LoadDocumentOptions::new();
+        for (url, code) in &[
+            (
+                "http://example.com/doc#broken",
+                ErrorCode::InvalidScriptElement,
+            ),
+            (
+                "http://example.com/doc#js",
+                ErrorCode::LoadingDocumentFailed,
+            ),
+            (
+                "http://example.com/doc#none",
+                ErrorCode::LoadingDocumentFailed,
+            ),
+        ] {
+            let err = extract(url, &options).unwrap_err();
+            assert_eq!(err.code(), *code, "url={:?}", url);
+        }
+
+        let url = IriStr::new("http://example.com/doc").expect("Should never fail: valid IRI");
+        let err = extract_json_ld("<p>no scripts</p>", url, &options).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+    }
+}
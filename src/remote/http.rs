@@ -0,0 +1,473 @@
+//! HTTP document loader.
+
+use std::{
+    fmt, io,
+    io::Read,
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use iri_string::types::{IriReferenceStr, IriStr, IriString};
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorCode},
+    remote::{
+        process_response, LoadDocumentOptions, LoadRemoteDocument, RemoteDocument, ResponseAction,
+    },
+};
+
+/// Default maximum number of redirects.
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Default maximum size of response bodies in bytes.
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Default timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Filter of URLs to be requested.
+type UrlFilter = Arc<dyn Fn(&IriStr) -> Result<(), String> + Send + Sync>;
+
+/// Filter of addresses to be connected.
+type AddressFilter = Arc<dyn Fn(IpAddr) -> Result<(), String> + Send + Sync>;
+
+/// Document loader which loads documents by HTTP(S) `GET` requests.
+///
+/// The loader implements the rules of the `LoadDocumentCallback`:
+///
+/// * The `Accept` header requests JSON-LD with the profiles of the load options.
+/// * Redirects are followed up to the limit (see [`set_max_redirects`]).
+/// * `rel="alternate"` links to `application/ld+json` documents are followed, and also count
+///   toward the redirect limit.
+/// * Every URL including redirect targets and alternate links is checked by the URL filter
+///   before it is requested (see [`set_url_filter`]).
+/// * Every resolved address is checked by the address filter before it is connected
+///   (see [`set_address_filter`]).
+/// * Context links of JSON documents are set as `contextUrl`.
+/// * JSON-LD script elements are extracted from HTML documents.
+///
+/// Note that requests are performed synchronously, so loading blocks the current thread.
+///
+/// This is available only if `http-loader` feature is enabled.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#loaddocumentcallback>.
+///
+/// [`set_max_redirects`]: #method.set_max_redirects
+/// [`set_url_filter`]: #method.set_url_filter
+/// [`set_address_filter`]: #method.set_address_filter
+#[derive(Clone)]
+pub struct HttpLoader {
+    /// HTTP agent.
+    agent: ureq::Agent,
+    /// Maximum number of redirects.
+    max_redirects: usize,
+    /// Maximum size of response bodies in bytes.
+    max_size: u64,
+    /// Timeout of each request.
+    timeout: Duration,
+    /// Filter of URLs to be requested.
+    url_filter: Option<UrlFilter>,
+    /// Filter of addresses to be connected.
+    address_filter: Option<AddressFilter>,
+}
+
+impl fmt::Debug for HttpLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpLoader")
+            .field("agent", &self.agent)
+            .field("max_redirects", &self.max_redirects)
+            .field("max_size", &self.max_size)
+            .field("timeout", &self.timeout)
+            .field("url_filter", &self.url_filter.as_ref().map(|_| ".."))
+            .field(
+                "address_filter",
+                &self.address_filter.as_ref().map(|_| ".."),
+            )
+            .finish()
+    }
+}
+
+impl Default for HttpLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpLoader {
+    /// Creates a new loader.
+    ///
+    /// By default, at most 10 redirects are followed, response bodies are limited to 10 MiB, and
+    /// each request times out in 30 seconds.
+    pub fn new() -> Self {
+        Self {
+            agent: agent(DEFAULT_TIMEOUT, None),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            max_size: DEFAULT_MAX_SIZE,
+            timeout: DEFAULT_TIMEOUT,
+            url_filter: None,
+            address_filter: None,
+        }
+    }
+
+    /// Sets the maximum number of redirects.
+    pub fn set_max_redirects(&mut self, max_redirects: usize) {
+        self.max_redirects = max_redirects;
+    }
+
+    /// Sets the maximum size of response bodies in bytes.
+    pub fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+
+    /// Sets the timeout of each request, including connection and reading the body.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        self.agent = agent(timeout, self.address_filter.clone());
+    }
+
+    /// Sets the filter of URLs to be requested.
+    ///
+    /// The filter is called before every request, including redirects and alternate links.
+    /// If it returns `Err(reason)`, the request is not sent and loading fails.
+    /// This replaces the previously set filter.
+    pub fn set_url_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(&IriStr) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.url_filter = Some(Arc::new(filter));
+    }
+
+    /// Sets the filter of addresses to be connected.
+    ///
+    /// The filter is called for every address that host names are resolved to, right before
+    /// connecting, so this is effective against DNS rebinding.
+    /// Addresses for which it returns `Err(reason)` are skipped, and loading fails if no
+    /// addresses are left.
+    /// This replaces the previously set filter.
+    pub fn set_address_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(IpAddr) -> Result<(), String> + Send + Sync + 'static,
+    {
+        let filter: AddressFilter = Arc::new(filter);
+        self.address_filter = Some(filter.clone());
+        self.agent = agent(self.timeout, Some(filter));
+    }
+
+    /// Loads the document.
+    fn load_sync(
+        &self,
+        iri: &IriStr,
+        options: &LoadDocumentOptions,
+    ) -> Result<RemoteDocument, Error> {
+        let accept = accept_header(options);
+        // URL to request.
+        let mut url = iri.to_owned();
+        // URL of the document, which is not updated by alternate links.
+        let mut document_url = url.clone();
+        let mut redirects = 0;
+        loop {
+            if let Some(filter) = &self.url_filter {
+                filter(&url).map_err(|reason| {
+                    ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                        "Request to {:?} is denied: {}",
+                        url.as_str(),
+                        reason
+                    ))
+                })?;
+            }
+            let response = match self.agent.get(url.as_str()).set("Accept", &accept).call() {
+                Ok(v) => v,
+                Err(ureq::Error::Status(status, _)) => {
+                    return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                        "Unsuccessful status {} for {:?}",
+                        status,
+                        url.as_str()
+                    )))
+                }
+                Err(e) => {
+                    return Err(ErrorCode::LoadingDocumentFailed
+                        .and_source(e)
+                        .context(format!("Failed to load {:?}", url.as_str())))
+                }
+            };
+
+            let status = response.status();
+            let next = if (300..400).contains(&status) {
+                let location = response.header("Location").ok_or_else(|| {
+                    ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                        "Redirect without `Location` header from {:?}",
+                        url.as_str()
+                    ))
+                })?;
+                let next = resolve(location, &url)?;
+                document_url = next.clone();
+                next
+            } else {
+                let content_type = response.header("Content-Type").map(ToOwned::to_owned);
+                let links = response.all("Link");
+                match process_response(&url, status, content_type.as_deref(), &links)? {
+                    ResponseAction::Alternate(next) => next,
+                    ResponseAction::Html => {
+                        let body = self.read_body(response, &url)?;
+                        let html = String::from_utf8(body).map_err(|e| {
+                            ErrorCode::LoadingDocumentFailed
+                                .and_source(e)
+                                .context(format!("Invalid UTF-8 in {:?}", url.as_str()))
+                        })?;
+                        // Keep the fragment to select the script element.
+                        let mut document_url = document_url.as_str().to_owned();
+                        if let Some(pos) = iri.as_str().find('#') {
+                            if !document_url.contains('#') {
+                                document_url.push_str(&iri.as_str()[pos..]);
+                            }
+                        }
+                        let builder = RemoteDocument::builder(document_url);
+                        let builder = match content_type {
+                            Some(content_type) => builder.content_type(content_type),
+                            None => builder,
+                        };
+                        return builder.html(&html, options);
+                    }
+                    ResponseAction::Json(json) => {
+                        let body = self.read_body(response, &url)?;
+                        let document: Value = serde_json::from_slice(&body).map_err(|e| {
+                            ErrorCode::LoadingDocumentFailed
+                                .and_source(e)
+                                .context(format!("Failed to parse {:?} as JSON", url.as_str()))
+                        })?;
+                        return Ok(json.into_remote_document(document_url.as_str(), document));
+                    }
+                }
+            };
+
+            redirects += 1;
+            if redirects > self.max_redirects {
+                return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                    "Too many redirects: limit = {}, url = {:?}",
+                    self.max_redirects,
+                    iri.as_str()
+                )));
+            }
+            url = next;
+        }
+    }
+
+    /// Reads the response body up to the size limit.
+    fn read_body(&self, response: ureq::Response, url: &IriStr) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_size.saturating_add(1))
+            .read_to_end(&mut body)
+            .map_err(|e| {
+                ErrorCode::LoadingDocumentFailed
+                    .and_source(e)
+                    .context(format!("Failed to read {:?}", url.as_str()))
+            })?;
+        if body.len() as u64 > self.max_size {
+            return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "Response too large: limit = {} bytes, url = {:?}",
+                self.max_size,
+                url.as_str()
+            )));
+        }
+
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl LoadRemoteDocument for HttpLoader {
+    type Error = Error;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        self.load_sync(iri, &options).map(Arc::new)
+    }
+}
+
+/// Creates an HTTP agent.
+fn agent(timeout: Duration, address_filter: Option<AddressFilter>) -> ureq::Agent {
+    // Redirects are followed by `HttpLoader` itself to track the document URL.
+    let builder = ureq::AgentBuilder::new().redirects(0).timeout(timeout);
+    let builder = match address_filter {
+        Some(filter) => builder.resolver(move |netloc: &str| resolve_filtered(netloc, &*filter)),
+        None => builder,
+    };
+    builder.build()
+}
+
+/// Resolves the host and port, and drops addresses denied by the filter.
+fn resolve_filtered(
+    netloc: &str,
+    filter: &(dyn Fn(IpAddr) -> Result<(), String> + Send + Sync),
+) -> io::Result<Vec<SocketAddr>> {
+    let mut denied = None;
+    let addrs = netloc
+        .to_socket_addrs()?
+        .filter(|addr| match filter(addr.ip()) {
+            Ok(()) => true,
+            Err(reason) => {
+                denied = Some(reason);
+                false
+            }
+        })
+        .collect::<Vec<_>>();
+    match denied {
+        Some(reason) if addrs.is_empty() => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Connection to {:?} is denied: {}", netloc, reason),
+        )),
+        _ => Ok(addrs),
+    }
+}
+
+/// Returns the value of `Accept` header.
+fn accept_header(options: &LoadDocumentOptions) -> String {
+    let profiles = options
+        .request_profile()
+        .iter()
+        .map(|profile| profile.uri())
+        .collect::<Vec<_>>();
+    let mut accept = String::new();
+    if !profiles.is_empty() {
+        accept.push_str("application/ld+json;profile=\"");
+        accept.push_str(&profiles.join(" "));
+        accept.push_str("\", ");
+    }
+    accept.push_str("application/ld+json, application/json;q=0.9, text/html;q=0.8, */*;q=0.1");
+
+    accept
+}
+
+/// Resolves the IRI reference against the base.
+fn resolve(reference: &str, base: &IriStr) -> Result<IriString, Error> {
+    let reference = IriReferenceStr::new(reference.trim()).map_err(|e| {
+        ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+            "Invalid redirect target {:?}: {}",
+            reference,
+            e
+        ))
+    })?;
+    Ok(reference.resolve_against(base.to_absolute()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        remote::Profile,
+        test_utils::{load, load_with, serve},
+    };
+
+    #[test]
+    fn load_documents() {
+        let origin = serve(5, |path, accept| match path {
+            "/old" => (301, vec!["Location: /doc".to_owned()], String::new()),
+            "/doc" => (
+                200,
+                vec![
+                    "Content-Type: application/json".to_owned(),
+                    "Link: </ctx.jsonld>; rel=\"http://www.w3.org/ns/json-ld#context\"".to_owned(),
+                ],
+                json!({ "accept": accept }).to_string(),
+            ),
+            "/page" => (
+                200,
+                vec![
+                    "Content-Type: text/html".to_owned(),
+                    "Link: <doc.jsonld>; rel=alternate; type=\"application/ld+json\"".to_owned(),
+                ],
+                String::new(),
+            ),
+            "/doc.jsonld" => (
+                200,
+                vec!["Content-Type: application/ld+json".to_owned()],
+                r#"{"@id": "x"}"#.to_owned(),
+            ),
+            _ => (404, vec![], String::new()),
+        });
+        let loader = HttpLoader::new();
+
+        let mut options = LoadDocumentOptions::new();
+        options.set_request_profile(Profile::Context);
+        let doc = load_with(&loader, &format!("{}/old", origin), options).unwrap();
+        assert_eq!(doc.document_url(), format!("{}/doc", origin));
+        assert_eq!(doc.content_type(), Some("application/json"));
+        assert_eq!(
+            doc.context_url(),
+            Some(format!("{}/ctx.jsonld", origin).as_str())
+        );
+        assert_eq!(
+            doc.document()["accept"],
+            "application/ld+json;profile=\"http://www.w3.org/ns/json-ld#context\", \
+             application/ld+json, application/json;q=0.9, text/html;q=0.8, */*;q=0.1"
+        );
+
+        // Alternate links keep the document URL.
+        let doc = load(&loader, &format!("{}/page", origin)).unwrap();
+        assert_eq!(doc.document_url(), format!("{}/page", origin));
+        assert_eq!(doc.document(), &json!({ "@id": "x" }));
+
+        let err = load(&loader, &format!("{}/missing", origin)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+    }
+
+    #[test]
+    fn load_limits() {
+        let origin = serve(3, |path, _| match path {
+            "/loop" => (302, vec!["Location: /loop".to_owned()], String::new()),
+            _ => (
+                200,
+                vec!["Content-Type: application/ld+json".to_owned()],
+                format!("[{}]", "0,".repeat(100) + "0"),
+            ),
+        });
+        let mut loader = HttpLoader::new();
+        loader.set_max_redirects(1);
+        loader.set_max_size(100);
+
+        let err = load(&loader, &format!("{}/loop", origin)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+        let err = load(&loader, &format!("{}/large", origin)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+    }
+
+    #[test]
+    fn filter_addresses() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let served = Arc::new(AtomicBool::new(false));
+        let served_handler = served.clone();
+        let origin = serve(1, move |_, _| {
+            served_handler.store(true, Ordering::SeqCst);
+            (
+                200,
+                vec!["Content-Type: application/ld+json".to_owned()],
+                "{}".to_owned(),
+            )
+        });
+        let mut loader = HttpLoader::new();
+        loader.set_address_filter(|addr| {
+            if addr.is_loopback() {
+                Err("loopback".to_owned())
+            } else {
+                Ok(())
+            }
+        });
+        loader.set_timeout(Duration::from_secs(10));
+
+        let err = load(&loader, &format!("{}/doc", origin)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+        assert!(!served.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,551 @@
+//! Security policy for remote document loading.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs},
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use async_trait::async_trait;
+use iri_string::types::IriStr;
+use serde_json::Value;
+
+#[cfg(feature = "http-loader")]
+use crate::remote::HttpLoader;
+use crate::remote::{LoadDocumentOptions, LoadRemoteDocument, RemoteDocument, StaticLoader};
+
+/// Schemes allowed by default.
+const DEFAULT_SCHEMES: &[&str] = &["http", "https"];
+
+/// Document loader wrapper which restricts IRIs to be dereferenced.
+///
+/// Remote contexts and `@import` IRIs come from input documents, so a service processing
+/// untrusted documents can be used to probe internal networks unless the loader is restricted.
+/// This loader checks IRIs in the order below before passing them to the inner loader:
+///
+/// 1. Preloaded documents (see [`preload`]) are always served, without the inner loader.
+/// 2. If the network is disabled (see [`set_network_enabled`]), other IRIs are denied.
+/// 3. The scheme should be allowed (see [`set_allowed_schemes`]).
+///    Only `http` and `https` are allowed by default.
+/// 4. The path should not have `.` nor `..` segments, including percent-encoded ones.
+/// 5. If any hosts or prefixes are allowed explicitly (see [`allow_host`] and [`allow_prefix`]),
+///    the IRI should match one of them.
+/// 6. If private addresses are blocked (see [`set_private_addresses_blocked`]), the host should
+///    not be `localhost` nor resolved to loopback, private, link-local or other non-global
+///    addresses.
+///    This is enabled by default, and does not apply to `file` IRIs.
+///
+/// Note that host names are resolved synchronously, so loading blocks the current thread.
+/// Also note that the inner loader resolves host names again, so this cannot prevent DNS
+/// rebinding attacks by itself.
+/// [`with_http_loader`] also checks the addresses which [`HttpLoader`] connects to.
+///
+/// Only the IRI passed to the inner loader is checked, so redirects and alternate links followed
+/// by the inner loader are not restricted in general.
+/// Use [`with_http_loader`] to check every request of [`HttpLoader`], or make sure that the
+/// inner loader does not follow redirects.
+///
+/// Clones share the policy with the original.
+///
+/// [`preload`]: #method.preload
+/// [`set_network_enabled`]: #method.set_network_enabled
+/// [`set_allowed_schemes`]: #method.set_allowed_schemes
+/// [`allow_host`]: #method.allow_host
+/// [`allow_prefix`]: #method.allow_prefix
+/// [`set_private_addresses_blocked`]: #method.set_private_addresses_blocked
+/// [`with_http_loader`]: #method.with_http_loader
+/// [`HttpLoader`]: struct.HttpLoader.html
+#[derive(Debug, Clone)]
+pub struct PolicyLoader<L> {
+    /// Inner loader.
+    loader: L,
+    /// Preloaded documents.
+    preloaded: StaticLoader,
+    /// Policy, which may be shared with the inner loader.
+    policy: Arc<RwLock<Policy>>,
+}
+
+/// Rules of `PolicyLoader`.
+#[derive(Debug)]
+struct Policy {
+    /// Whether the inner loader is used.
+    network_enabled: bool,
+    /// Allowed schemes, in lowercase.
+    allowed_schemes: Vec<String>,
+    /// Allowed hosts, in lowercase.
+    allowed_hosts: Vec<String>,
+    /// Allowed IRI prefixes.
+    allowed_prefixes: Vec<String>,
+    /// Whether private addresses are blocked.
+    private_addresses_blocked: bool,
+}
+
+impl<L: LoadRemoteDocument> PolicyLoader<L> {
+    /// Creates a new loader with the default policy.
+    pub fn new(loader: L) -> Self {
+        Self::with_policy(loader, Arc::new(RwLock::new(Policy::default())))
+    }
+
+    /// Creates a new loader with the given policy.
+    fn with_policy(loader: L, policy: Arc<RwLock<Policy>>) -> Self {
+        Self {
+            loader,
+            preloaded: StaticLoader::new(),
+            policy,
+        }
+    }
+
+    /// Adds the preloaded JSON-LD document for the IRI.
+    ///
+    /// Preloaded documents are served regardless of the policy.
+    pub fn preload(&mut self, iri: impl Into<String>, document: Value) {
+        self.preloaded.insert(iri, document);
+    }
+
+    /// Sets whether the inner loader is used.
+    ///
+    /// If disabled, only preloaded documents are served.
+    pub fn set_network_enabled(&mut self, enabled: bool) {
+        self.policy_mut().network_enabled = enabled;
+    }
+
+    /// Sets the allowed schemes.
+    ///
+    /// Schemes are case-insensitive.
+    pub fn set_allowed_schemes<I, S>(&mut self, schemes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.policy_mut().allowed_schemes = schemes
+            .into_iter()
+            .map(|s| s.as_ref().to_ascii_lowercase())
+            .collect();
+    }
+
+    /// Allows the host.
+    ///
+    /// Hosts are matched exactly and case-insensitively, so subdomains should be allowed
+    /// separately.
+    pub fn allow_host(&mut self, host: impl AsRef<str>) {
+        self.policy_mut()
+            .allowed_hosts
+            .push(host.as_ref().to_ascii_lowercase());
+    }
+
+    /// Allows IRIs starting with the prefix.
+    ///
+    /// Prefixes should end with `/`, otherwise `https://example.com` also allows
+    /// `https://example.com.example.net/`.
+    pub fn allow_prefix(&mut self, prefix: impl Into<String>) {
+        self.policy_mut().allowed_prefixes.push(prefix.into());
+    }
+
+    /// Sets whether loopback, private and other non-global addresses are blocked.
+    pub fn set_private_addresses_blocked(&mut self, blocked: bool) {
+        self.policy_mut().private_addresses_blocked = blocked;
+    }
+
+    /// Returns a reference to the inner loader.
+    pub fn loader(&self) -> &L {
+        &self.loader
+    }
+
+    /// Returns the policy.
+    fn policy(&self) -> RwLockReadGuard<'_, Policy> {
+        self.policy.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the policy for modification.
+    fn policy_mut(&self) -> RwLockWriteGuard<'_, Policy> {
+        self.policy.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "http-loader")]
+impl PolicyLoader<HttpLoader> {
+    /// Creates a new loader with the default policy, which also checks every request of the
+    /// HTTP loader including redirects and alternate links.
+    ///
+    /// Addresses are checked again when the HTTP loader connects to them, so private addresses
+    /// are blocked even if the host name is resolved differently from the check.
+    ///
+    /// This replaces the URL filter and the address filter of the HTTP loader.
+    pub fn with_http_loader(mut loader: HttpLoader) -> Self {
+        let policy = Arc::new(RwLock::new(Policy::default()));
+        let url_policy = policy.clone();
+        loader.set_url_filter(move |url| {
+            url_policy
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .check(url.as_str())
+        });
+        let address_policy = policy.clone();
+        loader.set_address_filter(move |addr| {
+            let policy = address_policy
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            if policy.private_addresses_blocked {
+                check_public_address(addr)
+            } else {
+                Ok(())
+            }
+        });
+        Self::with_policy(loader, policy)
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            network_enabled: true,
+            allowed_schemes: DEFAULT_SCHEMES.iter().map(|&s| s.to_owned()).collect(),
+            allowed_hosts: Vec::new(),
+            allowed_prefixes: Vec::new(),
+            private_addresses_blocked: true,
+        }
+    }
+}
+
+impl Policy {
+    /// Checks whether the IRI is allowed by the policy.
+    fn check(&self, iri: &str) -> Result<(), String> {
+        if !self.network_enabled {
+            return Err("network access is disabled".to_owned());
+        }
+
+        let (scheme, rest) = iri.split_once(':').unwrap_or(("", iri));
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.allowed_schemes.contains(&scheme) {
+            return Err(format!("scheme {:?} is not allowed", scheme));
+        }
+
+        // HTTP clients may remove percent-encoded dot segments, which allows escaping from the
+        // allowed prefixes.
+        if has_dot_segment(rest) {
+            return Err("the IRI has a dot segment".to_owned());
+        }
+
+        let host = host(rest);
+        let has_allowlist = !self.allowed_hosts.is_empty() || !self.allowed_prefixes.is_empty();
+        let is_allowed_host = host
+            .as_ref()
+            .is_some_and(|host| self.allowed_hosts.contains(host));
+        let is_allowed_prefix = self
+            .allowed_prefixes
+            .iter()
+            .any(|prefix| iri.starts_with(prefix.as_str()));
+        if has_allowlist && !is_allowed_host && !is_allowed_prefix {
+            return Err("the IRI is not in the allowlist".to_owned());
+        }
+
+        // `file` IRIs have no network authority.
+        if self.private_addresses_blocked && scheme != "file" {
+            let host = host.ok_or_else(|| "the IRI has no host".to_owned())?;
+            check_public_host(&host, &scheme)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<L: LoadRemoteDocument> LoadRemoteDocument for PolicyLoader<L> {
+    type Error = PolicyError<L::Error>;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        if let Ok(doc) = self.preloaded.load(iri, options.clone()).await {
+            return Ok(doc);
+        }
+        self.policy()
+            .check(iri.as_str())
+            .map_err(|reason| PolicyError::Denied {
+                iri: iri.as_str().to_owned(),
+                reason,
+            })?;
+        self.loader
+            .load(iri, options)
+            .await
+            .map_err(PolicyError::Loader)
+    }
+}
+
+/// Error of `PolicyLoader`.
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError<E: std::error::Error + 'static> {
+    /// The IRI is denied by the policy.
+    #[error("Loading {iri:?} is denied by the policy: {reason}")]
+    Denied {
+        /// IRI.
+        iri: String,
+        /// Reason.
+        reason: String,
+    },
+    /// The inner loader failed.
+    #[error(transparent)]
+    Loader(E),
+}
+
+/// Returns true if the path of the IRI without the scheme has `.` or `..` segments.
+///
+/// Percent-encoded dots (`%2e`) are also treated as dots, as WHATWG URL parsers do.
+fn has_dot_segment(hier_part: &str) -> bool {
+    let end = hier_part.find(&['?', '#'][..]).unwrap_or(hier_part.len());
+    let path = &hier_part[..end];
+    let path = match path.strip_prefix("//") {
+        Some(v) => v.find('/').map_or("", |pos| &v[pos..]),
+        None => path,
+    };
+    path.split('/').any(|segment| {
+        let segment = segment.to_ascii_lowercase().replace("%2e", ".");
+        segment == "." || segment == ".."
+    })
+}
+
+/// Returns the host of the IRI without the scheme, in lowercase.
+///
+/// IPv6 addresses are returned without brackets.
+fn host(hier_part: &str) -> Option<String> {
+    let authority = hier_part.strip_prefix("//")?;
+    let authority = authority
+        .split(&['/', '?', '#'][..])
+        .next()
+        .unwrap_or(authority);
+    let host_port = authority.rsplit('@').next().unwrap_or(authority);
+    let host = match host_port.strip_prefix('[') {
+        Some(v) => v.split(']').next().unwrap_or(v),
+        None => host_port.split(':').next().unwrap_or(host_port),
+    };
+    Some(host.to_ascii_lowercase())
+}
+
+/// Checks whether the host is resolved only to global addresses.
+fn check_public_host(host: &str, scheme: &str) -> Result<(), String> {
+    if let Ok(addr) = host.parse::<IpAddr>() {
+        return check_public_address(addr);
+    }
+    let name = host.trim_end_matches('.');
+    if name.is_empty() || name == "localhost" || name.ends_with(".localhost") {
+        return Err(format!("host {:?} is local", host));
+    }
+    // Percent-encoded hosts may be decoded by the inner loader.
+    if host.contains('%') {
+        return Err(format!("host {:?} is percent-encoded", host));
+    }
+    let port = if scheme == "https" { 443 } else { 80 };
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("failed to resolve host {:?}: {}", host, e))?;
+    for addr in addrs {
+        check_public_address(addr.ip())?;
+    }
+
+    Ok(())
+}
+
+/// Checks whether the address is global.
+fn check_public_address(addr: IpAddr) -> Result<(), String> {
+    let is_global = match addr {
+        IpAddr::V4(v4) => is_global_v4(v4),
+        IpAddr::V6(v6) => is_global_v6(v6),
+    };
+    if is_global {
+        Ok(())
+    } else {
+        Err(format!("address {} is not global", addr))
+    }
+}
+
+/// Returns true if the IPv4 address is global.
+fn is_global_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, ..] = addr.octets();
+    !(addr.is_private()
+        || addr.is_loopback()
+        || addr.is_link_local()
+        || addr.is_unspecified()
+        || addr.is_broadcast()
+        || addr.is_multicast()
+        || addr.is_documentation()
+        // "This network" (0.0.0.0/8).
+        || a == 0
+        // Shared address space (100.64.0.0/10).
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments (192.0.0.0/24).
+        || (a == 192 && b == 0 && addr.octets()[2] == 0)
+        // Benchmarking (198.18.0.0/15).
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved (240.0.0.0/4).
+        || a >= 240)
+}
+
+/// Returns true if the IPv6 address is global.
+fn is_global_v6(addr: Ipv6Addr) -> bool {
+    if let Some(v4) = embedded_v4(addr) {
+        return is_global_v4(v4);
+    }
+    let segments = addr.segments();
+    let first = segments[0];
+    !(addr.is_loopback()
+        || addr.is_unspecified()
+        || addr.is_multicast()
+        // Local-use NAT64 (64:ff9b:1::/48).
+        || (segments[..3] == [0x64, 0xff9b, 1])
+        // Unique local (fc00::/7).
+        || (first & 0xfe00) == 0xfc00
+        // Link-local (fe80::/10).
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// Returns the IPv4 address embedded in the IPv6 address, which may be routed to the IPv4
+/// address.
+fn embedded_v4(addr: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = addr.segments();
+    let [.., a, b, c, d] = addr.octets();
+    match segments {
+        // IPv4-mapped (::ffff:0:0/96).
+        [0, 0, 0, 0, 0, 0xffff, ..] => Some(Ipv4Addr::new(a, b, c, d)),
+        // IPv4-compatible (::/96), except for `::` and `::1`.
+        [0, 0, 0, 0, 0, 0, ..] if !addr.is_unspecified() && !addr.is_loopback() => {
+            Some(Ipv4Addr::new(a, b, c, d))
+        }
+        // NAT64 (64:ff9b::/96).
+        [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(Ipv4Addr::new(a, b, c, d)),
+        // 6to4 (2002::/16).
+        [0x2002, high, low, ..] => {
+            let [a, b] = high.to_be_bytes();
+            let [c, d] = low.to_be_bytes();
+            Some(Ipv4Addr::new(a, b, c, d))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::load;
+
+    /// Returns true if the IRI is denied by the policy.
+    fn is_denied<L: LoadRemoteDocument>(loader: &PolicyLoader<L>, iri: &str) -> bool {
+        matches!(load(loader, iri), Err(PolicyError::Denied { .. }))
+    }
+
+    #[test]
+    fn default_policy() {
+        let iris = [
+            "file:///etc/passwd",
+            "ftp://93.184.216.34/ctx",
+            "http://localhost:8080/ctx",
+            "http://127.0.0.1/ctx",
+            "http://user@10.1.2.3/ctx",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/ctx",
+            "http://[::ffff:192.168.0.1]/ctx",
+            "http://[fd00::1]:8080/ctx",
+            "http://[64:ff9b::a00:1]/ctx",
+            "http://[::a00:1]/ctx",
+            "http://[2002:a00:1::1]/ctx",
+            "http://198.18.0.1/ctx",
+            "http://192.0.0.8/ctx",
+            "http://93.184.216.34/ctx",
+        ];
+        let inner: StaticLoader = iris.iter().map(|&iri| (iri, json!({}))).collect();
+        let mut loader = PolicyLoader::new(inner);
+        for iri in &iris[..(iris.len() - 1)] {
+            assert!(is_denied(&loader, iri), "iri={:?}", iri);
+        }
+        assert!(load(&loader, "http://93.184.216.34/ctx").is_ok());
+        assert!(matches!(
+            load(&loader, "http://93.184.216.34/missing"),
+            Err(PolicyError::Loader(_))
+        ));
+
+        // Preloaded documents are served even without network.
+        loader.preload("http://127.0.0.1/ctx", json!({ "@context": {} }));
+        loader.set_network_enabled(false);
+        assert!(load(&loader, "http://127.0.0.1/ctx").is_ok());
+        assert!(is_denied(&loader, "http://93.184.216.34/ctx"));
+    }
+
+    #[test]
+    fn allowlists() {
+        let iris = [
+            "https://example.com/ctx",
+            "https://example.org/allowed/ctx",
+            "https://example.org/denied/ctx",
+            "https://example.net/ctx",
+            "file:///srv/ctx.jsonld",
+        ];
+        let inner: StaticLoader = iris.iter().map(|&iri| (iri, json!({}))).collect();
+        let mut loader = PolicyLoader::new(inner);
+        loader.set_private_addresses_blocked(false);
+        loader.allow_host("EXAMPLE.com");
+        loader.allow_prefix("https://example.org/allowed/");
+        loader.allow_prefix("file:///srv/");
+        assert!(load(&loader, "https://example.com/ctx").is_ok());
+        assert!(load(&loader, "https://example.org/allowed/ctx").is_ok());
+        assert!(is_denied(&loader, "https://example.org/denied/ctx"));
+        assert!(is_denied(&loader, "https://example.net/ctx"));
+        assert!(is_denied(&loader, "file:///srv/ctx.jsonld"));
+        // Dot segments are removed by HTTP clients.
+        for iri in &[
+            "https://example.org/allowed/../denied/ctx",
+            "https://example.org/allowed/%2e%2e/denied/ctx",
+            "https://example.org/allowed/.%2E/denied/ctx",
+            "https://example.org/allowed/%2E/../denied/ctx",
+        ] {
+            assert!(is_denied(&loader, iri), "iri={:?}", iri);
+        }
+
+        loader.set_allowed_schemes(["https", "FILE"]);
+        assert!(load(&loader, "file:///srv/ctx.jsonld").is_ok());
+    }
+
+    #[test]
+    fn file_scheme() {
+        let iris = ["file:///srv/ctx.jsonld", "http://127.0.0.1/ctx"];
+        let inner: StaticLoader = iris.iter().map(|&iri| (iri, json!({}))).collect();
+        let mut loader = PolicyLoader::new(inner);
+        loader.set_allowed_schemes(["http", "file"]);
+        assert!(load(&loader, "file:///srv/ctx.jsonld").is_ok());
+        assert!(is_denied(&loader, "http://127.0.0.1/ctx"));
+    }
+
+    #[cfg(feature = "http-loader")]
+    #[test]
+    fn check_redirects() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        use crate::test_utils::serve;
+
+        let secret_served = Arc::new(AtomicBool::new(false));
+        let secret_served_handler = secret_served.clone();
+        let origin = serve(2, move |path, _| match path {
+            "/allowed/ctx" => (302, vec!["Location: /secret".to_owned()], String::new()),
+            _ => {
+                secret_served_handler.store(true, Ordering::SeqCst);
+                (
+                    200,
+                    vec!["Content-Type: application/ld+json".to_owned()],
+                    "{}".to_owned(),
+                )
+            }
+        });
+        let mut loader = PolicyLoader::with_http_loader(HttpLoader::new());
+        loader.set_private_addresses_blocked(false);
+        loader.allow_prefix(format!("{}/allowed/", origin));
+
+        assert!(is_denied(&loader, &format!("{}/secret", origin)));
+        let err = load(&loader, &format!("{}/allowed/ctx", origin)).unwrap_err();
+        match err {
+            PolicyError::Loader(e) => assert_eq!(e.code(), crate::ErrorCode::LoadingDocumentFailed),
+            e => panic!("Unexpected error: {}", e),
+        }
+        assert!(!secret_served.load(Ordering::SeqCst));
+    }
+}
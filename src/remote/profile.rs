@@ -85,7 +85,7 @@ impl RequestProfile {
     }
 
     /// Returns an iterator of profiles.
-    fn iter(self) -> impl Iterator<Item = Profile> {
+    pub(crate) fn iter(self) -> impl Iterator<Item = Profile> {
         Profile::variants().filter(move |v| self.contains(*v))
     }
 }
@@ -110,7 +110,7 @@ impl iter::FromIterator<Profile> for RequestProfile {
         T: IntoIterator<Item = Profile>,
     {
         let mut v = RequestProfile::new();
-        v.extend(iter.into_iter());
+        v.extend(iter);
         v
     }
 }
@@ -0,0 +1,418 @@
+//! Transport-independent processing of responses for document loaders.
+//!
+//! See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#loaddocumentcallback>.
+
+use anyhow::anyhow;
+use iri_string::types::{IriReferenceStr, IriStr, IriString};
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorCode},
+    remote::{RemoteDocument, MEDIA_TYPE_JSON, MEDIA_TYPE_JSON_LD},
+};
+
+/// Media type of HTML documents.
+const MEDIA_TYPE_HTML: &str = "text/html";
+
+/// Link relation of JSON-LD context links.
+const REL_CONTEXT: &str = "http://www.w3.org/ns/json-ld#context";
+
+/// Link relation of alternate documents.
+const REL_ALTERNATE: &str = "alternate";
+
+/// Link in an HTTP `Link` header.
+///
+/// See <https://tools.ietf.org/html/rfc8288>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Target IRI reference.
+    target: String,
+    /// Parameters, with lowercased names.
+    params: Vec<(String, String)>,
+}
+
+impl Link {
+    /// Returns the target IRI reference, which is not resolved yet.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the value of the parameter.
+    ///
+    /// Parameter names are case-insensitive.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns true if the link has the relation type.
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.param("rel").is_some_and(|rels| {
+            rels.split_ascii_whitespace()
+                .any(|v| v.eq_ignore_ascii_case(rel))
+        })
+    }
+
+    /// Returns true if the `type` parameter is the media type.
+    fn has_type(&self, media_type: &str) -> bool {
+        self.param("type")
+            .is_some_and(|v| essence(v).eq_ignore_ascii_case(media_type))
+    }
+
+    /// Resolves the target against the base IRI.
+    fn resolve(&self, base: &IriStr) -> Result<IriString, Error> {
+        let target = IriReferenceStr::new(&self.target).map_err(|e| {
+            ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "Invalid link target {:?}: {}",
+                self.target,
+                e
+            ))
+        })?;
+        Ok(target.resolve_against(base.to_absolute()))
+    }
+}
+
+/// Parses values of HTTP `Link` headers.
+///
+/// Malformed links are skipped.
+pub fn parse_link_header(value: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return links;
+        }
+        let target = rest
+            .strip_prefix('<')
+            .and_then(|v| v.find('>').map(|end| (&v[..end], &v[(end + 1)..])));
+        let (target, after) = match target {
+            Some(v) => v,
+            None => {
+                // Skip the malformed link.
+                rest = skip_link(rest);
+                continue;
+            }
+        };
+        rest = after;
+        let mut params = Vec::new();
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            rest = match rest.strip_prefix(';') {
+                Some(v) => v.trim_start_matches(|c: char| c.is_ascii_whitespace()),
+                None => break,
+            };
+            let name_len = rest
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | ';' | ','))
+                .unwrap_or(rest.len());
+            let name = rest[..name_len].to_ascii_lowercase();
+            rest = rest[name_len..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+            let value = match rest.strip_prefix('=') {
+                Some(v) => {
+                    let (value, after) =
+                        parse_param_value(v.trim_start_matches(|c: char| c.is_ascii_whitespace()));
+                    rest = after;
+                    value
+                }
+                None => String::new(),
+            };
+            if !name.is_empty() {
+                params.push((name, value));
+            }
+        }
+        links.push(Link {
+            target: target.trim().to_owned(),
+            params,
+        });
+        rest = skip_link(rest);
+    }
+}
+
+/// Parses a token or a quoted string, and returns it and the rest.
+fn parse_param_value(s: &str) -> (String, &str) {
+    let quoted = match s.strip_prefix('"') {
+        Some(v) => v,
+        None => {
+            let end = s
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, ';' | ','))
+                .unwrap_or(s.len());
+            return (s[..end].to_owned(), &s[end..]);
+        }
+    };
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &quoted[(i + 1)..]),
+            '\\' => value.extend(chars.next().map(|(_, c)| c)),
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+/// Skips the rest of the current link, up to the next `,` outside quoted strings.
+fn skip_link(s: &str) -> &str {
+    let mut in_quote = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quote => escaped = true,
+            '"' => in_quote = !in_quote,
+            ',' if !in_quote => return &s[(i + 1)..],
+            _ => {}
+        }
+    }
+    ""
+}
+
+/// Returns the media type without parameters.
+fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// Returns the value of the parameter of the media type.
+pub(crate) fn media_type_param(media_type: &str, name: &str) -> Option<String> {
+    media_type.split(';').skip(1).find_map(|param| {
+        let (param_name, value) = param.split_once('=')?;
+        if !param_name.trim().eq_ignore_ascii_case(name) {
+            return None;
+        }
+        Some(parse_param_value(value.trim()).0)
+    })
+}
+
+/// Returns true if the media type is `application/json` or has `+json` suffix.
+///
+/// `application/ld+json` is also a JSON media type.
+pub fn is_json_media_type(media_type: &str) -> bool {
+    let essence = essence(media_type).to_ascii_lowercase();
+    essence == MEDIA_TYPE_JSON || essence.ends_with("+json")
+}
+
+/// Action to take for a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseAction {
+    /// The body is a JSON document.
+    Json(JsonResponse),
+    /// The body is an HTML document.
+    ///
+    /// JSON-LD should be extracted by [`extract_json_ld`](fn.extract_json_ld.html).
+    Html,
+    /// The alternate JSON-LD document at the IRI should be loaded instead.
+    Alternate(IriString),
+}
+
+/// Response with a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonResponse {
+    /// Content type.
+    content_type: String,
+    /// Context URL.
+    context_url: Option<IriString>,
+}
+
+impl JsonResponse {
+    /// Returns the content type.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Returns the context URL given by the `Link` header.
+    ///
+    /// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#dom-remotedocument-contexturl>.
+    pub fn context_url(&self) -> Option<&IriStr> {
+        self.context_url.as_deref()
+    }
+
+    /// Creates a remote document with the parsed body.
+    ///
+    /// `document_url` should be the final URL after redirects.
+    pub fn into_remote_document(
+        self,
+        document_url: impl Into<String>,
+        document: Value,
+    ) -> RemoteDocument {
+        let profile = media_type_param(&self.content_type, "profile");
+        let mut builder = RemoteDocument::builder(document_url).content_type(self.content_type);
+        if let Some(context_url) = self.context_url {
+            builder = builder.context_url(context_url);
+        }
+        if let Some(profile) = profile {
+            builder = builder.profile(profile);
+        }
+        builder.json(document)
+    }
+}
+
+/// Decides how to process the response.
+///
+/// `url` is the URL of the response after redirects, `content_type` is the value of the
+/// `Content-Type` header, and `link_headers` are the values of the `Link` headers.
+///
+/// This implements the rules below:
+///
+/// * Non-successful statuses are errors.
+/// * If the response is not JSON and has a `rel="alternate"` link to an `application/ld+json`
+///   document, the alternate document should be loaded.
+/// * JSON documents other than `application/ld+json` may have a context link.
+///   Multiple context links are errors.
+/// * HTML documents should be processed by [`extract_json_ld`](fn.extract_json_ld.html).
+/// * Other media types are errors.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-api-20191112/#loaddocumentcallback>.
+pub fn process_response(
+    url: &IriStr,
+    status: u16,
+    content_type: Option<&str>,
+    link_headers: &[&str],
+) -> Result<ResponseAction, Error> {
+    if !(200..300).contains(&status) {
+        return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+            "Unsuccessful status {} for {:?}",
+            status,
+            url.as_str()
+        )));
+    }
+    let content_type = content_type.unwrap_or_default().trim();
+    let links = link_headers
+        .iter()
+        .flat_map(|v| parse_link_header(v))
+        .collect::<Vec<_>>();
+
+    // Step 3
+    // > If the retrieved resource's `Content-Type` is not `application/json` nor any media type
+    // > with a `+json` suffix as defined in [RFC6839], and the response has an HTTP Link Header
+    // > [RFC8288] using the `alternate` link relation with type `application/ld+json`, set `url`
+    // > to the associated `href` relative to the previous `url` and restart the algorithm from
+    // > step 2, ensuring that `documentUrl` is set to the original `url`.
+    if !is_json_media_type(content_type) {
+        let alternate = links
+            .iter()
+            .find(|link| link.has_rel(REL_ALTERNATE) && link.has_type(MEDIA_TYPE_JSON_LD));
+        if let Some(alternate) = alternate {
+            return Ok(ResponseAction::Alternate(alternate.resolve(url)?));
+        }
+    }
+
+    let essence = essence(content_type).to_ascii_lowercase();
+    if essence == MEDIA_TYPE_HTML {
+        return Ok(ResponseAction::Html);
+    }
+    if !is_json_media_type(&essence) {
+        return Err(ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+            "Unsupported content type {:?} for {:?}",
+            content_type,
+            url.as_str()
+        )));
+    }
+
+    // Step 4
+    // > If the retrieved resource's `Content-Type` is `application/json` or any media type with a
+    // > `+json` suffix as defined in [RFC6839] except `application/ld+json`, and the response has
+    // > an HTTP Link Header [RFC8288] using the `http://www.w3.org/ns/json-ld#context` link
+    // > relation, set `contextUrl` to the associated `href`.
+    // >
+    // > If multiple HTTP Link Headers using the `http://www.w3.org/ns/json-ld#context` link
+    // > relation are found, the promise is rejected with a `JsonLdError` whose code is set to
+    // > `multiple context link headers`.
+    let context_url = if essence == MEDIA_TYPE_JSON_LD {
+        None
+    } else {
+        let mut context_links = links.iter().filter(|link| link.has_rel(REL_CONTEXT));
+        match (context_links.next(), context_links.next()) {
+            (Some(_), Some(_)) => {
+                return Err(ErrorCode::MultipleContextLinkHeaders
+                    .and_source(anyhow!("Multiple context links for {:?}", url.as_str())))
+            }
+            (Some(link), None) => Some(link.resolve(url)?),
+            (None, _) => None,
+        }
+    };
+
+    Ok(ResponseAction::Json(JsonResponse {
+        content_type: content_type.to_owned(),
+        context_url,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Document URL for tests.
+    const URL: &str = "http://example.com/dir/doc";
+
+    /// Processes the response for `URL`.
+    fn process(content_type: &str, links: &[&str]) -> Result<ResponseAction, Error> {
+        let url = IriStr::new(URL).expect("Should never fail: valid IRI");
+        process_response(url, 200, Some(content_type), links)
+    }
+
+    #[test]
+    fn parse_links() {
+        let links = parse_link_header(
+            r#"<ctx.jsonld>; rel="http://www.w3.org/ns/json-ld#context"; type=application/ld+json,
+            broken; rel=alternate, <http://example.com/a>;REL="alternate next";title="a, \"b\"""#,
+        );
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target(), "ctx.jsonld");
+        assert!(links[0].has_rel(REL_CONTEXT));
+        assert_eq!(links[0].param("type"), Some("application/ld+json"));
+        assert_eq!(links[1].target(), "http://example.com/a");
+        assert!(links[1].has_rel("next"));
+        assert_eq!(links[1].param("title"), Some(r#"a, "b""#));
+    }
+
+    #[test]
+    fn process_responses() {
+        let context_link = r#"<../ctx.jsonld>; rel="http://www.w3.org/ns/json-ld#context""#;
+        let alternate_link = r#"<doc.jsonld>; rel="alternate"; type="application/ld+json""#;
+
+        let content_type =
+            r#"application/activity+json; profile="https://www.w3.org/ns/activitystreams""#;
+        match process(content_type, &[context_link]).unwrap() {
+            ResponseAction::Json(response) => {
+                assert_eq!(
+                    response.context_url().map(IriStr::as_str),
+                    Some("http://example.com/ctx.jsonld")
+                );
+                let doc = response.into_remote_document(URL, Value::Null);
+                assert_eq!(doc.context_url(), Some("http://example.com/ctx.jsonld"));
+                assert_eq!(doc.profile(), Some("https://www.w3.org/ns/activitystreams"));
+            }
+            action => panic!("Unexpected action: {:?}", action),
+        }
+        match process("application/ld+json", &[context_link, alternate_link]).unwrap() {
+            ResponseAction::Json(response) => assert_eq!(response.context_url(), None),
+            action => panic!("Unexpected action: {:?}", action),
+        }
+        assert_eq!(
+            process("text/html", &[alternate_link]).unwrap(),
+            ResponseAction::Alternate(
+                IriStr::new("http://example.com/dir/doc.jsonld")
+                    .expect("Should never fail: valid IRI")
+                    .to_owned()
+            )
+        );
+        assert_eq!(process("TEXT/HTML", &[]).unwrap(), ResponseAction::Html);
+
+        for (content_type, links, code) in &[
+            (
+                "application/json",
+                &[context_link, context_link][..],
+                ErrorCode::MultipleContextLinkHeaders,
+            ),
+            ("text/plain", &[], ErrorCode::LoadingDocumentFailed),
+        ] {
+            let err = process(content_type, links).unwrap_err();
+            assert_eq!(err.code(), *code, "content_type={:?}", content_type);
+        }
+        let url = IriStr::new(URL).expect("Should never fail: valid IRI");
+        let err = process_response(url, 404, Some("application/ld+json"), &[]).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+    }
+}
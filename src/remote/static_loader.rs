@@ -0,0 +1,165 @@
+//! In-memory document loader.
+
+use std::{collections::HashMap, iter::FromIterator, sync::Arc};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use iri_string::types::IriStr;
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorCode},
+    remote::{LoadDocumentOptions, LoadRemoteDocument, RemoteDocument, MEDIA_TYPE_JSON_LD},
+};
+
+/// Document loader which serves documents from an in-memory map.
+///
+/// This never accesses the network or the filesystem, so it is suitable for preloading
+/// well-known contexts which must not be fetched at runtime.
+/// Fragments of IRIs are ignored.
+///
+/// ```
+/// use json_ld::remote::StaticLoader;
+/// use serde_json::json;
+///
+/// let mut loader = StaticLoader::new();
+/// loader.insert(
+///     "https://example.com/context.jsonld",
+///     json!({ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }),
+/// );
+/// assert!(loader.contains("https://example.com/context.jsonld"));
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct StaticLoader {
+    /// Documents.
+    documents: HashMap<String, Arc<RemoteDocument>>,
+}
+
+impl StaticLoader {
+    /// Creates a new empty loader.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the JSON-LD document for the IRI.
+    ///
+    /// If the loader already has a document for the IRI, the old one is replaced and returned.
+    pub fn insert(
+        &mut self,
+        iri: impl Into<String>,
+        document: Value,
+    ) -> Option<Arc<RemoteDocument>> {
+        let iri = iri.into();
+        let iri = without_fragment(&iri).to_owned();
+        let document = RemoteDocument::builder(iri.clone())
+            .content_type(MEDIA_TYPE_JSON_LD)
+            .json(document);
+        self.documents.insert(iri, Arc::new(document))
+    }
+
+    /// Removes the document for the IRI.
+    pub fn remove(&mut self, iri: &str) -> Option<Arc<RemoteDocument>> {
+        self.documents.remove(without_fragment(iri))
+    }
+
+    /// Returns true if the loader has the document for the IRI.
+    pub fn contains(&self, iri: &str) -> bool {
+        self.get(iri).is_some()
+    }
+
+    /// Returns the number of the documents.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Returns true if the loader has no documents.
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Returns the document for the IRI.
+    fn get(&self, iri: &str) -> Option<&Arc<RemoteDocument>> {
+        self.documents.get(without_fragment(iri))
+    }
+}
+
+/// Returns the IRI without the fragment.
+fn without_fragment(iri: &str) -> &str {
+    iri.split('#').next().unwrap_or(iri)
+}
+
+impl<S: Into<String>> Extend<(S, Value)> for StaticLoader {
+    fn extend<I: IntoIterator<Item = (S, Value)>>(&mut self, iter: I) {
+        for (iri, document) in iter {
+            self.insert(iri, document);
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, Value)> for StaticLoader {
+    fn from_iter<I: IntoIterator<Item = (S, Value)>>(iter: I) -> Self {
+        let mut loader = Self::new();
+        loader.extend(iter);
+        loader
+    }
+}
+
+#[async_trait]
+impl LoadRemoteDocument for StaticLoader {
+    type Error = Error;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        _options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        self.get(iri.as_str()).cloned().ok_or_else(|| {
+            ErrorCode::LoadingDocumentFailed.and_source(anyhow!(
+                "No static documents for the IRI {:?}",
+                iri.as_str()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{block_on, processor};
+
+    #[test]
+    fn load_static_documents() {
+        let loader: StaticLoader = vec![(
+            "http://example.com/context.jsonld",
+            json!({ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }),
+        )]
+        .into_iter()
+        .collect();
+        assert_eq!(loader.len(), 1);
+        assert!(loader.contains("http://example.com/context.jsonld#frag"));
+        let mut removed = loader.clone();
+        assert!(removed
+            .remove("http://example.com/context.jsonld#frag")
+            .is_some());
+        assert!(removed.is_empty());
+
+        let iri =
+            IriStr::new("http://example.com/other.jsonld").expect("Should never fail: valid IRI");
+        let err = block_on(loader.load(iri, LoadDocumentOptions::new()))
+            .expect_err("Should fail: unknown IRI");
+        assert_eq!(err.code(), ErrorCode::LoadingDocumentFailed);
+
+        let processor = processor().options().clone().build(loader);
+        let expanded = block_on(processor.expand(&json!({
+            "@context": "http://example.com/context.jsonld",
+            "name": "Alice"
+        })))
+        .unwrap();
+        assert_eq!(
+            expanded,
+            json!([{ "http://xmlns.com/foaf/0.1/name": [{ "@value": "Alice" }] }])
+        );
+    }
+}
@@ -1,5 +1,7 @@
 //! JSON-LD syntax related stuff.
 
+use serde_json::Value;
+
 /// Checks whether a string has the form of a keyword.
 ///
 /// > having the form of a keyword (i.e., it matches the ABNF rule `"@"1*ALPHA` from \[RFC5234\]),
@@ -8,3 +10,67 @@
 pub(crate) fn has_form_of_keyword(s: &str) -> bool {
     s.len() >= 2 && s.starts_with('@') && s[1..].bytes().all(|b| b.is_ascii_alphabetic())
 }
+
+/// Checks whether the given value is a value object.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#dfn-value-object>.
+pub(crate) fn is_value_object(v: &Value) -> bool {
+    v.as_object().is_some_and(|map| map.contains_key("@value"))
+}
+
+/// Checks whether the given value is a list object.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#dfn-list-object>.
+pub(crate) fn is_list_object(v: &Value) -> bool {
+    v.as_object().is_some_and(|map| map.contains_key("@list"))
+}
+
+/// Checks whether the given value is a graph object.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#dfn-graph-object>.
+pub(crate) fn is_graph_object(v: &Value) -> bool {
+    v.as_object().is_some_and(|map| {
+        map.contains_key("@graph")
+            && map
+                .keys()
+                .all(|k| matches!(k.as_str(), "@context" | "@graph" | "@id" | "@index"))
+    })
+}
+
+/// Checks whether the given value is a node object.
+///
+/// See <https://www.w3.org/TR/2019/WD-json-ld11-20191112/#dfn-node-object>.
+pub(crate) fn is_node_object(v: &Value) -> bool {
+    v.as_object().is_some_and(|map| {
+        !map.contains_key("@value") && !map.contains_key("@list") && !map.contains_key("@set")
+    })
+}
+
+/// Checks if the given string is a well-formed language tag.
+///
+/// See <https://tools.ietf.org/html/bcp47#section-2.1>.
+pub(crate) fn is_well_formed_language_tag(s: &str) -> bool {
+    let mut subtags = s.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    let is_valid_subtag = |tag: &str, pred: fn(&u8) -> bool| {
+        (1..=8).contains(&tag.len()) && tag.as_bytes().iter().all(pred)
+    };
+    is_valid_subtag(primary, u8::is_ascii_alphabetic)
+        && subtags.all(|tag| is_valid_subtag(tag, u8::is_ascii_alphanumeric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_tag() {
+        assert!(is_well_formed_language_tag("en"));
+        assert!(is_well_formed_language_tag("en-US"));
+        assert!(is_well_formed_language_tag("zh-Hant-TW"));
+        assert!(!is_well_formed_language_tag(""));
+        assert!(!is_well_formed_language_tag("en US"));
+        assert!(!is_well_formed_language_tag("toolongtag"));
+        assert!(!is_well_formed_language_tag("en-"));
+    }
+}
@@ -0,0 +1,165 @@
+//! Utilities for tests.
+
+use std::{collections::HashMap, future::Future, sync::Arc};
+#[cfg(feature = "http-loader")]
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+use async_trait::async_trait;
+use iri_string::types::IriStr;
+use serde_json::Value;
+use thiserror::Error as ThisError;
+
+use crate::{
+    context::Context,
+    processor::{Processor, ProcessorOptions},
+    remote::{LoadDocumentOptions, LoadRemoteDocument, RemoteDocument},
+};
+
+/// Base IRI used by tests.
+pub(crate) const BASE: &str = "http://example.com/doc.jsonld";
+
+/// Loader which never loads any documents.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NoLoader;
+
+/// Error returned by `NoLoader`.
+#[derive(Debug, Clone, ThisError)]
+#[error("Loading remote documents is not allowed in tests: {0}")]
+pub(crate) struct NoLoaderError(pub(crate) String);
+
+#[async_trait]
+impl LoadRemoteDocument for NoLoader {
+    type Error = NoLoaderError;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        _options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        Err(NoLoaderError(iri.as_str().to_owned()))
+    }
+}
+
+/// Loader which loads documents from the map.
+#[derive(Debug, Clone)]
+pub(crate) struct MapLoader(pub(crate) HashMap<String, Value>);
+
+#[async_trait]
+impl LoadRemoteDocument for MapLoader {
+    type Error = NoLoaderError;
+
+    async fn load(
+        &self,
+        iri: &IriStr,
+        _options: LoadDocumentOptions,
+    ) -> Result<Arc<RemoteDocument>, Self::Error> {
+        match self.0.get(iri.as_str()) {
+            Some(document) => Ok(Arc::new(
+                RemoteDocument::builder(iri.as_str()).json(document.clone()),
+            )),
+            None => Err(NoLoaderError(iri.as_str().to_owned())),
+        }
+    }
+}
+
+/// Creates a processor with `NoLoader` and the default options.
+pub(crate) fn processor() -> Processor<NoLoader> {
+    ProcessorOptions::with_base(
+        IriStr::new(BASE)
+            .expect("Should never fail: valid IRI")
+            .to_owned(),
+    )
+    .build(NoLoader)
+}
+
+/// Runs the given future to completion.
+pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    futures_executor::block_on(f)
+}
+
+/// Loads the document with the loader.
+pub(crate) fn load<L: LoadRemoteDocument>(
+    loader: &L,
+    iri: &str,
+) -> Result<Arc<RemoteDocument>, L::Error> {
+    load_with(loader, iri, LoadDocumentOptions::new())
+}
+
+/// Loads the document with the loader and the given options.
+pub(crate) fn load_with<L: LoadRemoteDocument>(
+    loader: &L,
+    iri: &str,
+    options: LoadDocumentOptions,
+) -> Result<Arc<RemoteDocument>, L::Error> {
+    let iri = IriStr::new(iri).expect("Should never fail: valid IRI");
+    block_on(loader.load(iri, options))
+}
+
+/// Creates a context by processing the given local context against an empty context.
+pub(crate) fn context(local_context: Value) -> Context {
+    let base = IriStr::new(BASE).expect("Should never fail: valid IRI");
+    block_on(Context::with_base(base.to_owned()).join_context_value(
+        &processor(),
+        &local_context,
+        base,
+        false,
+    ))
+    .expect("Should never fail: valid context")
+}
+
+/// Starts a local HTTP server which serves the given number of requests.
+///
+/// The handler receives the request path and the `Accept` header, and returns the status,
+/// the headers and the body.
+#[cfg(feature = "http-loader")]
+pub(crate) fn serve<F>(requests: usize, handler: F) -> String
+where
+    F: Fn(&str, &str) -> (u16, Vec<String>, String) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let path = request_line
+                .split(' ')
+                .nth(1)
+                .unwrap_or_default()
+                .to_owned();
+            let mut accept = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("accept") {
+                        accept = value.trim().to_owned();
+                    }
+                }
+            }
+            let (status, headers, body) = handler(&path, &accept);
+            let mut response = format!("HTTP/1.1 {} Status\r\n", status);
+            for header in headers {
+                response.push_str(&header);
+                response.push_str("\r\n");
+            }
+            response.push_str(&format!(
+                "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            ));
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    origin
+}